@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use memo_core::deck::{self, Budget};
+use memo_core::streak::Streak;
+use memo_core::{Config, Deck, Error, scan};
+
+use super::review::{self, DrawArgs, ReviewArgs};
+use crate::format::{self, Format};
+use crate::link::uri;
+use crate::seed::rng;
+
+/// Decks drawn from as one, with what was queued before and what was drawn.
+pub struct Draw {
+    pub decks: Vec<Deck>,
+    pub queues: Vec<Vec<PathBuf>>,
+    pub selected: Vec<Vec<PathBuf>>,
+    pub budget: Budget,
+}
+
+/// Draws from `decks`, all of them if empty, as if they were one deck under
+/// the `[review] files_per_day` quota, saving them unless `dry`.
+pub fn draw(
+    config: &Config,
+    decks: &[String],
+    args: &DrawArgs,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<Draw, Error> {
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.to_vec()
+    };
+    let mut decks = names
+        .iter()
+        .map(|name| args.open_deck(config, name, today))
+        .collect::<Result<Vec<_>, _>>()?;
+    let files_per_day = config
+        .review
+        .files_per_day
+        .unwrap_or_else(|| decks.iter().map(|d| d.settings.files_per_day).sum());
+    let budget = args.budget(config, files_per_day);
+
+    let queues: Vec<Vec<PathBuf>> = decks.iter().map(|d| d.pending()).collect();
+    let selected = deck::draw_all(&mut decks, today, budget, top, &mut rng());
+    if !dry {
+        for deck in &mut decks {
+            deck.save()?;
+        }
+        for (deck, selected) in decks.iter().zip(&selected) {
+            args.sink(config, deck, selected, today)?;
+        }
+    }
+
+    Ok(Draw {
+        decks,
+        queues,
+        selected,
+        budget,
+    })
+}
+
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    args: &ReviewArgs,
+    format: Format,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let Draw {
+        decks,
+        queues,
+        selected,
+        budget,
+    } = draw(config, decks, &args.draw, dry, top, today)?;
+
+    // Every file with its deck and whether it was drawn just now.
+    let rows: Vec<(&str, &PathBuf, bool)> = decks
+        .iter()
+        .zip(queues.iter().zip(&selected))
+        .flat_map(|(deck, (queue, selected))| {
+            queue
+                .iter()
+                .map(|f| (deck.name.as_str(), f, false))
+                .chain(selected.iter().map(|f| (deck.name.as_str(), f, true)))
+        })
+        .collect();
+    args.open
+        .open(config, &rows.iter().map(|(_, f, _)| *f).collect::<Vec<_>>());
+
+    if format == Format::Json {
+        let rows: Vec<_> = rows
+            .iter()
+            .map(|(name, path, drawn)| {
+                let file_name = scan::file_stem(path);
+                let notes: Vec<&str> = decks
+                    .iter()
+                    .find(|d| d.name == *name)
+                    .and_then(|d| d.info(&file_name))
+                    .map(|i| i.notes.iter().map(|n| n.text.as_str()).collect())
+                    .unwrap_or_default();
+                serde_json::json!({
+                    "deck": name,
+                    "file": file_name,
+                    "path": path,
+                    "uri": uri(config, path),
+                    "drawn": drawn,
+                    "notes": notes,
+                })
+            })
+            .collect();
+        format::print_json(&rows);
+        return review::up(rows.len(), None);
+    }
+    if format == Format::Tsv {
+        let rows: Vec<_> = rows
+            .iter()
+            .map(|(deck, path, drawn)| {
+                vec![
+                    uri(config, path),
+                    drawn.to_string(),
+                    deck.to_string(),
+                    scan::file_stem(path),
+                ]
+            })
+            .collect();
+        format::print_tsv(&["uri", "drawn", "deck", "file"], &rows);
+        return review::up(rows.len(), None);
+    }
+
+    let mut current = None;
+    for (name, file, _) in &rows {
+        if current != Some(*name) {
+            if current.is_some() {
+                outln!();
+            }
+            outln!("{}", name);
+            current = Some(*name);
+        }
+        if let Some(deck) = decks.iter().find(|d| d.name == *name) {
+            args.show(config, deck, file);
+        }
+    }
+    if let Budget::Minutes(_, words_per_minute) = budget {
+        let minutes: f64 = rows
+            .iter()
+            .map(|(_, f, _)| scan::reading_minutes(f, words_per_minute))
+            .sum();
+        outln!("about {:.0} minute(s) of reading", minutes);
+    }
+
+    let streak = Streak::load(&Streak::path(&config.revs_dir()));
+    outln!();
+    outln!(
+        "streak: {} day(s), longest {} day(s)",
+        streak.current(today),
+        streak.longest
+    );
+
+    review::up(rows.len(), None)
+}
@@ -0,0 +1,154 @@
+use memo_core::backtest::{self, Outcome, Replay};
+use memo_core::config::SchedulerKind;
+use memo_core::{Config, Error, deck, history};
+
+use crate::format::{self, Format};
+
+/// Compared when no `--scheduler` is given.
+const DEFAULT: [SchedulerKind; 4] = [
+    SchedulerKind::Decay,
+    SchedulerKind::Sm2,
+    SchedulerKind::Fsrs,
+    SchedulerKind::Leitner,
+];
+
+/// Replays the history of `decks`, all of them if empty, under each of
+/// `schedulers` with the settings of every deck otherwise kept.
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    schedulers: &[SchedulerKind],
+    format: Format,
+) -> Result<(), Error> {
+    let mut names: Vec<String> = Vec::new();
+    if decks.is_empty() {
+        names = config.decks();
+    }
+    for name in decks {
+        // Nested decks share the history of their top-level one.
+        let top = deck::top_level(&config.resolve_deck(name)).to_string();
+        if !names.contains(&top) {
+            names.push(top);
+        }
+    }
+    for name in &names {
+        if !config.vault.root.join(name).is_dir() {
+            return Err(Error::NoDeck(name.clone(), config.vault.root.clone()));
+        }
+    }
+    let schedulers = if schedulers.is_empty() {
+        DEFAULT.as_slice()
+    } else {
+        schedulers
+    };
+
+    let logs: Vec<_> = names
+        .iter()
+        .map(|name| {
+            let path = history::path(&config.revs_dir(), name);
+            (config.deck(name), history::load(&path))
+        })
+        .collect();
+    let outcomes: Vec<(SchedulerKind, Outcome)> = schedulers
+        .iter()
+        .map(|&kind| {
+            let mut replay = Replay::default();
+            for (settings, events) in &logs {
+                let mut settings = settings.clone();
+                settings.scheduler = kind;
+                replay.extend(backtest::replay(events, &settings));
+            }
+            (kind, replay.outcome())
+        })
+        .collect();
+
+    if format == Format::Json {
+        let rows: Vec<_> = outcomes
+            .iter()
+            .map(|(kind, outcome)| {
+                let mut row = serde_json::to_value(outcome).unwrap_or_default();
+                row["scheduler"] = kind.to_string().into();
+                row
+            })
+            .collect();
+        format::print_json(&rows);
+        return Ok(());
+    }
+    let optional = |value: Option<f64>, digits: usize| {
+        value.map_or_else(String::new, |v| format!("{:.*}", digits, v))
+    };
+    if format == Format::Tsv {
+        let rows: Vec<_> = outcomes
+            .iter()
+            .map(|(kind, o)| {
+                vec![
+                    kind.to_string(),
+                    o.reviews.to_string(),
+                    format!("{:.3}", o.recall),
+                    optional(o.predicted, 3),
+                    optional(o.log_loss, 3),
+                    optional(o.mean_interval, 1),
+                    optional(o.daily_reviews, 2),
+                ]
+            })
+            .collect();
+        format::print_tsv(
+            &[
+                "scheduler",
+                "reviews",
+                "recall",
+                "predicted",
+                "log_loss",
+                "interval",
+                "daily_reviews",
+            ],
+            &rows,
+        );
+        return Ok(());
+    }
+
+    let events: Vec<_> = logs.iter().flat_map(|(_, e)| e).collect();
+    let (Some(first), Some(last)) = (
+        events.iter().map(|e| e.date).min(),
+        events.iter().map(|e| e.date).max(),
+    ) else {
+        outln!("no reviews recorded in {}", names.join(", "));
+        return Ok(());
+    };
+    let Some((_, any)) = outcomes.first() else {
+        return Ok(());
+    };
+    outln!(
+        "replayed {} review(s) in {} from {} to {}, {} repeated, {:.0}% of those recalled",
+        events.len(),
+        names.join(", "),
+        first,
+        last,
+        any.reviews,
+        any.recall * 100.0
+    );
+    outln!();
+    outln!("scheduler | predicted | log loss | interval      | reviews/day");
+    for (kind, o) in &outcomes {
+        outln!(
+            "{:<9} | {:>9} | {:>8} | {:>13} | {:>11}",
+            kind.to_string(),
+            o.predicted
+                .map_or_else(|| "-".to_string(), |p| format!("{:.0}%", p * 100.0)),
+            o.log_loss
+                .map_or_else(|| "-".to_string(), |l| format!("{:.3}", l)),
+            o.mean_interval
+                .map_or_else(|| "-".to_string(), |i| format!("{:.1} day(s)", i)),
+            o.daily_reviews
+                .map_or_else(|| "-".to_string(), |d| format!("{:.2}", d)),
+        );
+    }
+    outln!();
+    outln!(
+        "predicted: retention expected at the repeated reviews, against the {:.0}% recalled",
+        any.recall * 100.0
+    );
+    outln!("log loss: how unlikely the grades were under it, lower fits better");
+
+    Ok(())
+}
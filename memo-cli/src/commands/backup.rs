@@ -0,0 +1,125 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use clap::Subcommand;
+use memo_core::lock::Lock;
+use memo_core::{Config, Error, store, zip};
+
+/// What `memo backup` does.
+#[derive(Subcommand)]
+pub enum Backup {
+    /// Write the revs dir, review history included, and the config file to
+    /// a zip archive.
+    Create {
+        /// Archive to write, `memo-backup-<DATE>.zip` if omitted.
+        output: Option<PathBuf>,
+    },
+
+    /// Put back the revs files of an archive in the revs dir, those it
+    /// replaces moved aside to `<file>.replaced`. See `--dry` first.
+    Restore {
+        archive: PathBuf,
+
+        /// Also put back the config file.
+        #[arg(long)]
+        config: bool,
+    },
+}
+
+pub fn run(config: &Config, backup: &Backup, dry: bool, today: NaiveDate) -> Result<(), Error> {
+    match backup {
+        Backup::Create { output } => {
+            let output = output
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(format!("memo-backup-{}.zip", today)));
+            create(config, &output)
+        }
+        Backup::Restore {
+            archive,
+            config: with_config,
+        } => restore(config, archive, *with_config, dry),
+    }
+}
+
+fn create(config: &Config, output: &Path) -> Result<(), Error> {
+    let revs_dir = config.revs_dir();
+    let mut paths: Vec<PathBuf> = fs::read_dir(&revs_dir)
+        .map_err(Error::Storage)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && *p != Lock::path(&revs_dir))
+        .collect();
+    paths.sort();
+
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    for path in paths {
+        let name = format!(
+            "revs/{}",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        entries.push((name, fs::read(&path).map_err(Error::Storage)?));
+    }
+    if let Some(path) = Config::path()
+        && let Ok(text) = fs::read(&path)
+    {
+        entries.push(("config.toml".to_string(), text));
+    }
+
+    let refs: Vec<(&str, &[u8])> = entries
+        .iter()
+        .map(|(n, d)| (n.as_str(), d.as_slice()))
+        .collect();
+    let archive = zip::archive(&refs).map_err(Error::Storage)?;
+    fs::write(output, archive).map_err(Error::Storage)?;
+    outln!("wrote {} file(s) to {}", entries.len(), output.display());
+
+    Ok(())
+}
+
+fn restore(config: &Config, archive: &Path, with_config: bool, dry: bool) -> Result<(), Error> {
+    let data = fs::read(archive).map_err(Error::Storage)?;
+    let entries = zip::entries(&data).map_err(|e| {
+        Error::Storage(io::Error::new(
+            e.kind(),
+            format!("{}: {}", archive.display(), e),
+        ))
+    })?;
+
+    let revs_dir = config.revs_dir();
+    let mut restored = 0;
+    for (name, contents) in &entries {
+        let target = match name.strip_prefix("revs/") {
+            // Names reaching outside the revs dir are never written.
+            Some(file) if !file.is_empty() && !file.contains(['/', '\\']) && file != ".." => {
+                revs_dir.join(file)
+            }
+            None if name == "config.toml" && with_config => match Config::path() {
+                Some(path) => path,
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        let current = fs::read(&target).ok();
+        if current.as_ref() == Some(contents) {
+            continue;
+        }
+        outln!("restore {}", target.display());
+        restored += 1;
+        if dry {
+            continue;
+        }
+        if current.is_some() {
+            let moved = store::set_aside(&target, "replaced").map_err(Error::Storage)?;
+            outln!("  moved the old file to {}", moved.display());
+        }
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir).map_err(Error::Storage)?;
+        }
+        store::write_atomic(&target, contents, 0).map_err(Error::Storage)?;
+    }
+    outln!("restored {} file(s) from {}", restored, archive.display());
+
+    Ok(())
+}
@@ -0,0 +1,495 @@
+use clap::{Command, ValueEnum};
+use memo_core::deck::slug;
+use memo_core::{Config, store};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// Names completed by asking `memo __complete`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dynamic {
+    Decks,
+    Files,
+}
+
+impl Dynamic {
+    fn command(self) -> &'static str {
+        match self {
+            Self::Decks => "memo __complete decks 2>/dev/null",
+            Self::Files => "memo __complete files 2>/dev/null",
+        }
+    }
+}
+
+struct Opt {
+    long: String,
+    about: String,
+    takes_value: bool,
+    values: Vec<String>,
+    dynamic: Option<Dynamic>,
+}
+
+struct Sub {
+    name: String,
+    about: String,
+    options: Vec<Opt>,
+    positional: Option<Dynamic>,
+}
+
+/// Prints the completion script of `shell` for `cli`.
+pub fn run(shell: Shell, cli: &Command) {
+    let globals = options(cli, true);
+    let subs: Vec<Sub> = cli
+        .get_subcommands()
+        .filter(|s| !s.is_hide_set())
+        .map(|s| Sub {
+            name: s.get_name().to_string(),
+            about: about(s.get_about()),
+            options: options(s, false),
+            positional: s
+                .get_arguments()
+                .filter(|a| a.is_positional())
+                .find_map(|a| dynamic(a.get_id().as_str())),
+        })
+        .collect();
+
+    let script = match shell {
+        Shell::Bash => bash(&globals, &subs),
+        Shell::Zsh => zsh(&globals, &subs),
+        Shell::Fish => fish(&globals, &subs),
+        Shell::Powershell => powershell(&globals, &subs),
+    };
+    out!("{}", script);
+}
+
+/// Prints the names `memo __complete <what>` stands for: decks and
+/// shortcuts, or the files known to the review data of every deck.
+pub fn names(config: &Config, what: &str) {
+    let mut names = Vec::new();
+    match what {
+        "decks" => {
+            names.extend(config.decks());
+            names.extend(config.shortcuts.keys().cloned());
+        }
+        "files" => {
+            for deck in config.decks() {
+                let storage = store::open(config, &format!("revs-{}", slug(&deck)));
+                names.extend(
+                    storage
+                        .load()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|r| r.file_name),
+                );
+            }
+        }
+        _ => {}
+    }
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        outln!("{}", name);
+    }
+}
+
+fn options(command: &Command, globals: bool) -> Vec<Opt> {
+    command
+        .get_arguments()
+        .filter(|a| !a.is_positional() && !a.is_hide_set() && a.is_global_set() == globals)
+        .filter_map(|a| {
+            // `--open[=TARGET]` never takes the next word.
+            let takes_value = a.get_action().takes_values() && !a.is_require_equals_set();
+            Some(Opt {
+                long: a.get_long()?.to_string(),
+                about: about(a.get_help()),
+                takes_value,
+                values: if takes_value {
+                    a.get_possible_values()
+                        .iter()
+                        .map(|v| v.get_name().to_string())
+                        .collect()
+                } else {
+                    Vec::new()
+                },
+                dynamic: dynamic(a.get_id().as_str()),
+            })
+        })
+        .chain(globals.then(|| Opt {
+            long: "help".to_string(),
+            about: "Print help".to_string(),
+            takes_value: false,
+            values: Vec::new(),
+            dynamic: None,
+        }))
+        .collect()
+}
+
+fn dynamic(id: &str) -> Option<Dynamic> {
+    match id {
+        "deck" | "decks" => Some(Dynamic::Decks),
+        "file" | "files" => Some(Dynamic::Files),
+        _ => None,
+    }
+}
+
+fn about(text: Option<&clap::builder::StyledStr>) -> String {
+    text.map(|t| t.to_string())
+        .unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn longs(options: &[&Opt]) -> String {
+    options
+        .iter()
+        .map(|o| format!("--{}", o.long))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Every option taking a value, once per name.
+fn valued<'a>(globals: &'a [Opt], subs: &'a [Sub]) -> Vec<&'a Opt> {
+    let mut valued: Vec<&Opt> = Vec::new();
+    for o in globals.iter().chain(subs.iter().flat_map(|s| &s.options)) {
+        if o.takes_value && !valued.iter().any(|v| v.long == o.long) {
+            valued.push(o);
+        }
+    }
+
+    valued
+}
+
+fn bash(globals: &[Opt], subs: &[Sub]) -> String {
+    let global_longs = longs(&globals.iter().collect::<Vec<_>>());
+    let valued = valued(globals, subs);
+    let names: Vec<&str> = subs.iter().map(|s| s.name.as_str()).collect();
+
+    let mut out = String::new();
+    out.push_str("_memo() {\n");
+    out.push_str(
+        "    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+    );
+    out.push_str("    local sub=\"\" i\n");
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        case \"${COMP_WORDS[i]}\" in\n");
+    out.push_str(&format!(
+        "            {}) ((i++)) ;;\n",
+        alternatives(&valued)
+    ));
+    out.push_str("            -*) ;;\n");
+    out.push_str("            *) sub=\"${COMP_WORDS[i]}\"; break ;;\n");
+    out.push_str("        esac\n");
+    out.push_str("    done\n\n");
+
+    out.push_str("    case \"$prev\" in\n");
+    let mut free = Vec::new();
+    for o in &valued {
+        let words = match o.dynamic {
+            Some(d) => format!("$({})", d.command()),
+            None if o.values.is_empty() => {
+                free.push(*o);
+                continue;
+            }
+            None => o.values.join(" "),
+        };
+        out.push_str(&format!(
+            "        --{}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n",
+            o.long, words
+        ));
+    }
+    if !free.is_empty() {
+        out.push_str(&format!("        {}) return ;;\n", alternatives(&free)));
+    }
+    out.push_str("    esac\n\n");
+
+    out.push_str("    local opts=\"\" words=\"\"\n");
+    out.push_str("    case \"$sub\" in\n");
+    out.push_str(&format!(
+        "        \"\") opts=\"{}\"; words=\"{} $({})\" ;;\n",
+        global_longs,
+        names.join(" "),
+        Dynamic::Decks.command()
+    ));
+    for sub in subs {
+        let positional = sub
+            .positional
+            .map_or_else(String::new, |d| format!("$({})", d.command()));
+        out.push_str(&format!(
+            "        {}) opts=\"{} {}\"; words=\"{}\" ;;\n",
+            sub.name,
+            longs(&sub.options.iter().collect::<Vec<_>>()),
+            global_longs,
+            positional
+        ));
+    }
+    out.push_str(&format!(
+        "        *) opts=\"{} {}\"; words=\"\" ;;\n",
+        longs(
+            &subs
+                .iter()
+                .find(|s| s.name == "review")
+                .map_or_else(Vec::new, |s| s.options.iter().collect())
+        ),
+        global_longs
+    ));
+    out.push_str("    esac\n\n");
+    out.push_str("    if [[ \"$cur\" == -* ]]; then\n");
+    out.push_str("        COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n");
+    out.push_str("    else\n");
+    out.push_str("        COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n");
+    out.push_str("    fi\n");
+    out.push_str("}\n\n");
+    out.push_str("complete -F _memo memo\n");
+
+    out
+}
+
+fn zsh(globals: &[Opt], subs: &[Sub]) -> String {
+    let global_longs = longs(&globals.iter().collect::<Vec<_>>());
+    let valued = valued(globals, subs);
+
+    let mut out = String::new();
+    out.push_str("#compdef memo\n\n");
+    out.push_str("_memo() {\n");
+    out.push_str("    local cur=${words[CURRENT]} prev=${words[CURRENT-1]} sub=\"\" i\n");
+    out.push_str("    local -a subs opts names\n");
+    out.push_str("    subs=(\n");
+    for sub in subs {
+        out.push_str(&format!(
+            "        '{}:{}'\n",
+            sub.name,
+            quote_zsh(&sub.about)
+        ));
+    }
+    out.push_str("    )\n\n");
+    out.push_str("    for ((i = 2; i < CURRENT; i++)); do\n");
+    out.push_str("        case ${words[i]} in\n");
+    out.push_str(&format!(
+        "            {}) ((i++)) ;;\n",
+        alternatives(&valued)
+    ));
+    out.push_str("            -*) ;;\n");
+    out.push_str("            *) sub=${words[i]}; break ;;\n");
+    out.push_str("        esac\n");
+    out.push_str("    done\n\n");
+
+    out.push_str("    case $prev in\n");
+    let mut free = Vec::new();
+    for o in &valued {
+        let words = match o.dynamic {
+            Some(d) => format!("${{(f)\"$({})\"}}", d.command()),
+            None if o.values.is_empty() => {
+                free.push(*o);
+                continue;
+            }
+            None => o.values.join(" "),
+        };
+        out.push_str(&format!(
+            "        --{}) compadd -- {}; return ;;\n",
+            o.long, words
+        ));
+    }
+    if !free.is_empty() {
+        out.push_str(&format!("        {}) return ;;\n", alternatives(&free)));
+    }
+    out.push_str("    esac\n\n");
+
+    out.push_str("    case $sub in\n");
+    out.push_str(&format!(
+        "        \"\") opts=({}); names=(${{(f)\"$({})\"}}) ;;\n",
+        global_longs,
+        Dynamic::Decks.command()
+    ));
+    for sub in subs {
+        let positional = sub
+            .positional
+            .map_or_else(String::new, |d| format!("${{(f)\"$({})\"}}", d.command()));
+        out.push_str(&format!(
+            "        {}) opts=({} {}); names=({}) ;;\n",
+            sub.name,
+            longs(&sub.options.iter().collect::<Vec<_>>()),
+            global_longs,
+            positional
+        ));
+    }
+    out.push_str("    esac\n\n");
+    out.push_str("    if [[ $cur == -* ]]; then\n");
+    out.push_str("        compadd -- $opts\n");
+    out.push_str("    else\n");
+    out.push_str("        [[ -z $sub ]] && _describe 'command' subs\n");
+    out.push_str("        compadd -- $names\n");
+    out.push_str("    fi\n");
+    out.push_str("}\n\n");
+    out.push_str("compdef _memo memo\n");
+
+    out
+}
+
+fn fish(globals: &[Opt], subs: &[Sub]) -> String {
+    let names: Vec<&str> = subs.iter().map(|s| s.name.as_str()).collect();
+    let condition = |sub: Option<&str>| match sub {
+        None => format!("not __fish_seen_subcommand_from {}", names.join(" ")),
+        Some(name) => format!("__fish_seen_subcommand_from {}", name),
+    };
+    let option = |o: &Opt, sub: Option<&str>| {
+        let mut line = match sub {
+            Some(_) => format!("complete -c memo -n '{}' -l {}", condition(sub), o.long),
+            None => format!("complete -c memo -l {}", o.long),
+        };
+        if o.takes_value {
+            line.push_str(" -r");
+            match o.dynamic {
+                Some(d) => line.push_str(&format!(" -f -a '({})'", d.command())),
+                None if !o.values.is_empty() => {
+                    line.push_str(&format!(" -f -a '{}'", o.values.join(" ")))
+                }
+                None => line.push_str(" -F"),
+            }
+        }
+        if !o.about.is_empty() {
+            line.push_str(&format!(" -d '{}'", quote_single(&o.about)));
+        }
+        line.push('\n');
+        line
+    };
+
+    let mut out = String::new();
+    out.push_str("complete -c memo -f\n");
+    for o in globals {
+        out.push_str(&option(o, None));
+    }
+    for sub in subs {
+        out.push_str(&format!(
+            "complete -c memo -n '{}' -a {} -d '{}'\n",
+            condition(None),
+            sub.name,
+            quote_single(&sub.about)
+        ));
+    }
+    out.push_str(&format!(
+        "complete -c memo -n '{}' -a '({})'\n",
+        condition(None),
+        Dynamic::Decks.command()
+    ));
+    for sub in subs {
+        for o in &sub.options {
+            out.push_str(&option(o, Some(&sub.name)));
+        }
+        if let Some(d) = sub.positional {
+            out.push_str(&format!(
+                "complete -c memo -n '{}' -a '({})'\n",
+                condition(Some(&sub.name)),
+                d.command()
+            ));
+        }
+    }
+
+    out
+}
+
+fn powershell(globals: &[Opt], subs: &[Sub]) -> String {
+    let list = |items: Vec<String>| {
+        items
+            .iter()
+            .map(|i| format!("'{}'", i.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let globals_list = list(globals.iter().map(|o| format!("--{}", o.long)).collect());
+
+    let mut out = String::new();
+    out.push_str("Register-ArgumentCompleter -Native -CommandName memo -ScriptBlock {\n");
+    out.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n\n");
+    out.push_str(&format!("    $globals = @({})\n", globals_list));
+    out.push_str("    $options = @{\n");
+    for sub in subs {
+        out.push_str(&format!(
+            "        '{}' = @({})\n",
+            sub.name,
+            list(
+                sub.options
+                    .iter()
+                    .map(|o| format!("--{}", o.long))
+                    .collect()
+            )
+        ));
+    }
+    out.push_str("    }\n");
+    out.push_str("    $positional = @{\n");
+    for sub in subs {
+        if let Some(d) = sub.positional {
+            let what = match d {
+                Dynamic::Decks => "decks",
+                Dynamic::Files => "files",
+            };
+            out.push_str(&format!("        '{}' = '{}'\n", sub.name, what));
+        }
+    }
+    out.push_str("    }\n");
+    out.push_str("    $values = @{\n");
+    for o in valued(globals, subs) {
+        let value = match o.dynamic {
+            Some(Dynamic::Decks) => "'decks'".to_string(),
+            Some(Dynamic::Files) => "'files'".to_string(),
+            None => format!("@({})", list(o.values.clone())),
+        };
+        out.push_str(&format!("        '--{}' = {}\n", o.long, value));
+    }
+    out.push_str("    }\n\n");
+    out.push_str("    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() })\n");
+    out.push_str(
+        "    if ($wordToComplete -ne '') { $words = @($words | Select-Object -SkipLast 1) }\n",
+    );
+    out.push_str("    $sub = $null\n");
+    out.push_str("    for ($i = 0; $i -lt $words.Count; $i++) {\n");
+    out.push_str("        if ($values.ContainsKey($words[$i])) { $i++; continue }\n");
+    out.push_str("        if (-not $words[$i].StartsWith('-')) { $sub = $words[$i]; break }\n");
+    out.push_str("    }\n");
+    out.push_str("    $prev = if ($words.Count -gt 0) { $words[-1] } else { '' }\n\n");
+    out.push_str("    $candidates = if ($values.ContainsKey($prev)) {\n");
+    out.push_str("        $v = $values[$prev]\n");
+    out.push_str("        if ($v -is [string]) { memo __complete $v 2>$null } else { $v }\n");
+    out.push_str("    } elseif ($wordToComplete.StartsWith('-')) {\n");
+    out.push_str("        $globals + $(if ($sub) { $options[$sub] } else { @() })\n");
+    out.push_str("    } elseif (-not $sub) {\n");
+    out.push_str(&format!(
+        "        @({}) + @(memo __complete decks 2>$null)\n",
+        list(subs.iter().map(|s| s.name.clone()).collect())
+    ));
+    out.push_str("    } elseif ($positional.ContainsKey($sub)) {\n");
+    out.push_str("        memo __complete $positional[$sub] 2>$null\n");
+    out.push_str("    } else { @() }\n\n");
+    out.push_str(
+        "    $candidates | Where-Object { $_ -like \"$wordToComplete*\" } | ForEach-Object {\n",
+    );
+    out.push_str("        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n");
+    out.push_str("    }\n");
+    out.push_str("}\n");
+
+    out
+}
+
+fn alternatives(options: &[&Opt]) -> String {
+    options
+        .iter()
+        .map(|o| format!("--{}", o.long))
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn quote_single(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn quote_zsh(text: &str) -> String {
+    text.replace('\'', "'\\''").replace(':', "\\:")
+}
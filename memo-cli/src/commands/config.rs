@@ -0,0 +1,186 @@
+use std::env;
+use std::fs;
+use std::io;
+
+use clap::Subcommand;
+use memo_core::config::{self, ConfigError};
+use memo_core::{Config, Error, store, toml};
+use serde_json::Value;
+
+/// What `memo config` does.
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the settings a deck is scheduled by, its own or inherited
+    /// from its parent decks and the rest defaults.
+    Show { deck: String },
+
+    /// Print the value of a key, e.g. `review.files_per_day` or
+    /// `deck.408.files_per_day`, a `MEMO_*` variable's if one sets it and
+    /// the effective one for deck settings.
+    Get { key: String },
+
+    /// Set a key in the config file, e.g. `deck.408.files_per_day 5`,
+    /// keeping the rest of it as is. Refused if the config would no longer
+    /// load.
+    Set { key: String, value: String },
+
+    /// Print where the config file, the revs files and the caches are.
+    Paths,
+}
+
+pub fn run(config: &Config, command: &ConfigCommand, dry: bool) -> Result<(), Error> {
+    match command {
+        ConfigCommand::Show { deck } => show(config, &config.resolve_deck(deck)),
+        ConfigCommand::Get { key } => get(config, key),
+        ConfigCommand::Set { key, value } => set(config, key, value, dry),
+        ConfigCommand::Paths => {
+            if let Some(path) = Config::path() {
+                outln!("config  {}", path.display());
+            }
+            outln!("revs    {}", config.revs_dir().display());
+            outln!("cache   {}", config.cache_dir().display());
+            Ok(())
+        }
+    }
+}
+
+/// `key` split into the path of its table and its name: `deck.<DECK>.<KEY>`
+/// and `decks.<DECK>.<KEY>` name a deck setting whatever dots the deck has,
+/// the deck resolved if a shortcut.
+fn split(config: &Config, key: &str) -> Result<(Vec<String>, String), Error> {
+    let parts: Vec<&str> = key.split('.').collect();
+    let invalid = || {
+        Error::Config(ConfigError::Invalid(format!(
+            "`{}` is not a key, expected one like `review.files_per_day` or `deck.<DECK>.<KEY>`",
+            key
+        )))
+    };
+    match parts.as_slice() {
+        ["deck" | "decks", deck @ .., name] if !deck.is_empty() => Ok((
+            vec!["decks".to_string(), config.resolve_deck(&deck.join("."))],
+            name.to_string(),
+        )),
+        [table @ .., name] if !table.is_empty() && parts.iter().all(|p| !p.is_empty()) => Ok((
+            table.iter().map(|t| t.to_string()).collect(),
+            name.to_string(),
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+fn get(config: &Config, key: &str) -> Result<(), Error> {
+    let (table, name) = split(config, key)?;
+    let value = if table[0] == "decks" {
+        // Set by the deck's table, a parent's, a variable or a default.
+        let settings = serde_json::to_value(config.deck(&table[1])).unwrap_or_default();
+        match settings.get(&name) {
+            Some(value) => value.clone(),
+            None => return Err(unknown(key)),
+        }
+    } else {
+        let text = match Config::path() {
+            Some(path) => fs::read_to_string(path).unwrap_or_default(),
+            None => String::new(),
+        };
+        let document = toml::parse(&text).map_err(|e| {
+            Error::Storage(io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+        })?;
+        let (env_tables, _) = config::env_overrides(env::vars()).map_err(Error::Config)?;
+        let from_env = env_tables
+            .into_iter()
+            .find(|(t, k, _)| [t.as_str()] == table.as_slice() && *k == name)
+            .map(|(_, _, v)| v);
+        from_env.unwrap_or_else(|| {
+            let mut current = &Value::Object(document);
+            for part in table.iter().chain([&name]) {
+                current = current.get(part).unwrap_or(&Value::Null);
+            }
+            current.clone()
+        })
+    };
+
+    match value {
+        Value::Null => outln!("`{}` is not set", key),
+        Value::String(s) => outln!("{}", s),
+        value => outln!("{}", toml::format_value(&value)),
+    }
+
+    Ok(())
+}
+
+fn set(config: &Config, key: &str, raw: &str, dry: bool) -> Result<(), Error> {
+    let (table, name) = split(config, key)?;
+    let Some(path) = Config::path() else {
+        return Err(Error::Config(ConfigError::Invalid(
+            "no config dir found, edit the config file by hand".to_string(),
+        )));
+    };
+    // A TOML value if it parses as one, like `5`, `true` or `["md"]`,
+    // else the string as given.
+    let value = toml::parse(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut v| v.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()));
+
+    let text = fs::read_to_string(&path).unwrap_or_default();
+    let tables: Vec<&str> = table.iter().map(String::as_str).collect();
+    let updated = toml::set(&text, &tables, &name, &value)
+        .map_err(|e| Error::Storage(io::Error::other(format!("{}: {}", path.display(), e))))?;
+
+    // Only a config that still loads is written, so typos in keys and
+    // values of the wrong type are refused.
+    let vault = (key != "vault.root").then(|| config.vault.root.clone());
+    Config::from_text(&path, &updated)
+        .and_then(|c| c.resolve(Some(path.clone()), vault, config.profile.clone()))?;
+
+    outln!("{} = {}", key, toml::format_value(&value));
+    if dry {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(Error::Storage)?;
+    }
+    store::write_atomic(&path, updated.as_bytes(), 1).map_err(Error::Storage)?;
+    outln!("wrote {}", path.display());
+
+    Ok(())
+}
+
+fn unknown(key: &str) -> Error {
+    Error::Config(ConfigError::Invalid(format!("no setting `{}`", key)))
+}
+
+fn show(config: &Config, deck: &str) -> Result<(), Error> {
+    if !config.vault.root.join(deck).is_dir() {
+        return Err(Error::NoDeck(deck.to_string(), config.vault.root.clone()));
+    }
+    let set = config.deck_table(deck);
+    let Ok(Value::Object(settings)) = serde_json::to_value(config.deck(deck)) else {
+        return Ok(());
+    };
+
+    outln!(
+        "[decks.{}]",
+        toml::format_value(&Value::String(deck.to_string()))
+    );
+    let lines: Vec<(String, &str)> = settings
+        .iter()
+        .map(|(key, value)| {
+            let note = if set.contains_key(key) { "" } else { "default" };
+            match value {
+                Value::Null => (format!("# {} unset", key), ""),
+                value => (format!("{} = {}", key, toml::format_value(value)), note),
+            }
+        })
+        .collect();
+    let width = lines.iter().map(|(l, _)| l.len()).max().unwrap_or(0);
+    for (line, note) in lines {
+        if note.is_empty() {
+            outln!("{}", line);
+        } else {
+            outln!("{:<width$}  # {}", line, note);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use memo_core::{Config, Deck, Error, scan, select};
+
+use crate::link::show_link;
+use crate::seed::rng;
+
+/// Draws `count` files of the deck uniformly at random, leaving the review
+/// data, history and queue untouched.
+pub fn run(
+    config: &Config,
+    deck: &str,
+    tags: &[String],
+    count: Option<usize>,
+) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    deck.retain_tags(tags);
+
+    let files: Vec<_> = deck
+        .files
+        .iter()
+        .filter(|f| !deck.is_suspended(&scan::file_stem(f)))
+        .map(|f| (f.clone(), 1))
+        .collect();
+    let count = count.unwrap_or(deck.settings.files_per_day);
+    for file in select::random(&files, count, &mut rng()) {
+        show_link(&file, config);
+    }
+    outln!();
+    outln!("cramming, nothing is recorded");
+
+    Ok(())
+}
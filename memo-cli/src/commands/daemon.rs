@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use memo_core::lock::Lock;
+use memo_core::{Config, Deck, Error, scan, undo};
+
+use super::all::{self, Draw};
+use super::review::DrawArgs;
+use crate::notify::notify;
+
+/// Stays resident, drawing from `decks`, all of them if none are given, once
+/// a day at `[review] draw_at` with a notification of what was drawn, and
+/// looking over the vault every `interval` to take in notes added, renamed
+/// or removed. Only holds the lock of the vault while saving.
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    draw: &DrawArgs,
+    interval: Duration,
+    dry: bool,
+    top: bool,
+) -> Result<(), Error> {
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    };
+    let at = config.review.draw_at().unwrap_or_default();
+    outln!(
+        "watching {} deck(s), drawing daily at {}",
+        names.len(),
+        at.format("%H:%M")
+    );
+
+    let mut known: BTreeMap<String, BTreeSet<PathBuf>> = BTreeMap::new();
+    let mut drawn_on: Option<NaiveDate> = None;
+    loop {
+        let now = Local::now().naive_local();
+        let today = now.date();
+
+        for name in &names {
+            let deck = match Deck::open(config, name) {
+                Ok(deck) => deck,
+                Err(e) => {
+                    eprintln!("{}: {}", stamp(), e);
+                    continue;
+                }
+            };
+            let files: BTreeSet<PathBuf> = deck.files.iter().cloned().collect();
+            let Some(before) = known.insert(name.clone(), files.clone()) else {
+                continue;
+            };
+            if before == files {
+                continue;
+            }
+
+            for file in files.difference(&before) {
+                outln!("{}: new in `{}`: {}", stamp(), name, scan::file_stem(file));
+            }
+            for file in before.difference(&files) {
+                outln!(
+                    "{}: gone from `{}`: {}",
+                    stamp(),
+                    name,
+                    scan::file_stem(file)
+                );
+            }
+            if !dry {
+                let _lock = Lock::for_saving(config, true)?;
+                undo::begin();
+                // Opened again under the lock, so that nothing saved since is lost.
+                Deck::open(config, name)?.save()?;
+            }
+        }
+
+        if now.time() >= at && drawn_on != Some(today) {
+            drawn_on = Some(today);
+            let drawn = {
+                let _lock = (!dry).then(|| Lock::for_saving(config, true)).transpose()?;
+                undo::begin();
+                all::draw(config, &names, draw, dry, top, today)
+            };
+            match drawn {
+                Ok(drawn) => announce(&drawn),
+                Err(e) => eprintln!("{}: cannot draw: {}", stamp(), e),
+            }
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+/// Prints and notifies what is up for review after a draw.
+fn announce(drawn: &Draw) {
+    let counts: Vec<String> = drawn
+        .decks
+        .iter()
+        .zip(drawn.queues.iter().zip(&drawn.selected))
+        .map(|(deck, (queue, selected))| (deck, queue.len() + selected.len()))
+        .filter(|(_, files)| *files > 0)
+        .map(|(deck, files)| format!("{}: {}", deck.name, files))
+        .collect();
+    if counts.is_empty() {
+        outln!("{}: nothing to review", stamp());
+        return;
+    }
+
+    let body = counts.join(", ");
+    outln!("{}: drew {}", stamp(), body);
+    if let Err(e) = notify("memo: time to review", &body, None) {
+        eprintln!("{}: cannot notify: {}", stamp(), e);
+    }
+}
+
+fn stamp() -> String {
+    Local::now().format("%Y-%m-%d %H:%M").to_string()
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use memo_core::{Config, Deck, Error, Grade, scan, sections, store};
+
+use super::all::{self, Draw};
+use super::review::DrawArgs;
+use crate::link::vault_path;
+
+/// Draws from `decks` as `memo all` does and writes today's queue to the
+/// daily note as a checklist of links, printing it instead if `dry`. Files
+/// reviewed today are ticked, and so are those ticked in the note before.
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    args: &DrawArgs,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let Draw { decks, .. } = all::draw(config, decks, args, dry, top, today)?;
+    let path = path(config, today);
+    let before = fs::read_to_string(&path).unwrap_or_default();
+    let before = entries(&before);
+
+    let mut text = format!("# Review of {}\n", today);
+    let mut files = 0;
+    for deck in &decks {
+        let listed: Vec<&str> = before.iter().map(|e| e.target.as_str()).collect();
+        let queue = queue(deck, today, |f| {
+            listed.contains(&target(config, f).as_str())
+        });
+        if queue.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n## {}\n\n", deck.name));
+        for (file, reviewed) in queue {
+            let target = target(config, &file);
+            let ticked = before.iter().any(|e| e.ticked && e.target == target);
+            let mark = if reviewed || ticked { 'x' } else { ' ' };
+            text.push_str(&format!(
+                "- [{}] [[{}|{}]]\n",
+                mark,
+                target,
+                scan::file_stem(&file)
+            ));
+            files += 1;
+        }
+    }
+
+    if dry {
+        out!("{}", text);
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(Error::Storage)?;
+    }
+    store::write_atomic(&path, text.as_bytes(), 0).map_err(Error::Storage)?;
+    outln!("wrote {} file(s) to {}", files, path.display());
+
+    Ok(())
+}
+
+/// Marks the files ticked in today's daily note as reviewed, those of
+/// `decks` only if any are given.
+pub fn sync(config: &Config, decks: &[String], dry: bool, today: NaiveDate) -> Result<(), Error> {
+    let path = path(config, today);
+    let Ok(text) = fs::read_to_string(&path) else {
+        outln!("no daily note at {}, see `memo daily`", path.display());
+        return Ok(());
+    };
+    let entries = entries(&text);
+    let mut names: Vec<String> = entries
+        .iter()
+        .filter(|e| e.ticked && !e.deck.is_empty())
+        .map(|e| e.deck.clone())
+        .collect();
+    names.dedup();
+    if !decks.is_empty() {
+        let wanted: Vec<String> = decks.iter().map(|d| config.resolve_deck(d)).collect();
+        names.retain(|n| wanted.contains(n));
+    }
+
+    for name in names {
+        let mut deck = Deck::open(config, &name)?;
+        let mut marked = 0;
+        for entry in entries.iter().filter(|e| e.ticked && e.deck == name) {
+            let Some(file) = deck
+                .files
+                .iter()
+                .find(|f| target(config, f) == entry.target)
+            else {
+                eprintln!("no file of {} links to [[{}]]", name, entry.target);
+                continue;
+            };
+            let file_name = scan::file_stem(file);
+            let reviewed = deck
+                .info(&file_name)
+                .is_some_and(|i| i.pending.is_none() && i.last_reviewed == Some(today));
+            if reviewed {
+                continue;
+            }
+            deck.rate(&file_name, Grade::Good, today)?;
+            outln!("done: {}", file_name);
+            marked += 1;
+        }
+        if marked > 0 && !dry {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Today's daily note.
+fn path(config: &Config, today: NaiveDate) -> PathBuf {
+    config.daily_dir().join(format!("{}.md", today))
+}
+
+/// The files still queued and the ones reviewed today that were drawn today
+/// or `listed` in the note, with whether they were reviewed. Skipped files
+/// are left out.
+fn queue(deck: &Deck, today: NaiveDate, listed: impl Fn(&Path) -> bool) -> Vec<(PathBuf, bool)> {
+    let drawn_today = |file_name: &str| {
+        deck.drawn.date == Some(today) && deck.drawn.files.iter().any(|f| f == file_name)
+    };
+    let mut queue: Vec<(PathBuf, bool)> = deck.pending().into_iter().map(|f| (f, false)).collect();
+    for file in &deck.files {
+        let file_name = scan::file_stem(file);
+        let reviewed = deck
+            .info(&file_name)
+            .is_some_and(|i| i.pending.is_none() && i.last_reviewed == Some(today));
+        if reviewed && (drawn_today(&file_name) || listed(file)) {
+            queue.push((file.clone(), true));
+        }
+    }
+
+    queue
+}
+
+/// The link target of `file`, with the heading of an item of a split note.
+fn target(config: &Config, file: &Path) -> String {
+    match sections::split(file) {
+        (note, Some(heading)) => format!("{}#{}", vault_path(config, &note), heading),
+        (note, None) => vault_path(config, &note),
+    }
+}
+
+/// A checkbox of the daily note.
+struct Entry {
+    /// The deck of the heading above it.
+    deck: String,
+    target: String,
+    ticked: bool,
+}
+
+/// The checkboxes of the daily note `text`, under the deck headings.
+fn entries(text: &str) -> Vec<Entry> {
+    let mut deck = String::new();
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        if let Some(name) = line.strip_prefix("## ") {
+            deck = name.trim().to_string();
+            continue;
+        }
+        let line = line.trim_start();
+        let Some(rest) = line
+            .strip_prefix("- [")
+            .or_else(|| line.strip_prefix("* ["))
+        else {
+            continue;
+        };
+        let ticked = rest.starts_with(['x', 'X']);
+        let Some(link) = rest
+            .split_once("[[")
+            .and_then(|(_, link)| link.split_once("]]"))
+            .map(|(link, _)| link)
+        else {
+            continue;
+        };
+        let target = link.split_once('|').map_or(link, |(target, _)| target);
+        entries.push(Entry {
+            deck: deck.clone(),
+            target: target.trim().to_string(),
+            ticked,
+        });
+    }
+
+    entries
+}
@@ -0,0 +1,101 @@
+use std::io;
+
+use chrono::NaiveDate;
+use memo_core::config::ConfigError;
+use memo_core::streak::Streak;
+use memo_core::{Config, Deck, Error, email, scan};
+
+use super::remind::waiting;
+
+/// Overdue files listed at most.
+const TOP_OVERDUE: usize = 10;
+
+/// Prints a summary of `decks`, all of them if none are given: the files up
+/// for review in each, the streak and the most overdue files. Mails it by
+/// `[email]` instead if `email`.
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    email: bool,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    };
+
+    let mut lines = Vec::new();
+    let mut total = 0;
+    let mut overdue = Vec::new();
+    for name in &names {
+        let mut deck = Deck::open(config, name)?;
+        deck.resume(today);
+        let up = waiting(&deck, today).len();
+        total += up;
+        lines.push(format!("  {}: {} file(s)", deck.name, up));
+        for file in &deck.files {
+            let file_name = scan::file_stem(file);
+            if let Some(due) = deck.due(&file_name)
+                && due < today
+                && !deck.is_suspended(&file_name)
+            {
+                overdue.push((due, deck.name.clone(), file_name));
+            }
+        }
+    }
+    overdue.sort();
+
+    let streak = Streak::load(&Streak::path(&config.revs_dir()));
+    let mut body = vec![
+        format!("Up for review on {}: {} file(s)", today, total),
+        String::new(),
+    ];
+    body.extend(lines);
+    body.push(String::new());
+    body.push(format!(
+        "Streak: {} day(s), longest {} day(s)",
+        streak.current(today),
+        streak.longest
+    ));
+    if !overdue.is_empty() {
+        body.push(String::new());
+        body.push(format!("Most overdue, {} file(s) in all:", overdue.len()));
+        for (due, deck, file) in overdue.iter().take(TOP_OVERDUE) {
+            body.push(format!(
+                "  {} in {}, due {} ({} day(s) ago)",
+                file,
+                deck,
+                due,
+                (today - *due).num_days()
+            ));
+        }
+    }
+    let body = body.join("\n");
+
+    if !email {
+        outln!("{}", body);
+        return Ok(());
+    }
+    let Some(settings) = &config.email else {
+        return Err(Error::Config(ConfigError::Invalid(
+            "`--email` needs an [email] table with `url`, `from` and `to`".to_string(),
+        )));
+    };
+    let subject = format!("memo: {} file(s) to review on {}", total, today);
+    if dry {
+        outln!("would mail {} to {}", subject, settings.to.join(", "));
+        return Ok(());
+    }
+    email::send(
+        settings,
+        &subject,
+        &body,
+        &config.revs_dir().join("digest.tmp"),
+    )
+    .map_err(|e| Error::Storage(io::Error::new(e.kind(), format!("cannot send mail: {}", e))))?;
+    outln!("mailed {} to {}", subject, settings.to.join(", "));
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use chrono::NaiveDate;
+use memo_core::deck::slug;
+use memo_core::index::Index;
+use memo_core::store;
+use memo_core::streak::Streak;
+use memo_core::{Config, Error, cards, doctor, history, scan};
+
+/// Checks the saved data of decks, all of them if none are given, asking
+/// whether to repair each problem if `fix`.
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    fix: bool,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    };
+    let mut found = 0;
+    let index = Index::path(&config.cache_dir());
+    let mut cache = Index::load(&index);
+
+    for name in &names {
+        let files = scan::note_files(
+            &config.vault.root.join(name),
+            &config.ignore(),
+            &config.deck(name).extensions,
+            &mut cache,
+        );
+        let stems: HashSet<String> = files.iter().map(|f| scan::file_stem(f)).collect();
+        for (stem, paths) in scan::collisions(&files) {
+            found += 1;
+            outln!(
+                "{}: `{}` names {} files, which share review data: {}",
+                name,
+                stem,
+                paths.len(),
+                paths
+                    .iter()
+                    .map(|p| p
+                        .strip_prefix(&config.vault.root)
+                        .unwrap_or(p)
+                        .display()
+                        .to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        let ids: HashSet<String> = cards::collect(&files).into_iter().map(|c| c.id).collect();
+
+        for (key, known) in [
+            (format!("revs-{}", slug(name)), stems),
+            (format!("cards-{}", slug(name)), ids),
+        ] {
+            let storage = store::open(config, &key);
+            let entries = match storage.load() {
+                Ok(entries) => entries,
+                Err(e) => {
+                    found += 1;
+                    outln!("{}: {}", key, e);
+                    if fix && !dry && confirm("restore it from a backup?") {
+                        restore(config, &key, &storage.location())?;
+                    }
+                    continue;
+                }
+            };
+
+            let problems = doctor::check(&entries, &known, today);
+            found += problems.len();
+            let fixes: Vec<_> = problems
+                .into_iter()
+                .filter(|p| {
+                    outln!("{}: {}", key, p);
+                    fix && confirm("fix it?")
+                })
+                .collect();
+            if !fixes.is_empty() && !dry {
+                let data = doctor::repair(entries, &fixes, today);
+                storage.save(&data).map_err(Error::Storage)?;
+            }
+        }
+
+        let path = history::path(&config.revs_dir(), name);
+        let bad = history::bad_lines(&path);
+        if bad > 0 {
+            found += 1;
+            outln!("{}: {} line(s) don't parse", path.display(), bad);
+            if fix && !dry && confirm("drop them?") {
+                history::compact(&path).map_err(Error::Storage)?;
+            }
+        }
+    }
+    // A cache that can't be saved is read again next time.
+    let _ = cache.save(&index);
+
+    let path = Streak::path(&config.revs_dir());
+    let corrupt =
+        fs::read_to_string(&path).is_ok_and(|data| serde_json::from_str::<Streak>(&data).is_err());
+    if corrupt {
+        found += 1;
+        outln!("{}: doesn't parse", path.display());
+        if fix && !dry && confirm("reset the streak?") {
+            fs::remove_file(&path).map_err(Error::Storage)?;
+        }
+    }
+
+    if found == 0 {
+        outln!("no problems found");
+    }
+
+    Ok(())
+}
+
+fn restore(config: &Config, key: &str, location: &str) -> Result<(), Error> {
+    let Some(file) = store::revs_file(config, key) else {
+        outln!("cannot restore {}, fix it by hand", location);
+        return Ok(());
+    };
+
+    match store::restore(file.as_ref()).map_err(Error::Storage)? {
+        Some(n) => outln!("restored {} from backup {}", location, n),
+        None => outln!("no usable backup, moved {} aside", location),
+    }
+
+    Ok(())
+}
+
+fn confirm(question: &str) -> bool {
+    out!("  {} [y/N] ", question);
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+}
@@ -0,0 +1,61 @@
+use std::cmp::Ordering;
+
+use chrono::{Days, NaiveDate};
+use memo_core::{Config, Deck, Error, forecast, scan};
+
+use crate::link::show_link;
+
+/// Prints the files of the deck that are overdue, due today and due within
+/// `days` days, by the deck's `due_retention` or `retention` if given.
+pub fn run(
+    config: &Config,
+    deck: &str,
+    days: u32,
+    retention: Option<f64>,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    deck.resume(today);
+    let threshold = retention.or(deck.settings.due_retention);
+    let until = today + Days::new(days.into());
+
+    let mut due: Vec<_> = deck
+        .files
+        .iter()
+        .filter(|f| !deck.is_suspended(&scan::file_stem(f)))
+        .filter_map(|f| {
+            let on = forecast::due_below(&deck, &scan::file_stem(f), threshold, until)?;
+            Some((on, f))
+        })
+        .filter(|(on, _)| *on <= until)
+        .collect();
+    due.sort();
+
+    let groups = [
+        "overdue".to_string(),
+        "due today".to_string(),
+        format!("upcoming, next {} day(s)", days),
+    ];
+    for (group, title) in groups.iter().enumerate() {
+        let files: Vec<_> = due
+            .iter()
+            .filter(|(on, _)| match on.cmp(&today) {
+                Ordering::Less => group == 0,
+                Ordering::Equal => group == 1,
+                Ordering::Greater => group == 2,
+            })
+            .collect();
+        outln!("{}: {} file(s)", title, files.len());
+        for (on, file) in files {
+            let retention = forecast::retention(&deck, &scan::file_stem(file), today);
+            out!(
+                "  {} | {:>4} | ",
+                on,
+                retention.map_or_else(|| "-".to_string(), |r| format!("{:.0}%", r * 100.0))
+            );
+            show_link(file, config);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use chrono::NaiveDate;
+use memo_core::config::SchedulerKind;
+use memo_core::plugin::{self, Kind};
+use memo_core::{Config, Deck, Error, scan};
+
+/// Shows the terms of the weight of every file in `deck`, or only `files`.
+pub fn run(
+    config: &Config,
+    deck: &str,
+    files: &[String],
+    json: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    // Weight plugins only, to show the weights drawn by with every file.
+    let plugins: Vec<_> = plugin::dir()
+        .map(|d| plugin::load(&d))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| p.kind == Kind::Weight)
+        .collect();
+    deck.apply_plugins(&plugins, today)?;
+    for file in files {
+        if deck.info(file).is_none() {
+            return Err(Error::NoFile(file.clone()));
+        }
+    }
+
+    let explanations: Vec<_> = deck
+        .explain(today)
+        .into_iter()
+        .filter(|e| files.is_empty() || files.contains(&scan::file_stem(&e.file)))
+        .collect();
+
+    if json {
+        outln!(
+            "{}",
+            serde_json::to_string_pretty(&explanations).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    match deck.settings.scheduler {
+        SchedulerKind::Decay => outln!("scheduler: decay, rate {}", deck.settings.decay_rate),
+        scheduler => outln!("scheduler: {}", scheduler),
+    }
+    match &deck.settings.formula {
+        Some(formula) if deck.settings.scheduler == SchedulerKind::Formula => {
+            outln!(
+                "weight = ({}) * x, at least {}",
+                formula,
+                deck.settings.minimum_weight
+            )
+        }
+        _ => outln!(
+            "weight = ({} + priority - penalty) * x, at least {}",
+            deck.settings.basic_weight,
+            deck.settings.minimum_weight
+        ),
+    }
+    outln!();
+    outln!("weight | days | retention | overdue | priority | penalty | x    | file");
+    for e in &explanations {
+        let b = &e.breakdown;
+        outln!(
+            "{:>6} | {:>4} | {:>9} | {:>7} | {:>8.1} | {:>7.1} | {:<4} | {}{}{}{}",
+            if e.suspended || e.postponed.is_some() {
+                "-".to_string()
+            } else {
+                e.weight.to_string()
+            },
+            b.days_since
+                .map_or_else(|| "new".to_string(), |d| d.to_string()),
+            b.retention
+                .map_or_else(|| "-".to_string(), |r| format!("{:.0}%", r * 100.0)),
+            b.overdue
+                .map_or_else(|| "-".to_string(), |o| format!("{:.2}", o)),
+            b.priority,
+            b.penalty,
+            e.multiplier,
+            scan::file_stem(&e.file),
+            e.decay_rate
+                .map_or_else(String::new, |r| format!(" (decay rate {})", r)),
+            if e.suspended { " (suspended)" } else { "" },
+            e.postponed
+                .map_or_else(String::new, |d| format!(" (postponed to {})", d)),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use clap::Subcommand;
+use memo_core::cards::CardDeck;
+use memo_core::deck::slug;
+use memo_core::review::ReviewData;
+use memo_core::{Config, Deck, Error, anki, forecast, history, ics, scan, schema, store};
+
+use crate::{format, out};
+
+/// What `memo export` writes.
+#[derive(Subcommand)]
+pub enum Export {
+    /// Write the flashcards of a deck for Anki: an `.apkg` package keeping
+    /// their schedules if the output ends in `.apkg`, else a text file for
+    /// its importer.
+    Anki {
+        deck: String,
+
+        /// File to write, stdout if omitted.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Anki deck to put the cards in, the deck's name with `::` between
+        /// sub-decks if omitted.
+        #[arg(long, value_name = "NAME")]
+        anki_deck: Option<String>,
+    },
+
+    /// Write the forecast reviews of a deck as an iCalendar file, an
+    /// all-day event for each day files are due.
+    Ics {
+        deck: String,
+
+        /// File to write, stdout if omitted.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Number of days to look ahead.
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+
+    /// Write the review data of every file as CSV, or the review history
+    /// with `--history`, for spreadsheets and the like.
+    Csv {
+        /// Only these decks, all of them if omitted.
+        #[arg(long, value_delimiter = ',', value_name = "DECKS")]
+        decks: Vec<String>,
+
+        /// File to write, stdout if omitted.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Write the review history instead, one review a line.
+        #[arg(long)]
+        history: bool,
+    },
+
+    /// Write the review data of a deck as a JSON revs file, whichever the
+    /// storage backend, e.g. to read or `memo merge` a MessagePack one.
+    Json {
+        deck: String,
+
+        /// File to write, stdout if omitted.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+pub fn run(config: &Config, export: &Export, today: NaiveDate) -> Result<(), Error> {
+    match export {
+        Export::Anki {
+            deck,
+            output,
+            anki_deck,
+        } => anki(config, deck, output.as_deref(), anki_deck.as_deref(), today),
+        Export::Ics { deck, output, days } => ics(config, deck, output.as_deref(), *days, today),
+        Export::Csv {
+            decks,
+            output,
+            history,
+        } => csv(config, decks, output.as_deref(), *history),
+        Export::Json { deck, output } => json(config, deck, output.as_deref()),
+    }
+}
+
+fn anki(
+    config: &Config,
+    deck: &str,
+    output: Option<&Path>,
+    anki_deck: Option<&str>,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let deck = Deck::open(config, &config.resolve_deck(deck))?;
+    let cards = CardDeck::open(config, &deck)?;
+    let notes = anki::notes(&deck, &cards);
+    let anki_deck = anki_deck.map_or_else(|| anki::deck_name(&deck.name), str::to_string);
+
+    let apkg = output.is_some_and(|o| o.extension().is_some_and(|e| e == "apkg"));
+    let contents = if apkg {
+        anki::apkg(&notes, &anki_deck, today).map_err(Error::Storage)?
+    } else {
+        anki::tsv(&notes, &anki_deck).into_bytes()
+    };
+
+    let scheduled = notes.iter().filter(|n| n.schedule.is_some()).count();
+    write(output, &contents, || {
+        format!(
+            "{} card(s), {} of them reviewed before, of `{}`",
+            notes.len(),
+            scheduled,
+            deck.name
+        )
+    })
+}
+
+fn ics(
+    config: &Config,
+    deck: &str,
+    output: Option<&Path>,
+    days: u32,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let deck = Deck::open(config, &config.resolve_deck(deck))?;
+    let due = forecast::due_files(&deck, today, days);
+    let calendar = ics::calendar(&deck.name, &due, today);
+
+    write(output, calendar.as_bytes(), || {
+        let events = due.values().filter(|f| !f.is_empty()).count();
+        format!("{} day(s) of reviews of `{}`", events, deck.name)
+    })
+}
+
+fn csv(
+    config: &Config,
+    decks: &[String],
+    output: Option<&Path>,
+    with_history: bool,
+) -> Result<(), Error> {
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    };
+    let date = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+    let number = |n: Option<f64>| n.map(|n| n.to_string()).unwrap_or_default();
+
+    let mut rows = Vec::new();
+    for name in &names {
+        let deck = Deck::open(config, name)?;
+        if with_history {
+            for event in history::load(&deck.history) {
+                rows.push(vec![
+                    event.date.to_string(),
+                    event.deck,
+                    event.file_name,
+                    event.grade.to_string(),
+                    event.duration.map(|d| d.to_string()).unwrap_or_default(),
+                ]);
+            }
+            continue;
+        }
+
+        let mut files: Vec<String> = deck.files.iter().map(|f| scan::file_stem(f)).collect();
+        files.sort();
+        for file_name in files {
+            let Some(info) = deck.info(&file_name) else {
+                continue;
+            };
+            rows.push(vec![
+                deck.name.clone(),
+                file_name.clone(),
+                date(info.last_reviewed),
+                info.review_count.to_string(),
+                date(deck.due(&file_name)),
+                date(info.pending),
+                date(info.postponed),
+                info.pinned.to_string(),
+                deck.is_suspended(&file_name).to_string(),
+                info.leitner_box.map(|b| b.to_string()).unwrap_or_default(),
+                number(info.sm2.as_ref().map(|s| s.ease)),
+                number(info.fsrs.as_ref().map(|s| s.stability)),
+                number(info.fsrs.as_ref().map(|s| s.difficulty)),
+            ]);
+        }
+    }
+
+    let header: &[&str] = if with_history {
+        &["date", "deck", "file", "grade", "duration"]
+    } else {
+        &[
+            "deck",
+            "file",
+            "last_reviewed",
+            "review_count",
+            "due",
+            "pending",
+            "postponed",
+            "pinned",
+            "suspended",
+            "leitner_box",
+            "sm2_ease",
+            "fsrs_stability",
+            "fsrs_difficulty",
+        ]
+    };
+    let what = if with_history { "review(s)" } else { "file(s)" };
+    write(output, format::csv(header, &rows).as_bytes(), || {
+        format!("{} {}", rows.len(), what)
+    })
+}
+
+fn json(config: &Config, deck: &str, output: Option<&Path>) -> Result<(), Error> {
+    let deck = config.resolve_deck(deck);
+    let data: ReviewData = store::open(config, &format!("revs-{}", slug(&deck)))
+        .load()
+        .map_err(Error::Storage)?
+        .into_iter()
+        .map(|i| (i.file_name.clone(), i))
+        .collect();
+    let mut contents = schema::encode(&data).map_err(|e| Error::Storage(e.into()))?;
+    contents.push('\n');
+
+    write(output, contents.as_bytes(), || {
+        format!("{} entries of `{}`", data.len(), deck)
+    })
+}
+
+/// Writes `contents` to `output`, saying what was written, or to stdout.
+fn write(output: Option<&Path>, contents: &[u8], what: impl Fn() -> String) -> Result<(), Error> {
+    match output {
+        Some(path) => {
+            fs::write(path, contents).map_err(Error::Storage)?;
+            eprintln!("wrote {} to {}", what(), path.display());
+        }
+        None => out::write_all(contents),
+    }
+
+    Ok(())
+}
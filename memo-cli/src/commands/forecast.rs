@@ -0,0 +1,71 @@
+use chrono::NaiveDate;
+use memo_core::{Config, Deck, Error, forecast, scan};
+
+/// Widest bar of the chart, in characters.
+const BAR_WIDTH: usize = 40;
+
+/// Prints how many files of the deck fall due on each of the next `days` days.
+pub fn run(config: &Config, deck: &str, days: u32, today: NaiveDate) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    deck.resume(today);
+    if deck
+        .files
+        .iter()
+        .all(|f| deck.due(&scan::file_stem(f)).is_none())
+    {
+        outln!(
+            "no file of {} has a due date under the {} scheduler",
+            deck.name,
+            deck.settings.scheduler
+        );
+        return Ok(());
+    }
+
+    let counts = forecast::due_per_day(&deck, today, days);
+    let max = counts.values().copied().max().unwrap_or(0);
+    let per_day = deck.settings.max_reviews_per_day;
+    for (date, count) in &counts {
+        let bar = "#".repeat((count * BAR_WIDTH).div_ceil(max.max(1)));
+        let over = match per_day {
+            Some(limit) if *count > limit => " (over the daily limit)",
+            _ => "",
+        };
+        let line = format!(
+            "{} | {:>4} | {}{}",
+            date.format("%a %Y-%m-%d"),
+            count,
+            bar,
+            over
+        );
+        outln!("{}", line.trim_end());
+    }
+
+    let total: usize = counts.values().sum();
+    outln!();
+    outln!(
+        "{} review(s) over {} day(s), {:.1} a day on average",
+        total,
+        days,
+        total as f64 / days.max(1) as f64
+    );
+    if let Some((date, count)) = counts
+        .iter()
+        .filter(|(_, c)| **c > 0)
+        .max_by_key(|(d, c)| (**c, std::cmp::Reverse(**d)))
+    {
+        outln!("busiest day: {} with {} file(s)", date, count);
+    }
+    let new = deck
+        .files
+        .iter()
+        .filter(|f| {
+            deck.info(&scan::file_stem(f))
+                .is_some_and(|i| i.last_reviewed.is_none())
+        })
+        .count();
+    if new > 0 {
+        outln!("{} new file(s) not counted", new);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use memo_core::{Config, Error};
+
+use super::queue::queued;
+
+/// Marks files hard, weighing them up by `difficulty_weight`, or no longer
+/// if `!hard`.
+pub fn run(
+    config: &Config,
+    files: &[String],
+    deck: Option<&str>,
+    hard: bool,
+    dry: bool,
+) -> Result<(), Error> {
+    for (mut deck, files) in queued(config, files, deck)? {
+        for file in &files {
+            deck.set_hard(file, hard)?;
+            if hard {
+                outln!("marked hard: {}", file);
+            } else {
+                outln!("no longer hard: {}", file);
+            }
+        }
+        if !dry {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
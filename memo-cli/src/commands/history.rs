@@ -0,0 +1,111 @@
+use chrono::NaiveDate;
+use memo_core::{Config, Deck, Error, history};
+
+use crate::format::{self, Format};
+
+/// Bars of the sparkline, from the shortest interval to the longest.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Prints every review of `file` from the log of its deck, with the days
+/// since the review before, and a sparkline of those intervals.
+pub fn run(
+    config: &Config,
+    file: &str,
+    deck: Option<&str>,
+    format: Format,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let name = match deck {
+        Some(deck) => config.resolve_deck(deck),
+        None => Deck::locate(config, file)?,
+    };
+    let deck = Deck::open(config, &name)?;
+    let events: Vec<_> = history::load(&deck.history)
+        .into_iter()
+        .filter(|e| e.file_name == file)
+        .collect();
+    if events.is_empty() && deck.info(file).is_none() {
+        return Err(Error::NoFile(file.to_string()));
+    }
+
+    let intervals: Vec<Option<i64>> = events
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            i.checked_sub(1)
+                .map(|j| (e.date - events[j].date).num_days())
+        })
+        .collect();
+
+    if format != Format::Plain {
+        let rows: Vec<_> = events.iter().zip(&intervals).collect();
+        if format == Format::Json {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(e, interval)| {
+                    serde_json::json!({
+                        "date": e.date,
+                        "grade": e.grade,
+                        "interval": interval,
+                        "duration": e.duration,
+                    })
+                })
+                .collect();
+            format::print_json(&rows);
+        } else {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(e, interval)| {
+                    vec![
+                        e.date.to_string(),
+                        e.grade.to_string(),
+                        interval.map_or_else(String::new, |i| i.to_string()),
+                    ]
+                })
+                .collect();
+            format::print_tsv(&["date", "grade", "interval"], &rows);
+        }
+        return Ok(());
+    }
+
+    outln!("{} in `{}`, {} review(s)", file, deck.name, events.len());
+    if !events.is_empty() {
+        outln!();
+        outln!("date       | grade | interval");
+        for (event, interval) in events.iter().zip(&intervals) {
+            outln!(
+                "{} | {:<5} | {}",
+                event.date,
+                event.grade.to_string(),
+                interval.map_or_else(|| "-".to_string(), |i| format!("{} day(s)", i))
+            );
+        }
+    }
+
+    let spans: Vec<i64> = intervals.iter().flatten().copied().collect();
+    if !spans.is_empty() {
+        outln!();
+        outln!("intervals: {}", sparkline(&spans));
+    }
+    match deck.due(file) {
+        Some(due) if due > today => outln!(
+            "next review: {} (in {} day(s))",
+            due,
+            (due - today).num_days()
+        ),
+        Some(due) => outln!("next review: {}, due now", due),
+        None => outln!("next review: not scheduled, weighed by the deck"),
+    }
+
+    Ok(())
+}
+
+/// `values` as bars scaled to the largest.
+fn sparkline(values: &[i64]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+
+    values
+        .iter()
+        .map(|&v| BARS[(v.max(0) * (BARS.len() as i64 - 1) / max) as usize])
+        .collect()
+}
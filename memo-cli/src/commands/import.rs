@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use memo_core::cards::CardDeck;
+use memo_core::{Config, Deck, Error, ReviewInfo, anki, frontmatter, scan, sections, sr};
+
+/// What `memo import` reads.
+#[derive(Subcommand)]
+pub enum Import {
+    /// Take over the reviews of an Anki collection: each note reviewed there
+    /// whose field names a file, by its name or its `id`, gets its last
+    /// review and review count unless they are behind.
+    Anki {
+        /// The `collection.anki2` file, in Anki's profile folder.
+        collection: PathBuf,
+
+        /// Field of the Anki notes naming the files, by name or number from
+        /// 1, the first one if omitted.
+        #[arg(long, value_name = "FIELD")]
+        field: Option<String>,
+
+        /// Only files of these decks, all of them if omitted.
+        #[arg(long, value_delimiter = ',', value_name = "DECKS")]
+        decks: Vec<String>,
+    },
+
+    /// Take over the schedules the Spaced Repetition plugin for Obsidian
+    /// left in the frontmatter of notes and in `<!--SR:...-->` comments
+    /// after cards, unless they were reviewed since. Set `write_sr` on a
+    /// deck to keep them up to date after reviews.
+    Sr {
+        /// Only these decks, all of them if omitted.
+        #[arg(long, value_delimiter = ',', value_name = "DECKS")]
+        decks: Vec<String>,
+    },
+}
+
+pub fn run(config: &Config, import: &Import, dry: bool) -> Result<(), Error> {
+    match import {
+        Import::Anki {
+            collection,
+            field,
+            decks,
+        } => anki(config, collection, field.as_deref(), decks, dry),
+        Import::Sr { decks } => sr(config, decks, dry),
+    }
+}
+
+fn anki(
+    config: &Config,
+    collection: &Path,
+    field: Option<&str>,
+    decks: &[String],
+    dry: bool,
+) -> Result<(), Error> {
+    let notes = anki::reviewed(collection).map_err(Error::Storage)?;
+
+    let mut matched = vec![false; notes.len()];
+    for name in names(config, decks) {
+        let mut deck = Deck::open(config, &name)?;
+        let keys = keys(&deck);
+        let mut seeded = 0;
+        for (i, note) in notes.iter().enumerate() {
+            let value = match field {
+                None => note.fields.first().map(|(_, v)| v),
+                Some(field) => note
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .find(|(n, (name, _))| name == field || (n + 1).to_string() == field)
+                    .map(|(_, (_, v))| v),
+            };
+            let Some(file_name) = value.and_then(|v| keys.get(&v.to_lowercase())) else {
+                continue;
+            };
+            matched[i] = true;
+            if deck.seed(file_name, note.reviews, note.last_reviewed)? {
+                seeded += 1;
+            }
+        }
+        if seeded > 0 {
+            outln!("{}: took over the reviews of {} file(s)", deck.name, seeded);
+            if !dry {
+                deck.save()?;
+            }
+        }
+    }
+    let unmatched = matched.iter().filter(|m| !**m).count();
+    if unmatched > 0 {
+        outln!(
+            "{} reviewed Anki note(s) named no file, see `--field`",
+            unmatched
+        );
+    }
+
+    Ok(())
+}
+
+fn sr(config: &Config, decks: &[String], dry: bool) -> Result<(), Error> {
+    for name in names(config, decks) {
+        let mut deck = Deck::open(config, &name)?;
+        let mut files = 0;
+        for file in deck.files.clone() {
+            if sections::split(&file).1.is_some() {
+                continue;
+            }
+            let text = fs::read_to_string(&file).unwrap_or_default();
+            let Some(schedule) = sr::Schedule::of_note(&frontmatter::parse(&text).0) else {
+                continue;
+            };
+            let file_name = scan::file_stem(&file);
+            let scheduler = deck.settings_for(&file_name).scheduler;
+            if let Some(info) = deck.review_data.get_mut(&file_name)
+                && schedule.apply(info, scheduler)
+            {
+                files += 1;
+            }
+        }
+
+        let mut cards = CardDeck::open(config, &deck)?;
+        let mut taken = 0;
+        for card in &cards.cards {
+            let Some(schedule) = sr::Schedule::of_card(card) else {
+                continue;
+            };
+            let info = cards
+                .review_data
+                .entry(card.id.clone())
+                .or_insert_with(|| ReviewInfo::new(card.id.clone()));
+            if schedule.apply(info, cards.settings.scheduler) {
+                taken += 1;
+            }
+        }
+
+        if files + taken > 0 {
+            outln!(
+                "{}: took over the schedules of {} file(s) and {} card(s)",
+                deck.name,
+                files,
+                taken
+            );
+            if !dry {
+                deck.save()?;
+                cards.save()?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn names(config: &Config, decks: &[String]) -> Vec<String> {
+    if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    }
+}
+
+/// Lowercased names and frontmatter `id`s of the files of `deck`, with the
+/// file names they stand for.
+fn keys(deck: &Deck) -> HashMap<String, String> {
+    let mut keys = HashMap::new();
+    for file in &deck.files {
+        let file_name = scan::file_stem(file);
+        let text = fs::read_to_string(sections::split(file).0).unwrap_or_default();
+        if let Some(id) = frontmatter::parse(&text).0.get("id") {
+            keys.insert(id.to_lowercase(), file_name.clone());
+        }
+        keys.insert(file_name.to_lowercase(), file_name);
+    }
+
+    keys
+}
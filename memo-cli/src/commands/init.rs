@@ -0,0 +1,215 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use memo_core::config::{ConfigError, Profile, expand_tilde};
+use memo_core::ignore::Ignore;
+use memo_core::{Config, Error, store, toml, weight};
+use serde_json::Value;
+
+/// Asks for the vault root, its decks and their daily quotas, then writes the
+/// config file and creates the revs dir, unless `dry`. With a `profile` the
+/// vault goes to `[vault.<NAME>]`.
+pub fn run(vault: Option<PathBuf>, profile: Option<String>, dry: bool) -> Result<(), Error> {
+    let Some(path) = Config::path() else {
+        return Err(ConfigError::Invalid("no config dir found, set HOME".to_string()).into());
+    };
+    if path.exists() && !confirm(&format!("{} exists, update it?", path.display())) {
+        outln!("left {} as is", path.display());
+        return Ok(());
+    }
+
+    let profile = profile.or_else(|| env::var("MEMO_PROFILE").ok().filter(|p| !p.is_empty()));
+    let existing = Config::from_file(&path)
+        .ok()
+        .flatten()
+        .map(|mut c| match &profile {
+            Some(name) => c.profiles.remove(name).unwrap_or_default(),
+            None => Profile {
+                vault: c.vault,
+                shortcuts: c.shortcuts,
+                decks: c.decks,
+                deck_tables: c.deck_tables,
+            },
+        });
+    let vault_table: Vec<&str> = match &profile {
+        Some(name) => vec!["vault", name],
+        None => vec!["vault"],
+    };
+
+    let default_root = vault
+        .or_else(|| env::var_os("MEMO_VAULT").map(PathBuf::from))
+        .or_else(|| existing.as_ref().map(|p| p.vault.root.clone()))
+        .filter(|r| !r.as_os_str().is_empty())
+        .or_else(|| env::current_dir().ok().and_then(|d| detect(&d)))
+        .unwrap_or_default();
+    let root = ask("vault root", &default_root.display().to_string());
+    let expanded = expand_tilde(Path::new(&root));
+    if !expanded.is_dir() {
+        return Err(ConfigError::Invalid(format!(
+            "vault root {} is not a directory",
+            expanded.display()
+        ))
+        .into());
+    }
+    if !expanded.join(".obsidian").is_dir() {
+        outln!(
+            "note: no .obsidian dir in {}, set `link_scheme` if it isn't an Obsidian vault",
+            expanded.display()
+        );
+    }
+
+    let default_revs = existing
+        .as_ref()
+        .and_then(|p| p.vault.revs.as_ref())
+        .map_or_else(|| "revs".to_string(), |r| r.display().to_string());
+    let revs = ask("revs dir, relative to the vault root", &default_revs);
+    let subdirs = subdirs(&expanded, &expanded.join(&revs));
+    if subdirs.is_empty() {
+        outln!(
+            "note: {} has no subdirs yet, every one of them will be a deck",
+            expanded.display()
+        );
+    }
+
+    // Rerunning starts from the decks and quotas already set up.
+    let exclude = existing
+        .as_ref()
+        .map(|p| p.vault.exclude.clone())
+        .unwrap_or_default();
+    let ignore = Ignore::load(&expanded, &exclude);
+    let current: Vec<&str> = subdirs
+        .iter()
+        .filter(|s| !ignore.is_ignored(&expanded.join(s)))
+        .map(String::as_str)
+        .collect();
+
+    let mut decks = Vec::new();
+    if !subdirs.is_empty() {
+        outln!("subdirs: {}", subdirs.join(", "));
+        for deck in ask("decks, separated by commas", &current.join(", ")).split(',') {
+            let deck = deck.trim();
+            if deck.is_empty() || decks.iter().any(|(d, _)| d == deck) {
+                continue;
+            }
+            if !subdirs.iter().any(|s| s == deck) {
+                outln!("no subdir {}, skipped", deck);
+                continue;
+            }
+            let files_per_day = existing
+                .as_ref()
+                .and_then(|p| p.decks.get(deck))
+                .map_or(weight::FILES_PER_DAY, |d| d.files_per_day);
+            decks.push((deck.to_string(), quota(deck, files_per_day)));
+        }
+    }
+
+    let mut text = fs::read_to_string(&path).unwrap_or_default();
+    let mut set = |table: &[&str], key: &str, value: Value| -> Result<(), Error> {
+        text = toml::set(&text, table, key, &value)
+            .map_err(|e| Error::Storage(io::Error::other(format!("{}: {}", path.display(), e))))?;
+        Ok(())
+    };
+    set(&vault_table, "root", Value::from(root.as_str()))?;
+    if revs != default_revs || revs != "revs" {
+        set(&vault_table, "revs", Value::from(revs.as_str()))?;
+    }
+    let chosen = |s: &str| decks.iter().any(|(d, _)| format!("/{}", d) == s);
+    let mut excluded: Vec<String> = exclude.into_iter().filter(|e| !chosen(e)).collect();
+    for subdir in &subdirs {
+        let pattern = format!("/{}", subdir);
+        if !chosen(&pattern) && !excluded.contains(&pattern) {
+            excluded.push(pattern);
+        }
+    }
+    if !excluded.is_empty() || existing.is_some_and(|p| !p.vault.exclude.is_empty()) {
+        set(&vault_table, "exclude", Value::from(excluded))?;
+    }
+    for (deck, files_per_day) in &decks {
+        let table = match &profile {
+            Some(name) => vec!["vault", name, "decks", deck],
+            None => vec!["decks", deck],
+        };
+        set(&table, "files_per_day", Value::from(*files_per_day))?;
+    }
+
+    if dry {
+        out!("{}", text);
+        return Ok(());
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(Error::Storage)?;
+    }
+    store::write_atomic(&path, text.as_bytes(), 1).map_err(Error::Storage)?;
+    outln!("wrote {}", path.display());
+    let revs_dir = expanded.join(expand_tilde(Path::new(&revs)));
+    fs::create_dir_all(&revs_dir).map_err(Error::Storage)?;
+    outln!("created {}", revs_dir.display());
+    if let Some((deck, _)) = decks.first() {
+        match &profile {
+            Some(name) => outln!("review with `memo --profile {} {}`", name, deck),
+            None => outln!("review with `memo {}`", deck),
+        }
+    }
+
+    Ok(())
+}
+
+/// The nearest of `dir` and its ancestors with an `.obsidian` dir.
+fn detect(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".obsidian").is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// Top-level subdirs of `root` that could be decks.
+fn subdirs(root: &Path, revs_dir: &Path) -> Vec<String> {
+    let mut subdirs: Vec<String> = fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && p != revs_dir)
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .filter(|n| !n.starts_with('.'))
+        .collect();
+    subdirs.sort();
+
+    subdirs
+}
+
+fn quota(deck: &str, default: usize) -> usize {
+    let default = default.to_string();
+    loop {
+        match ask(&format!("files per day of {}", deck), &default).parse() {
+            Ok(n) if n > 0 => return n,
+            _ => outln!("  expected a whole number above 0"),
+        }
+    }
+}
+
+/// The answer to `question`, `default` if it is left blank or stdin is closed.
+fn ask(question: &str, default: &str) -> String {
+    if default.is_empty() {
+        out!("{}: ", question);
+    } else {
+        out!("{} [{}]: ", question, default);
+    }
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    match io::stdin().lock().read_line(&mut answer) {
+        Ok(n) if n > 0 && !answer.trim().is_empty() => answer.trim().to_string(),
+        _ => default.to_string(),
+    }
+}
+
+fn confirm(question: &str) -> bool {
+    out!("{} [y/N] ", question);
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+}
@@ -0,0 +1,135 @@
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use memo_core::{Config, Deck, Error, scan};
+
+use crate::format::{self, Format};
+use crate::link::{show_link, uri};
+
+/// Order of `memo list`.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum Sort {
+    /// Lightest first.
+    #[default]
+    Weight,
+    /// Least recently reviewed first, never reviewed ones before all.
+    Last,
+    /// Fewest reviews first.
+    Count,
+    Name,
+}
+
+/// Prints the weight, last review and review count of every file of the
+/// deck, held ones grayed out, without changing anything.
+pub fn run(
+    config: &Config,
+    deck: &str,
+    sort: Sort,
+    overdue: bool,
+    new: bool,
+    format: Format,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    deck.resume(today);
+
+    let mut rows: Vec<_> = deck
+        .explain(today)
+        .into_iter()
+        .map(|e| {
+            let info = deck
+                .info(&scan::file_stem(&e.file))
+                .cloned()
+                .unwrap_or_default();
+            (e, info)
+        })
+        .filter(|(e, info)| {
+            let file_name = scan::file_stem(&e.file);
+            (!overdue || deck.due(&file_name).is_some_and(|d| d < today))
+                && (!new || info.last_reviewed.is_none())
+        })
+        .collect();
+    match sort {
+        Sort::Weight => {}
+        Sort::Last => rows.sort_by_key(|(_, info)| info.last_reviewed),
+        Sort::Count => rows.sort_by_key(|(_, info)| info.review_count),
+        Sort::Name => rows.sort_by_key(|(e, _)| scan::file_stem(&e.file)),
+    }
+    // Held files last whatever the order, as the draw leaves them out.
+    rows.sort_by_key(|(e, _)| e.suspended || e.postponed.is_some());
+
+    match format {
+        Format::Json => {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(e, info)| {
+                    let file_name = scan::file_stem(&e.file);
+                    serde_json::json!({
+                        "file": file_name,
+                        "path": e.file,
+                        "uri": uri(config, &e.file),
+                        "weight": (!e.suspended && e.postponed.is_none()).then_some(e.weight),
+                        "last_reviewed": info.last_reviewed,
+                        "review_count": info.review_count,
+                        "suspended": e.suspended,
+                        "postponed": e.postponed,
+                    })
+                })
+                .collect();
+            format::print_json(&rows);
+            return Ok(());
+        }
+        Format::Tsv => {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(e, info)| {
+                    let file_name = scan::file_stem(&e.file);
+                    let held = e.suspended || e.postponed.is_some();
+                    vec![
+                        if held {
+                            String::new()
+                        } else {
+                            e.weight.to_string()
+                        },
+                        info.last_reviewed
+                            .map_or_else(String::new, |d| d.to_string()),
+                        info.review_count.to_string(),
+                        uri(config, &e.file),
+                        file_name,
+                    ]
+                })
+                .collect();
+            format::print_tsv(
+                &["weight", "last_reviewed", "review_count", "uri", "file"],
+                &rows,
+            );
+            return Ok(());
+        }
+        Format::Plain => {}
+    }
+
+    for (e, info) in &rows {
+        let held = e.suspended || e.postponed.is_some();
+        out!(
+            "{}{:>3} | {:>10} | {:>2} | ",
+            if held { "\x1b[90m" } else { "" },
+            if held {
+                "-".to_string()
+            } else {
+                e.weight.to_string()
+            },
+            info.last_reviewed
+                .map_or_else(|| "N/A".to_string(), |date| date.to_string()),
+            info.review_count,
+        );
+        show_link(&e.file, config);
+        if held {
+            out!("\x1b[0m");
+        }
+    }
+
+    if rows.is_empty() {
+        outln!("no files");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use memo_core::review::ReviewData;
+use memo_core::{Config, Error, history, merge, schema, store};
+
+pub fn run(config: &Config, file: &Path, dry: bool) -> Result<(), Error> {
+    let Some(key) = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .filter(|k| k.starts_with("revs-"))
+    else {
+        return Err(Error::Storage(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{}: not a revs file, named `revs-<deck>.json`",
+                file.display()
+            ),
+        )));
+    };
+    let text = fs::read(file).map_err(Error::Storage)?;
+    let theirs = schema::parse(&text, &file.display().to_string()).map_err(Error::Storage)?;
+
+    let storage = store::open(config, &key);
+    let mut ours: ReviewData = storage
+        .load()
+        .map_err(Error::Storage)?
+        .into_iter()
+        .map(|i| (i.file_name.clone(), i))
+        .collect();
+
+    // The deck part of `revs-<deck>`, which its history is named by as well.
+    let slug = &key["revs-".len()..];
+    let history_path = history::path(&config.revs_dir(), slug);
+    let their_history = history::path(file.parent().unwrap_or(Path::new(".")), slug);
+    let our_events = history::load(&history_path);
+    let their_events = history::load(&their_history);
+    let events = merge::events(&our_events, &their_events);
+
+    let merged = merge::merge(&mut ours, theirs, &our_events, &their_events);
+    outln!(
+        "added {}, took {} reviewed later, recounted {} file(s), {} new review(s) in the history",
+        merged.added,
+        merged.taken,
+        merged.counted,
+        events.len() - our_events.len()
+    );
+    if !dry {
+        storage.save(&ours).map_err(Error::Storage)?;
+        if events.len() > our_events.len() {
+            history::write(&history_path, &events).map_err(Error::Storage)?;
+        }
+    }
+
+    Ok(())
+}
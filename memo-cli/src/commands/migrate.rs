@@ -0,0 +1,67 @@
+use std::fs;
+
+use clap::ValueEnum;
+use memo_core::config::Backend;
+use memo_core::{Config, Error, sqlite, store};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Direction {
+    JsonToSqlite,
+    SqliteToJson,
+    JsonToMsgpack,
+    MsgpackToJson,
+}
+
+impl Direction {
+    fn backends(self) -> (Backend, Backend) {
+        match self {
+            Self::JsonToSqlite => (Backend::Json, Backend::Sqlite),
+            Self::SqliteToJson => (Backend::Sqlite, Backend::Json),
+            Self::JsonToMsgpack => (Backend::Json, Backend::Msgpack),
+            Self::MsgpackToJson => (Backend::Msgpack, Backend::Json),
+        }
+    }
+}
+
+/// Copies all review data from one backend to the other, leaving the source
+/// as is. Point `[storage] backend` at the target afterwards.
+pub fn run(config: &Config, direction: Direction, dry: bool) -> Result<(), Error> {
+    let (from_backend, to_backend) = direction.backends();
+    let keys = match from_backend {
+        Backend::Sqlite => sqlite::keys(&config.sqlite_path()).map_err(Error::Storage)?,
+        Backend::Msgpack => file_keys(config, ".msgpack"),
+        _ => file_keys(config, ".json"),
+    };
+
+    for key in keys {
+        let from = store::open_backend(config, from_backend, &key);
+        let to = store::open_backend(config, to_backend, &key);
+
+        let data = from.load().map_err(Error::Storage)?;
+        outln!("{}: {} entries -> {}", key, data.len(), to.location());
+        if !dry {
+            let data = data.into_iter().map(|r| (r.file_name.clone(), r)).collect();
+            to.save(&data).map_err(Error::Storage)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Stems of the `revs-*` and `cards-*` files ending in `extension` in the
+/// revs dir.
+fn file_keys(config: &Config, extension: &str) -> Vec<String> {
+    let mut keys: Vec<String> = fs::read_dir(config.revs_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            let key = name.strip_suffix(extension)?;
+            (key.starts_with("revs-") || key.starts_with("cards-")).then(|| key.to_string())
+        })
+        .collect();
+    keys.sort();
+
+    keys
+}
@@ -0,0 +1,46 @@
+pub mod all;
+pub mod backtest;
+pub mod backup;
+pub mod completions;
+pub mod config;
+pub mod cram;
+pub mod daemon;
+pub mod daily;
+pub mod digest;
+pub mod doctor;
+pub mod due;
+pub mod explain;
+pub mod export;
+pub mod forecast;
+pub mod hard;
+pub mod history;
+pub mod import;
+pub mod init;
+pub mod list;
+pub mod merge;
+pub mod migrate;
+pub mod note;
+pub mod optimize;
+pub mod pause;
+pub mod pin;
+pub mod plugins;
+pub mod prune;
+pub mod queue;
+pub mod quiz;
+pub mod rate;
+pub mod read;
+pub mod rebuild;
+pub mod recover;
+pub mod relink;
+pub mod remind;
+pub mod report;
+pub mod review;
+pub mod schedule;
+pub mod serve;
+pub mod session;
+pub mod stats;
+pub mod streak;
+pub mod suspend;
+pub mod sync;
+pub mod tui;
+pub mod undo;
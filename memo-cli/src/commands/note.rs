@@ -0,0 +1,38 @@
+use chrono::NaiveDate;
+use memo_core::{Config, Error};
+
+use super::queue::queued;
+
+/// Leaves `text` as a note on `file`, shown whenever it is drawn, removes
+/// its notes if `clear`, or else lists them.
+pub fn run(
+    config: &Config,
+    file: &str,
+    text: Option<&str>,
+    deck: Option<&str>,
+    clear: bool,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    for (mut deck, files) in queued(config, &[file.to_string()], deck)? {
+        for file in &files {
+            if text.is_none() && !clear {
+                for note in deck.info(file).map_or(&[][..], |i| &i.notes) {
+                    outln!("{}  {}", note.date, note.text);
+                }
+                continue;
+            }
+
+            deck.annotate(file, text, today)?;
+            match text {
+                Some(_) => outln!("noted: {}", file),
+                None => outln!("cleared notes: {}", file),
+            }
+        }
+        if !dry && (text.is_some() || clear) {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
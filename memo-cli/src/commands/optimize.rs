@@ -0,0 +1,79 @@
+use std::fs;
+
+use memo_core::config::SchedulerKind;
+use memo_core::optimize::{self, MIN_SAMPLES};
+use memo_core::{Config, Deck, Error, history, store, toml, weight};
+use serde_json::Value;
+
+/// Fits the deck's decay rate to its review history and writes it to the
+/// config file unless `dry`.
+pub fn run(config: &Config, deck: &str, dry: bool) -> Result<(), Error> {
+    let deck = Deck::open(config, &config.resolve_deck(deck))?;
+    let samples = optimize::samples(&history::load(&deck.history));
+    let Some(fit) = optimize::fit_decay(&samples) else {
+        outln!(
+            "{} has {} repeated review(s), at least {} are needed",
+            deck.name,
+            samples.len(),
+            MIN_SAMPLES
+        );
+        return Ok(());
+    };
+
+    let current = deck.settings.decay_rate;
+    outln!(
+        "{} review(s), {:.0}% recalled",
+        fit.samples,
+        fit.recall * 100.0
+    );
+    outln!(
+        "decay rate {} -> {} (log-likelihood {:.3} -> {:.3})",
+        current,
+        fit.decay_rate,
+        optimize::log_likelihood(&samples, current),
+        fit.log_likelihood
+    );
+    outln!(
+        "interval {} -> {} day(s)",
+        weight::interval(current),
+        weight::interval(fit.decay_rate)
+    );
+
+    if !matches!(
+        deck.settings.scheduler,
+        SchedulerKind::Decay | SchedulerKind::Formula
+    ) {
+        outln!(
+            "note: {} uses the {} scheduler, which ignores the decay rate",
+            deck.name,
+            deck.settings.scheduler
+        );
+    }
+
+    if dry {
+        return Ok(());
+    }
+    let Some(path) = Config::path() else {
+        outln!(
+            "no config dir found, set `decay_rate = {}` by hand",
+            fit.decay_rate
+        );
+        return Ok(());
+    };
+
+    let text = fs::read_to_string(&path).unwrap_or_default();
+    let value = serde_json::Number::from_f64(fit.decay_rate).map_or(Value::Null, Value::Number);
+    let text = toml::set(&text, &["decks", &deck.name], "decay_rate", &value)
+        .map_err(|e| Error::Storage(std::io::Error::other(format!("{}: {}", path.display(), e))))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(Error::Storage)?;
+    }
+    store::write_atomic(&path, text.as_bytes(), 1).map_err(Error::Storage)?;
+    outln!(
+        "wrote `decay_rate` of [decks.{}] to {}",
+        deck.name,
+        path.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use chrono::NaiveDate;
+use memo_core::pause::{Pause, Pauses};
+use memo_core::{Config, Deck, Error};
+
+/// Records a pause from `from` to `to`, moving the schedules of every deck
+/// right away if it is over. Lists the recorded pauses if `to` is `None`.
+pub fn run(
+    config: &Config,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let path = Pauses::path(&config.revs_dir());
+    let mut pauses = Pauses::load(&path);
+
+    let Some(to) = to else {
+        if pauses.pauses.is_empty() {
+            outln!("no pauses");
+        }
+        for pause in &pauses.pauses {
+            outln!(
+                "{} to {} | {:>3} day(s) | {}",
+                pause.from,
+                pause.to,
+                pause.days(),
+                if pause.applied.is_empty() {
+                    "pending".to_string()
+                } else {
+                    format!("applied to {}", pause.applied.join(", "))
+                }
+            );
+        }
+        return Ok(());
+    };
+
+    let from = from.unwrap_or(today);
+    if to < from {
+        return Err(Error::InvalidPause(from, to));
+    }
+    if pauses.pauses.iter().any(|p| p.from <= to && from <= p.to) {
+        return Err(Error::InvalidPause(from, to));
+    }
+    let mut pause = Pause {
+        from,
+        to,
+        applied: Vec::new(),
+    };
+    outln!("pause from {} to {}, {} day(s)", from, to, pause.days());
+
+    if to < today {
+        for name in config.decks() {
+            let mut deck = Deck::open(config, &name)?;
+            outln!(
+                "{}: moved {} file(s)",
+                deck.name,
+                pause.shift(&mut deck.review_data)
+            );
+            pause.applied.push(deck.name.clone());
+            if !dry {
+                deck.save()?;
+            }
+        }
+    } else {
+        outln!("schedules will move once it is over");
+    }
+
+    pauses.pauses.push(pause);
+    if !dry {
+        pauses.save(&path).map_err(Error::Storage)?;
+    }
+
+    Ok(())
+}
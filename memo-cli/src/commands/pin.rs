@@ -0,0 +1,28 @@
+use memo_core::{Config, Error};
+
+use super::queue::queued;
+
+/// Has files drawn next until reviewed, or no longer if `!pinned`.
+pub fn run(
+    config: &Config,
+    files: &[String],
+    deck: Option<&str>,
+    pinned: bool,
+    dry: bool,
+) -> Result<(), Error> {
+    for (mut deck, files) in queued(config, files, deck)? {
+        for file in &files {
+            deck.pin(file, pinned)?;
+            if pinned {
+                outln!("pinned: {}", file);
+            } else {
+                outln!("unpinned: {}", file);
+            }
+        }
+        if !dry {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use memo_core::Error;
+use memo_core::plugin;
+
+pub fn run() -> Result<(), Error> {
+    let Some(dir) = plugin::dir() else {
+        outln!("no plugins dir, the config dir is unknown");
+        return Ok(());
+    };
+
+    let plugins = plugin::load(&dir);
+    if plugins.is_empty() {
+        outln!(
+            "no plugins, add executables to the filter, weight or sink subdir of {}",
+            dir.display()
+        );
+    }
+    for plugin in plugins {
+        outln!(
+            "{:<7} {:<20} {}",
+            plugin.kind.name(),
+            plugin.name,
+            plugin.path.display()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use memo_core::{Config, Deck, Error};
+
+pub fn run(config: &Config, deck: &str, dry: bool) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    let pruned = deck.prune();
+
+    for name in &pruned {
+        outln!("pruned: {}", name);
+    }
+    outln!("{} orphaned file(s) in {}", pruned.len(), deck.name);
+    if !dry {
+        deck.save()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use memo_core::{Config, Deck, Error, Grade, scan};
+
+/// Marks files as reviewed, or every file in the queue if none are given.
+pub fn done(
+    config: &Config,
+    files: &[String],
+    deck: Option<&str>,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    for (mut deck, files) in queued(config, files, deck)? {
+        for file in &files {
+            deck.rate(file, Grade::Good, today)?;
+            outln!("done: {}", file);
+        }
+        if !dry {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Takes files out of the queue without reviewing them.
+pub fn skip(config: &Config, files: &[String], deck: Option<&str>, dry: bool) -> Result<(), Error> {
+    for (mut deck, files) in queued(config, files, deck)? {
+        for file in &files {
+            deck.skip(file)?;
+            outln!("skipped: {}", file);
+        }
+        if !dry {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Groups `files` by their decks, defaulting to the whole queue of `deck`, or
+/// of every deck if that isn't given either.
+pub(crate) fn queued(
+    config: &Config,
+    files: &[String],
+    deck: Option<&str>,
+) -> Result<Vec<(Deck, Vec<String>)>, Error> {
+    let deck = deck.map(|d| config.resolve_deck(d));
+
+    if files.is_empty() {
+        let names = match deck {
+            Some(deck) => vec![deck],
+            None => config.decks(),
+        };
+        return names
+            .iter()
+            .map(|name| {
+                let deck = Deck::open(config, name)?;
+                let files = deck.pending().iter().map(|f| scan::file_stem(f)).collect();
+                Ok((deck, files))
+            })
+            .collect();
+    }
+
+    let mut grouped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for file in files {
+        let name = match &deck {
+            Some(deck) => deck.clone(),
+            None => Deck::locate(config, file)?,
+        };
+        grouped.entry(name).or_default().push(file.clone());
+    }
+
+    grouped
+        .into_iter()
+        .map(|(name, files)| Ok((Deck::open(config, &name)?, files)))
+        .collect()
+}
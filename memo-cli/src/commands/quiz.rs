@@ -0,0 +1,77 @@
+use std::io::{self, BufRead, Write};
+
+use chrono::NaiveDate;
+use memo_core::cards::CardDeck;
+use memo_core::{Config, Deck, Error, Grade, select};
+
+use crate::seed::rng;
+
+/// Asks the flashcards of a deck one by one, grading each on its own.
+pub fn run(
+    config: &Config,
+    deck: &str,
+    count: usize,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let deck = Deck::open(config, &config.resolve_deck(deck))?;
+    let mut cards = CardDeck::open(config, &deck)?;
+    if cards.cards.is_empty() {
+        outln!("no flashcards in `{}`", deck.name);
+        return Ok(());
+    }
+
+    let weights = cards.weights(today);
+    let selected = if top {
+        select::top(&weights, count.min(weights.len()))
+    } else {
+        select::random(&weights, count, &mut rng())
+    };
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut asked = 0;
+
+    'cards: for (i, card) in selected.iter().enumerate() {
+        outln!();
+        outln!("[{}/{}] {}", i + 1, selected.len(), card.file_name);
+        outln!("Q: {}", card.question);
+        prompt("press enter to show the answer");
+        if lines.next().is_none() {
+            break;
+        }
+        outln!("A: {}", card.answer);
+
+        let grade = loop {
+            prompt("[a]gain [h]ard [g]ood [e]asy, [q]uit");
+            let Some(Ok(line)) = lines.next() else {
+                break 'cards;
+            };
+            match line.trim() {
+                "a" => break Grade::Again,
+                "h" => break Grade::Hard,
+                "g" | "" => break Grade::Good,
+                "e" => break Grade::Easy,
+                "q" => break 'cards,
+                _ => continue,
+            }
+        };
+        let interval = cards.rate(card, grade, today);
+        outln!("{}, next in {} day(s)", grade, interval);
+        asked += 1;
+    }
+
+    outln!();
+    outln!("{} card(s) reviewed", asked);
+    if !dry {
+        cards.save()?;
+    }
+
+    Ok(())
+}
+
+fn prompt(text: &str) {
+    out!("{} > ", text);
+    let _ = io::stdout().flush();
+}
@@ -0,0 +1,24 @@
+use chrono::NaiveDate;
+use memo_core::{Config, Deck, Error, Grade};
+
+pub fn run(
+    config: &Config,
+    file: &str,
+    grade: Grade,
+    deck: Option<&str>,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let name = match deck {
+        Some(deck) => config.resolve_deck(deck),
+        None => Deck::locate(config, file)?,
+    };
+    let mut deck = Deck::open(config, &name)?;
+    let interval = deck.rate(file, grade, today)?;
+    outln!("{}: {}, next review in {} day(s)", file, grade, interval);
+    if !dry {
+        deck.save()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use std::path::{Path, PathBuf};
+
+use memo_core::{Config, Deck, Error, scan, sections};
+
+use crate::markdown;
+
+/// Prints the note `file`, a path or a file of a deck, rendered for the
+/// terminal. Org notes are printed as they are.
+pub fn run(config: &Config, file: &str, deck: Option<&str>) -> Result<(), Error> {
+    let path = if Path::new(file).is_file() {
+        PathBuf::from(file)
+    } else {
+        let name = match deck {
+            Some(deck) => config.resolve_deck(deck),
+            None => Deck::locate(config, file)?,
+        };
+        let deck = Deck::open(config, &name)?;
+        deck.files
+            .into_iter()
+            .find(|f| scan::file_stem(f) == file)
+            .ok_or_else(|| Error::NoFile(file.to_string()))?
+    };
+
+    let text = sections::read(&path);
+    let is_markdown = sections::split(&path)
+        .0
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("md"));
+    if is_markdown {
+        outln!("{}", markdown::render(&text, markdown::color()));
+    } else {
+        outln!("{}", text);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use memo_core::{Config, Deck, Error};
+
+pub fn run(config: &Config, deck: &str, dry: bool) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    let rebuilt = deck.rebuild();
+
+    outln!(
+        "rebuilt {} of {} file(s) from {}",
+        rebuilt,
+        deck.files.len(),
+        deck.history.display()
+    );
+    if !dry {
+        deck.save()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::fs;
+
+use memo_core::config::Backend;
+use memo_core::deck::{slug, top_level};
+use memo_core::store;
+use memo_core::{Config, Error};
+
+/// Salvages the revs files of decks that don't parse, all decks if none are
+/// given: the entries still whole in a file, and those missing from it in
+/// its newest backup that parses, are saved in its place and the file is
+/// moved aside to `<file>.corrupt`.
+pub fn run(config: &Config, decks: &[String], dry: bool) -> Result<(), Error> {
+    if !matches!(config.storage.backend, Backend::Json | Backend::Msgpack) {
+        outln!("only revs files of the json and msgpack backends are recovered");
+        return Ok(());
+    }
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    };
+
+    // A nested deck's review data is that of its top-level deck, its cards
+    // its own.
+    let mut keys: Vec<String> = Vec::new();
+    for name in &names {
+        let name = name.trim_end_matches(['/', '\\']).replace('\\', "/");
+        for key in [
+            format!("revs-{}", slug(top_level(&name))),
+            format!("cards-{}", slug(&name)),
+        ] {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    let mut recovered = 0;
+    for key in &keys {
+        let Some(file) = store::revs_file(config, key) else {
+            continue;
+        };
+        let Ok(text) = fs::read(file.path()) else {
+            continue;
+        };
+        if file.parse(&text).is_ok() {
+            continue;
+        }
+        if file.is_newer(&text) {
+            outln!("{}: written by a newer memo, left as is", key);
+            continue;
+        }
+
+        let mut data = file.salvage(&text);
+        let salvaged = data.len();
+        let mut from_backup = String::new();
+        if let Some((n, entries)) = store::newest_backup(file.as_ref()) {
+            let before = data.len();
+            for info in entries {
+                data.entry(info.file_name.clone()).or_insert(info);
+            }
+            from_backup = format!(", {} more from backup {}", data.len() - before, n);
+        }
+        outln!("{}: salvaged {} entries{}", key, salvaged, from_backup);
+        recovered += 1;
+        if dry {
+            continue;
+        }
+
+        let moved = store::quarantine(file.path()).map_err(Error::Storage)?;
+        let contents = file.encode(&data).map_err(Error::Storage)?;
+        store::write_atomic(file.path(), &contents, 0).map_err(Error::Storage)?;
+        outln!("  moved the file to {}", moved.display());
+    }
+
+    if recovered == 0 {
+        outln!("nothing to recover");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use memo_core::{Config, Deck, Error};
+
+/// Follows renames by stable ID, recording the IDs of every file so that
+/// later renames can be followed too.
+pub fn run(config: &Config, deck: &str, dry: bool) -> Result<(), Error> {
+    let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+    let mut relinked = deck.relinked.clone();
+    relinked.extend(deck.relink());
+
+    for (old, new) in &relinked {
+        outln!("relinked: {} -> {}", old, new);
+    }
+    outln!(
+        "{} file(s) relinked, {} orphan(s) left",
+        relinked.len(),
+        deck.orphans.len()
+    );
+    if !dry {
+        deck.save()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+use chrono::NaiveDate;
+use memo_core::{Config, Deck, Error, forecast, scan};
+
+use super::review;
+use crate::link::uri;
+use crate::notify::notify;
+use crate::open::open;
+
+/// Sends a desktop notification of the files up for review in `decks`, all of
+/// them if none are given, for running from cron or the like. Clicking it
+/// runs `command` through the shell, or else opens the first of the files.
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    command: Option<&str>,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    };
+
+    let mut counts = Vec::new();
+    let mut first = None;
+    for name in &names {
+        let mut deck = Deck::open(config, name)?;
+        deck.resume(today);
+        let waiting = waiting(&deck, today);
+        if waiting.is_empty() {
+            continue;
+        }
+        counts.push(format!("{}: {}", deck.name, waiting.len()));
+        first = first.or_else(|| waiting.first().map(|f| uri(config, f)));
+    }
+    review::up(counts.len(), None)?;
+
+    let title = "memo: time to review";
+    let body = counts.join(", ");
+    outln!("{}", body);
+    if dry {
+        return Ok(());
+    }
+
+    let clicked = notify(title, &body, Some("Start review"))
+        .map_err(|e| Error::Storage(io::Error::new(e.kind(), format!("cannot notify: {}", e))))?;
+    if !clicked {
+        return Ok(());
+    }
+    match command {
+        Some(command) => {
+            let shell = if cfg!(target_os = "windows") {
+                Command::new("cmd").args(["/C", command]).status()
+            } else {
+                Command::new("sh").args(["-c", command]).status()
+            };
+            shell.map_err(Error::Storage)?;
+        }
+        None => {
+            if let Some(uri) = first
+                && let Err(e) = open(&uri)
+            {
+                eprintln!("cannot open {}: {}", uri, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Files of `deck` up for review today: its queue, and unless drawn today
+/// the files due, at least its `files_per_day`.
+pub(crate) fn waiting(deck: &Deck, today: NaiveDate) -> Vec<PathBuf> {
+    let mut files = deck.pending();
+    if deck.drawn.date == Some(today) {
+        return files;
+    }
+
+    let mut due: Vec<_> = deck
+        .files
+        .iter()
+        .filter(|f| !files.contains(f))
+        .filter(|f| {
+            let name = scan::file_stem(f);
+            !deck.is_suspended(&name)
+                && forecast::due_below(deck, &name, deck.settings.due_retention, today)
+                    .is_some_and(|d| d <= today)
+        })
+        .cloned()
+        .collect();
+    let quota = deck.settings.files_per_day.min(deck.files.len());
+    if due.len() < quota {
+        let rest: Vec<_> = deck
+            .files
+            .iter()
+            .filter(|f| !files.contains(f) && !due.contains(f))
+            .filter(|f| !deck.is_suspended(&scan::file_stem(f)))
+            .take(quota - due.len())
+            .cloned()
+            .collect();
+        due.extend(rest);
+    }
+    files.extend(due);
+
+    files
+}
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use clap::Subcommand;
+use memo_core::chart::{self, escape};
+use memo_core::stats::{self, DeckStats};
+use memo_core::streak::Streak;
+use memo_core::{Config, Deck, Error, forecast, history, scan, store};
+
+use crate::link::uri;
+
+/// What `memo report` writes.
+#[derive(Subcommand)]
+pub enum Report {
+    /// Write a self-contained HTML dashboard of every deck: its stats, a
+    /// heatmap of reviews, a forecast and the overdue files.
+    Html {
+        /// Directory to write `index.html` to.
+        #[arg(long, default_value = "report")]
+        out: PathBuf,
+
+        /// Days of the forecast.
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+}
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\
+table{border-collapse:collapse;width:100%}th,td{padding:4px 8px;border-bottom:1px solid #ddd;text-align:right}\
+th:first-child,td:first-child{text-align:left}a{color:#4c78a8}section{margin-bottom:2em}\
+.muted{color:#888}";
+
+pub fn run(config: &Config, report: &Report, dry: bool, today: NaiveDate) -> Result<(), Error> {
+    match report {
+        Report::Html { out, days } => html(config, out, *days, dry, today),
+    }
+}
+
+fn html(config: &Config, out: &Path, days: u32, dry: bool, today: NaiveDate) -> Result<(), Error> {
+    let mut decks = config
+        .decks()
+        .iter()
+        .map(|name| Deck::open(config, name))
+        .collect::<Result<Vec<_>, _>>()?;
+    for deck in &mut decks {
+        deck.resume(today);
+    }
+
+    let stats: Vec<DeckStats> = decks.iter().map(|d| DeckStats::compute(d, today)).collect();
+    let events: Vec<_> = decks
+        .iter()
+        .flat_map(|d| history::load(&d.history))
+        .collect();
+    let mut due: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for deck in &decks {
+        for (day, count) in forecast::due_per_day(deck, today, days) {
+            *due.entry(day).or_insert(0) += count;
+        }
+    }
+    let streak = decks
+        .first()
+        .map(|d| Streak::load(&d.streak))
+        .unwrap_or_default();
+
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <title>memo report {0}</title><style>{1}</style></head><body>\
+         <h1>memo report</h1><p class=\"muted\">{0}, streak {2} day(s), longest {3} day(s)</p>",
+        today,
+        STYLE,
+        streak.current(today),
+        streak.longest
+    );
+
+    page.push_str(
+        "<section><h2>Decks</h2><table><tr><th>deck</th><th>files</th><th>new</th>\
+         <th>today</th><th>week</th><th>avg interval</th><th>overdue</th>\
+         <th>again/hard/good/easy</th></tr>",
+    );
+    for s in &stats {
+        page.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+             <td>{}</td><td>{}/{}/{}/{}</td></tr>",
+            escape(&s.deck),
+            s.files,
+            s.new,
+            s.reviewed_today,
+            s.reviewed_this_week,
+            s.average_interval
+                .map_or_else(|| "N/A".to_string(), |i| format!("{:.1}d", i)),
+            s.overdue,
+            s.grades.again,
+            s.grades.hard,
+            s.grades.good,
+            s.grades.easy
+        ));
+    }
+    page.push_str("</table></section>");
+
+    page.push_str(&format!(
+        "<section><h2>Reviews</h2>{}<p class=\"muted\">{} review(s) in all</p></section>",
+        chart::heatmap(&stats::daily_counts(&events), today),
+        events.len()
+    ));
+    page.push_str(&format!(
+        "<section><h2>Forecast</h2>{}<p class=\"muted\">files due a day over the next {} day(s), \
+         if each is reviewed when due</p></section>",
+        chart::bars(&due, 900, 200).svg(),
+        days
+    ));
+
+    page.push_str("<section><h2>Overdue</h2>");
+    let mut any = false;
+    for deck in &decks {
+        let mut overdue: Vec<(NaiveDate, &PathBuf)> = deck
+            .files
+            .iter()
+            .filter(|f| !deck.is_suspended(&scan::file_stem(f)))
+            .filter_map(|f| Some((deck.due(&scan::file_stem(f))?, f)))
+            .filter(|(due, _)| *due < today)
+            .collect();
+        if overdue.is_empty() {
+            continue;
+        }
+        overdue.sort();
+        any = true;
+        page.push_str(&format!(
+            "<h3>{}</h3><table><tr><th>file</th><th>due</th><th>days over</th></tr>",
+            escape(&deck.name)
+        ));
+        for (due, file) in overdue {
+            page.push_str(&format!(
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                escape(&uri(config, file)),
+                escape(&scan::file_stem(file)),
+                due,
+                (today - due).num_days()
+            ));
+        }
+        page.push_str("</table>");
+    }
+    if !any {
+        page.push_str("<p class=\"muted\">nothing overdue</p>");
+    }
+    page.push_str("</section></body></html>\n");
+
+    let path = out.join("index.html");
+    if dry {
+        outln!("would write {}", path.display());
+        return Ok(());
+    }
+    fs::create_dir_all(out).map_err(Error::Storage)?;
+    store::write_atomic(&path, page.as_bytes(), 0).map_err(Error::Storage)?;
+    outln!("wrote {}", path.display());
+
+    Ok(())
+}
@@ -0,0 +1,347 @@
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use clap::Args;
+use memo_core::config::{OnChange, OpenTarget};
+use memo_core::deck::Budget;
+use memo_core::plugin::{self, Kind, Plugin};
+use memo_core::streak::Streak;
+use memo_core::{Config, Deck, Error, hooks, scan, sections};
+use serde_json::json;
+
+use crate::format::{self, Format};
+use crate::link::{show_link, uri};
+use crate::open::open;
+use crate::seed::rng;
+
+/// What to draw from a deck, shared by `memo review` and `memo tui`.
+#[derive(Args)]
+pub struct DrawArgs {
+    /// Only draw files with this frontmatter tag, repeat for any of several.
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Files to draw, defaults to the deck's `files_per_day`.
+    #[arg(long, value_name = "N")]
+    pub count: Option<usize>,
+
+    /// Draw as many files as can be read in this many minutes instead.
+    #[arg(long, value_name = "N", conflicts_with = "count")]
+    pub minutes: Option<f64>,
+
+    /// Put back the rest of today's draw and draw anew.
+    #[arg(long)]
+    pub redraw: bool,
+
+    /// Leave out the filter, weight and sink plugins.
+    #[arg(long)]
+    pub no_plugins: bool,
+}
+
+/// Arguments of `memo review`.
+#[derive(Args)]
+pub struct ReviewArgs {
+    #[command(flatten)]
+    pub draw: DrawArgs,
+
+    #[command(flatten)]
+    pub open: OpenArgs,
+
+    /// Print the note's `summary` field, or else its first N lines, under
+    /// each link.
+    #[arg(long, value_name = "N")]
+    pub preview: Option<usize>,
+}
+
+impl ReviewArgs {
+    /// Prints `file` of `deck` as a link, with its notes and its preview if
+    /// asked for.
+    pub fn show(&self, config: &Config, deck: &Deck, file: &Path) {
+        show_link(file, config);
+        if let Some(info) = deck.info(&scan::file_stem(file)) {
+            for note in &info.notes {
+                outln!("  note ({}): {}", note.date, note.text);
+            }
+        }
+        if let Some(lines) = self.preview {
+            for line in scan::preview(file, lines) {
+                outln!("  {}", line);
+            }
+        }
+    }
+}
+
+/// Whether and how `memo review` opens the notes it shows.
+#[derive(Args)]
+pub struct OpenArgs {
+    /// Open the queue with the OS opener, by link or as files, defaults to
+    /// `[review] open`.
+    #[arg(
+        long,
+        value_name = "uri|file",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "uri"
+    )]
+    pub open: Option<OpenTarget>,
+
+    /// Don't open anything, whatever the config says.
+    #[arg(long, conflicts_with = "open")]
+    pub no_open: bool,
+
+    /// Seconds between opening two notes, defaults to `[review] open_delay`.
+    #[arg(long, value_name = "SECS")]
+    pub open_delay: Option<f64>,
+}
+
+impl OpenArgs {
+    /// Opens `files` unless neither the flags nor the config ask for it.
+    pub fn open(&self, config: &Config, files: &[&PathBuf]) {
+        let Some(target) = self.open.or(config.review.open).filter(|_| !self.no_open) else {
+            return;
+        };
+        let delay = self.open_delay.unwrap_or(config.review.open_delay).max(0.0);
+
+        // Several items of a split note open it once.
+        let mut opened = Vec::new();
+        for file in files {
+            let target = match target {
+                OpenTarget::Uri => uri(config, file),
+                OpenTarget::File => sections::split(file).0.display().to_string(),
+            };
+            if opened.contains(&target) {
+                continue;
+            }
+            if !opened.is_empty() && delay > 0.0 {
+                thread::sleep(Duration::from_secs_f64(delay));
+            }
+            opened.push(target.clone());
+            if let Err(e) = open(&target) {
+                eprintln!("cannot open {}: {}", scan::file_stem(file), e);
+            }
+        }
+    }
+}
+
+impl DrawArgs {
+    /// Opens `deck` restricted to the wanted files, with how much to draw.
+    pub fn open(
+        &self,
+        config: &Config,
+        deck: &str,
+        today: NaiveDate,
+    ) -> Result<(Deck, Budget), Error> {
+        let deck = self.open_deck(config, deck, today)?;
+        let budget = self.budget(config, deck.settings.files_per_day);
+        if let Budget::Files(count) = budget
+            && (1..count).contains(&deck.files.len())
+            && deck.drawn.date != Some(today)
+        {
+            eprintln!(
+                "note: `{}` has {} file(s), fewer than the {} drawn a day, drawing what there is",
+                deck.name,
+                deck.files.len(),
+                count
+            );
+        }
+
+        Ok((deck, budget))
+    }
+
+    /// Opens `deck` restricted to the wanted files, resuming it from pauses
+    /// and putting back today's draw if asked to.
+    pub fn open_deck(&self, config: &Config, deck: &str, today: NaiveDate) -> Result<Deck, Error> {
+        let mut deck = Deck::open(config, &config.resolve_deck(deck))?;
+        for (pause, moved) in deck.resume(today) {
+            eprintln!(
+                "welcome back: moved {} file(s) by the {} day(s) paused from {} to {}",
+                moved,
+                pause.days(),
+                pause.from,
+                pause.to
+            );
+        }
+        for (stem, paths) in scan::collisions(&deck.files) {
+            eprintln!(
+                "warning: `{}` names {} files of {}, which share review data, see `memo doctor`",
+                stem,
+                paths.len(),
+                deck.name
+            );
+        }
+        if deck.files.is_empty() {
+            eprintln!(
+                "`{}` has no notes yet, add {} files to {} to review them",
+                deck.name,
+                deck.settings
+                    .extensions
+                    .iter()
+                    .map(|e| format!(".{}", e.trim_start_matches('.')))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                deck.dir.display()
+            );
+        }
+        if !deck.rewritten.is_empty() {
+            let done = match deck.settings.on_change {
+                OnChange::Reset => "started over",
+                _ => "boosted until reviewed",
+            };
+            eprintln!(
+                "changed since last seen, {}: {}",
+                done,
+                deck.rewritten.join(", ")
+            );
+        }
+        deck.retain_tags(&self.tags);
+        if self.redraw {
+            deck.redraw(today);
+        }
+        if let Some(command) = &config.hooks.pre_draw {
+            let payload = json!({ "event": "pre_draw", "deck": deck.name, "date": today });
+            hooks::run(command, &payload).map_err(|e| Error::Hook("pre_draw", e))?;
+        }
+        deck.apply_plugins(&self.plugins(), today)?;
+
+        Ok(deck)
+    }
+
+    /// The plugins of the plugins dir, none with `--no-plugins`.
+    pub fn plugins(&self) -> Vec<Plugin> {
+        match plugin::dir().filter(|_| !self.no_plugins) {
+            Some(dir) => plugin::load(&dir),
+            None => Vec::new(),
+        }
+    }
+
+    /// Hands the files just drawn from `deck` to the sink plugins.
+    pub fn sink(
+        &self,
+        config: &Config,
+        deck: &Deck,
+        selected: &[PathBuf],
+        today: NaiveDate,
+    ) -> Result<(), Error> {
+        let sinks: Vec<Plugin> = self
+            .plugins()
+            .into_iter()
+            .filter(|p| p.kind == Kind::Sink)
+            .collect();
+        if sinks.is_empty() || selected.is_empty() {
+            return Ok(());
+        }
+
+        let files: Vec<_> = selected
+            .iter()
+            .map(|f| json!({ "file": scan::file_stem(f), "path": f, "uri": uri(config, f) }))
+            .collect();
+        let payload = json!({ "kind": "sink", "deck": deck.name, "date": today, "files": files });
+        for sink in sinks {
+            sink.sink(&payload)
+                .map_err(|e| Error::Plugin(sink.name.clone(), e))?;
+        }
+
+        Ok(())
+    }
+
+    /// How much to draw, `files_per_day` files unless given.
+    pub fn budget(&self, config: &Config, files_per_day: usize) -> Budget {
+        match self.minutes {
+            Some(minutes) => Budget::Minutes(minutes, config.review.words_per_minute),
+            None => Budget::Files(self.count.unwrap_or(files_per_day)),
+        }
+    }
+}
+
+/// `Ok` if `files` are up for review, [`Error::NothingDue`] in `deck`
+/// otherwise, so that scripts can tell by the exit status.
+pub fn up(files: usize, deck: Option<&str>) -> Result<(), Error> {
+    match files {
+        0 => Err(Error::NothingDue(deck.map(str::to_string))),
+        _ => Ok(()),
+    }
+}
+
+pub fn run(
+    config: &Config,
+    deck: &str,
+    args: &ReviewArgs,
+    format: Format,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let (mut deck, budget) = args.draw.open(config, deck, today)?;
+
+    let queue = deck.pending();
+    let selected = deck.draw(today, budget, top, &mut rng());
+    if !dry {
+        deck.save()?;
+        args.draw.sink(config, &deck, &selected, today)?;
+    }
+    args.open
+        .open(config, &queue.iter().chain(&selected).collect::<Vec<_>>());
+
+    if format != Format::Plain {
+        let rows: Vec<_> = queue
+            .iter()
+            .map(|f| (f, false))
+            .chain(selected.iter().map(|f| (f, true)))
+            .map(|(f, drawn)| {
+                let file_name = scan::file_stem(f);
+                let uri = uri(config, f);
+                let notes: Vec<String> = deck
+                    .info(&file_name)
+                    .map(|i| i.notes.iter().map(|n| n.text.clone()).collect())
+                    .unwrap_or_default();
+                (file_name, f, uri, drawn, notes)
+            })
+            .collect();
+        if format == Format::Json {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(file_name, path, uri, drawn, notes)| {
+                    serde_json::json!({
+                        "file": file_name,
+                        "path": path,
+                        "uri": uri,
+                        "drawn": drawn,
+                        "notes": notes,
+                    })
+                })
+                .collect();
+            format::print_json(&rows);
+        } else {
+            let rows: Vec<_> = rows
+                .into_iter()
+                .map(|(file_name, _, uri, drawn, _)| vec![uri, drawn.to_string(), file_name])
+                .collect();
+            format::print_tsv(&["uri", "drawn", "file"], &rows);
+        }
+        return up(queue.len() + selected.len(), Some(&deck.name));
+    }
+
+    for file in queue.iter().chain(&selected) {
+        args.show(config, &deck, file);
+    }
+    if let Budget::Minutes(_, words_per_minute) = budget {
+        let minutes: f64 = queue
+            .iter()
+            .chain(&selected)
+            .map(|f| scan::reading_minutes(f, words_per_minute))
+            .sum();
+        outln!("about {:.0} minute(s) of reading", minutes);
+    }
+
+    let streak = Streak::load(&deck.streak);
+    outln!();
+    outln!(
+        "streak: {} day(s), longest {} day(s)",
+        streak.current(today),
+        streak.longest
+    );
+
+    up(queue.len() + selected.len(), Some(&deck.name))
+}
@@ -0,0 +1,41 @@
+use chrono::{Days, NaiveDate};
+use memo_core::{Config, Error};
+
+use super::queue::queued;
+
+/// When to make files due.
+#[derive(Debug, Clone, Copy)]
+pub enum When {
+    /// This many days after they are due, or after today if that is later.
+    Later(u64),
+    On(NaiveDate),
+}
+
+/// Moves the next review of files without reviewing them.
+pub fn run(
+    config: &Config,
+    files: &[String],
+    deck: Option<&str>,
+    when: When,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    for (mut deck, files) in queued(config, files, deck)? {
+        for file in &files {
+            if deck.info(file).is_none() {
+                return Err(Error::NoFile(file.clone()));
+            }
+            let on = match when {
+                When::Later(days) => deck.due(file).unwrap_or(today).max(today) + Days::new(days),
+                When::On(date) => date,
+            };
+            deck.schedule(file, on, today)?;
+            outln!("{}: due on {}", file, on);
+        }
+        if !dry {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
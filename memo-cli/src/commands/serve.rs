@@ -0,0 +1,362 @@
+use std::io;
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::{Component, Path};
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use memo_core::lock::Lock;
+use memo_core::stats::DeckStats;
+use memo_core::{Config, Deck, Error, Grade, forecast, scan, undo};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::all::{self, Draw};
+use super::review::DrawArgs;
+use crate::http::{Request, respond};
+use crate::link::uri;
+
+/// Body of `POST /review`.
+#[derive(Deserialize)]
+struct Rating {
+    file: String,
+    #[serde(default = "default_grade")]
+    grade: Grade,
+    deck: Option<String>,
+}
+
+fn default_grade() -> Grade {
+    Grade::Good
+}
+
+/// Serves JSON on `host`:`port` until killed, one request at a time:
+///
+/// - `GET /decks`, the decks of the vault;
+/// - `GET /due?deck=`, their queues and the files due by schedule;
+/// - `POST /draw?deck=&count=`, drawing as `memo review` or `memo all` do;
+/// - `POST /review`, rating `{"file", "grade", "deck"}`, `good` by default;
+/// - `GET /stats?deck=`, what `memo stats` shows.
+///
+/// See [`Access`] for who may call.
+pub fn run(
+    config: &Config,
+    host: &str,
+    port: u16,
+    access: &Access,
+    dry: bool,
+    top: bool,
+) -> Result<(), Error> {
+    if access.token.is_none() && !is_loopback(host) {
+        return Err(Error::Storage(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "serving on {} lets other machines in, pass a `--token` they must send",
+                host
+            ),
+        )));
+    }
+    let listener = TcpListener::bind((host, port)).map_err(Error::Storage)?;
+    outln!("serving on http://{}:{}", host, port);
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+        if let Err(e) = handle(config, &stream, access, dry, top) {
+            eprintln!("memo: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Who may call `memo serve`.
+///
+/// With a `token`, requests must carry it as `Authorization: Bearer` and
+/// may come from any origin. Without one, which only serving this machine
+/// allows, requests from web pages must come from one of `origins` or
+/// Obsidian's, and name this machine as their `Host`, so that pages open in
+/// a browser can neither call nor rebind a name to the server.
+pub struct Access {
+    pub token: Option<String>,
+    pub origins: Vec<String>,
+}
+
+/// Origin of the pages of Obsidian, its plugins included.
+const OBSIDIAN: &str = "app://obsidian.md";
+
+impl Access {
+    /// The origin responses are opened to, if any.
+    fn cors<'a>(&'a self, origin: Option<&'a str>) -> Option<&'a str> {
+        if self.token.is_some() {
+            return Some("*");
+        }
+
+        origin.filter(|o| *o == OBSIDIAN || self.origins.iter().any(|a| a == o))
+    }
+
+    /// Why `request` is refused before looking at what it asks for, if it is.
+    fn refusal(&self, request: &Request) -> Option<(u16, &'static str)> {
+        if let Some(token) = &self.token {
+            let expected = format!("Bearer {}", token);
+            return (request.headers.get("authorization") != Some(&expected))
+                .then_some((401, "bad token"));
+        }
+
+        let origin = request.headers.get("origin").map(String::as_str);
+        if origin.is_some() && self.cors(origin).is_none() {
+            return Some((403, "origin not allowed"));
+        }
+        let host = request.headers.get("host").map_or("", String::as_str);
+        let name = match host.rsplit_once(':') {
+            Some((name, port)) if port.parse::<u16>().is_ok() => name,
+            _ => host,
+        };
+        if !is_loopback(name.trim_start_matches('[').trim_end_matches(']')) {
+            return Some((403, "unexpected host"));
+        }
+
+        None
+    }
+}
+
+/// Whether `host` is this machine.
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+fn handle(
+    config: &Config,
+    stream: &TcpStream,
+    access: &Access,
+    dry: bool,
+    top: bool,
+) -> io::Result<()> {
+    let request = match Request::read(stream) {
+        Ok(request) => request,
+        Err(e) => {
+            return respond(
+                stream,
+                None,
+                400,
+                &json!({ "error": e.to_string() }).to_string(),
+            );
+        }
+    };
+    let cors = access.cors(request.headers.get("origin").map(String::as_str));
+    if request.method == "OPTIONS" {
+        return respond(stream, cors, 204, "");
+    }
+    if let Some((status, error)) = access.refusal(&request) {
+        return respond(stream, cors, status, &json!({ "error": error }).to_string());
+    }
+    // Only pages allowed to may send JSON, which browsers ask about first.
+    let json_body = request
+        .headers
+        .get("content-type")
+        .and_then(|t| t.split(';').next())
+        .is_some_and(|t| t.trim().eq_ignore_ascii_case("application/json"));
+    if request.method == "POST" && !json_body {
+        return respond(
+            stream,
+            cors,
+            415,
+            &json!({ "error": "POST bodies must be application/json" }).to_string(),
+        );
+    }
+
+    let today = Local::now().date_naive();
+    let deck = request
+        .query
+        .get("deck")
+        .map(|d| deck_name(config, d))
+        .transpose();
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/decks") => Ok(json!(config.decks())),
+        ("GET", "/due") => deck.and_then(|deck| due(config, deck, today)),
+        ("GET", "/stats") => deck.and_then(|deck| stats(config, deck, today)),
+        ("POST", "/draw") => {
+            let count = request.query.get("count").and_then(|c| c.parse().ok());
+            deck.and_then(|deck| locked(config, dry, || draw(config, deck, count, dry, top, today)))
+        }
+        ("POST", "/review") => match serde_json::from_slice::<Rating>(&request.body) {
+            Ok(rating) => locked(config, dry, || rate(config, rating, dry, today)),
+            Err(e) => {
+                return respond(
+                    stream,
+                    cors,
+                    400,
+                    &json!({ "error": e.to_string() }).to_string(),
+                );
+            }
+        },
+        (_, "/decks" | "/due" | "/stats" | "/draw" | "/review") => {
+            return respond(
+                stream,
+                cors,
+                405,
+                &json!({ "error": "method not allowed" }).to_string(),
+            );
+        }
+        _ => {
+            return respond(
+                stream,
+                cors,
+                404,
+                &json!({ "error": "no such endpoint" }).to_string(),
+            );
+        }
+    };
+
+    match result {
+        Ok(body) => respond(stream, cors, 200, &body.to_string()),
+        Err(e) => respond(
+            stream,
+            cors,
+            status(&e),
+            &json!({ "error": e.to_string() }).to_string(),
+        ),
+    }
+}
+
+/// Runs `action` holding the lock of the vault unless `dry`.
+fn locked(
+    config: &Config,
+    dry: bool,
+    action: impl FnOnce() -> Result<Value, Error>,
+) -> Result<Value, Error> {
+    let _lock = (!dry).then(|| Lock::for_saving(config, true)).transpose()?;
+    undo::begin();
+
+    action()
+}
+
+fn status(e: &Error) -> u16 {
+    match e {
+        Error::NoDeck(..) | Error::NoFile(_) => 404,
+        Error::Locked(..) => 409,
+        Error::Storage(_) => 500,
+        _ => 400,
+    }
+}
+
+/// `name` resolved to a deck of the vault, one of [`Config::decks`] or a dir
+/// below one, as no request may open anything out of them, like with `..`.
+fn deck_name(config: &Config, name: &str) -> Result<String, Error> {
+    let deck = config.resolve_deck(name);
+    let mut components = Path::new(&deck).components();
+    let known = matches!(
+        components.next(),
+        Some(Component::Normal(top)) if config.decks().iter().any(|d| top == d.as_str())
+    );
+    if known
+        && components.all(|c| matches!(c, Component::Normal(_)))
+        && config.vault.root.join(&deck).is_dir()
+    {
+        Ok(deck)
+    } else {
+        Err(Error::NoDeck(name.to_string(), config.vault.root.clone()))
+    }
+}
+
+fn decks(config: &Config, deck: Option<String>) -> Vec<String> {
+    deck.map_or_else(|| config.decks(), |d| vec![d])
+}
+
+fn due(config: &Config, deck: Option<String>, today: NaiveDate) -> Result<Value, Error> {
+    let mut rows = Vec::new();
+    for name in decks(config, deck) {
+        let mut deck = Deck::open(config, &name)?;
+        deck.resume(today);
+        let queue = deck.pending();
+        for file in &deck.files {
+            let file_name = scan::file_stem(file);
+            let due = forecast::due_below(&deck, &file_name, deck.settings.due_retention, today);
+            let queued = queue.contains(file);
+            if !queued && (deck.is_suspended(&file_name) || due.is_none_or(|d| d > today)) {
+                continue;
+            }
+            rows.push(json!({
+                "deck": deck.name,
+                "file": file_name,
+                "path": file,
+                "uri": uri(config, file),
+                "due": due,
+                "queued": queued,
+            }));
+        }
+    }
+
+    Ok(json!(rows))
+}
+
+fn stats(config: &Config, deck: Option<String>, today: NaiveDate) -> Result<Value, Error> {
+    let stats = decks(config, deck)
+        .iter()
+        .map(|name| Ok(DeckStats::compute(&Deck::open(config, name)?, today)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(json!(stats))
+}
+
+fn draw(
+    config: &Config,
+    deck: Option<String>,
+    count: Option<usize>,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<Value, Error> {
+    let args = DrawArgs {
+        tags: Vec::new(),
+        count,
+        minutes: None,
+        redraw: false,
+        no_plugins: false,
+    };
+    let Draw {
+        decks,
+        queues,
+        selected,
+        ..
+    } = all::draw(config, &decks(config, deck), &args, dry, top, today)?;
+
+    let mut rows = Vec::new();
+    for (deck, (queue, selected)) in decks.iter().zip(queues.iter().zip(&selected)) {
+        let files = queue
+            .iter()
+            .map(|f| (f, false))
+            .chain(selected.iter().map(|f| (f, true)));
+        for (file, drawn) in files {
+            rows.push(json!({
+                "deck": deck.name,
+                "file": scan::file_stem(file),
+                "path": file,
+                "uri": uri(config, file),
+                "drawn": drawn,
+            }));
+        }
+    }
+
+    Ok(json!(rows))
+}
+
+fn rate(config: &Config, rating: Rating, dry: bool, today: NaiveDate) -> Result<Value, Error> {
+    let name = match &rating.deck {
+        Some(deck) => deck_name(config, deck)?,
+        None => Deck::locate(config, &rating.file)?,
+    };
+    let mut deck = Deck::open(config, &name)?;
+    let interval = deck.rate(&rating.file, rating.grade, today)?;
+    if !dry {
+        deck.save()?;
+    }
+
+    Ok(json!({
+        "deck": deck.name,
+        "file": rating.file,
+        "grade": rating.grade,
+        "interval": interval,
+    }))
+}
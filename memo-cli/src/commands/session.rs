@@ -0,0 +1,216 @@
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use memo_core::webhook::{self, Digest};
+use memo_core::{Config, Deck, Error, Grade, focus, scan};
+
+use super::review::{self, DrawArgs};
+use crate::link::{show_link, uri};
+use crate::open::open;
+use crate::seed::rng;
+
+/// Periods of work and the breaks between them.
+#[derive(Clone, Copy)]
+pub struct Pomodoro {
+    pub work: Duration,
+    pub pause: Duration,
+}
+
+/// Works through today's queue of `deck` one file at a time, opening each and
+/// timing it from then until it is graded, the time going to the history.
+/// With a `pomodoro`, waits between files and takes breaks between periods.
+pub fn run(
+    config: &Config,
+    deck: &str,
+    draw: &DrawArgs,
+    pomodoro: Option<Pomodoro>,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let (mut deck, budget) = draw.open(config, deck, today)?;
+
+    let queue = deck.pending();
+    let drawn = deck.draw(today, budget, top, &mut rng());
+    if !dry {
+        deck.save()?;
+    }
+    let files: Vec<_> = queue.iter().chain(&drawn).collect();
+    review::up(files.len(), Some(&deck.name))?;
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut timed = Vec::new();
+    let mut reviewed = Vec::new();
+    let mut period = Instant::now();
+    let mut pomodoros = 0;
+    'files: for (i, file) in files.iter().enumerate() {
+        let name = scan::file_stem(file);
+        outln!();
+        match pomodoro {
+            Some(pomodoro) => out!(
+                "[{}/{}, {} left] ",
+                i + 1,
+                files.len(),
+                clock(pomodoro.work.saturating_sub(period.elapsed()))
+            ),
+            None => out!("[{}/{}] ", i + 1, files.len()),
+        }
+        show_link(file, config);
+        if let Some(info) = deck.info(&name) {
+            for note in &info.notes {
+                outln!("  note ({}): {}", note.date, note.text);
+            }
+        }
+        if let Err(e) = open(&uri(config, file)) {
+            eprintln!("cannot open {}: {}", name, e);
+        }
+
+        let opened = Instant::now();
+        let grade = loop {
+            out!("(g)ood, (h)ard, (a)gain, (e)asy, (s)kip or (q)uit [g]: ");
+            let _ = io::stdout().flush();
+            let Some(Ok(line)) = lines.next() else {
+                break 'files;
+            };
+            match line.trim() {
+                "" | "g" => break Grade::Good,
+                "h" => break Grade::Hard,
+                "a" => break Grade::Again,
+                "e" => break Grade::Easy,
+                "s" => {
+                    deck.skip(&name)?;
+                    outln!("skipped {}", name);
+                    if !dry {
+                        deck.save()?;
+                    }
+                    continue 'files;
+                }
+                "q" => break 'files,
+                other => outln!("unknown answer `{}`", other),
+            }
+        };
+
+        let seconds = opened.elapsed().as_secs();
+        let interval = deck.rate_timed(&name, grade, today, Some(seconds))?;
+        timed.push(seconds);
+        reviewed.push((name.clone(), grade));
+        outln!(
+            "{}: {} in {}s, next review in {} day(s)",
+            name,
+            grade,
+            seconds,
+            interval
+        );
+        if !dry {
+            deck.save()?;
+        }
+
+        let Some(pomodoro) = pomodoro else {
+            continue;
+        };
+        if i + 1 == files.len() {
+            break;
+        }
+        if period.elapsed() >= pomodoro.work {
+            pomodoros += 1;
+            outln!("pomodoro {} done, take a break", pomodoros);
+            count_down(pomodoro.pause);
+            period = Instant::now();
+        }
+        out!("enter for the next file, q to quit: ");
+        let _ = io::stdout().flush();
+        match lines.next() {
+            Some(Ok(line)) if line.trim() != "q" => {}
+            _ => break,
+        }
+    }
+    if let Some(pomodoro) = pomodoro
+        && period.elapsed() >= pomodoro.work
+    {
+        pomodoros += 1;
+    }
+
+    outln!();
+    if timed.is_empty() {
+        outln!("nothing reviewed");
+    } else {
+        let total: u64 = timed.iter().sum();
+        outln!(
+            "reviewed {} file(s) in {}, {}s a file on average",
+            timed.len(),
+            clock(Duration::from_secs(total)),
+            total / timed.len() as u64
+        );
+        if !dry {
+            let path = focus::path(&config.revs_dir());
+            let session = focus::Session {
+                date: today,
+                deck: deck.name.clone(),
+                focused: total,
+                pomodoros,
+            };
+            focus::append(&path, &session).map_err(Error::Storage)?;
+            let (all, on_day) = focus::focused(&focus::load(&path), &deck.name, today);
+            outln!(
+                "focused on `{}` for {} today, {} in all",
+                deck.name,
+                clock(Duration::from_secs(on_day)),
+                clock(Duration::from_secs(all))
+            );
+        }
+    }
+    if pomodoros > 0 {
+        outln!("{} pomodoro(s) done", pomodoros);
+    }
+    if !dry {
+        post_digest(config, &deck, reviewed, today);
+    }
+
+    Ok(())
+}
+
+/// Posts what was `reviewed` in `deck` to the webhooks wanting it, warning
+/// of those that fail.
+pub fn post_digest(config: &Config, deck: &Deck, reviewed: Vec<(String, Grade)>, today: NaiveDate) {
+    let hooks: Vec<_> = config
+        .webhooks
+        .iter()
+        .filter(|(_, hook)| webhook::wants(hook, &deck.name))
+        .collect();
+    if reviewed.is_empty() || hooks.is_empty() {
+        return;
+    }
+
+    let digest = Digest::new(deck, reviewed, today);
+    for (name, hook) in hooks {
+        if let Err(e) = webhook::post(hook, &digest) {
+            eprintln!("cannot post to webhook `{}`: {}", name, e);
+        }
+    }
+}
+
+/// Counts `pause` down on one line.
+fn count_down(pause: Duration) {
+    let end = Instant::now() + pause;
+    while let Some(left) = end.checked_duration_since(Instant::now()) {
+        out!("\rbreak, {} left ", clock(left));
+        let _ = io::stdout().flush();
+        thread::sleep(left.min(Duration::from_secs(1)));
+        if left.is_zero() {
+            break;
+        }
+    }
+    outln!("\rbreak over         ");
+}
+
+/// `duration` as minutes and seconds, hours too if it has any.
+fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds / 3600 {
+        0 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
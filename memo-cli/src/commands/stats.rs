@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{Datelike, Days, NaiveDate};
+use clap::{Args, ValueEnum};
+use memo_core::chart::{self, Plot};
+use memo_core::stats::{self, DeckStats};
+use memo_core::{Config, Deck, Error, forecast, history, scan};
+
+use crate::format::{self, Format};
+
+/// 256-color codes of the heatmap, from no reviews to the busiest days.
+const HEAT: [u8; 5] = [237, 22, 28, 34, 46];
+
+/// Writing a chart of `memo stats` to a picture.
+#[derive(Args)]
+pub struct ChartArgs {
+    /// Write a chart to this file instead, PNG or SVG by its extension.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["json", "heatmap"])]
+    pub chart: Option<PathBuf>,
+
+    /// What the chart shows.
+    #[arg(
+        long,
+        value_name = "KIND",
+        default_value = "retention",
+        requires = "chart"
+    )]
+    pub chart_kind: ChartKind,
+
+    /// Days the chart covers, ahead for retention and the forecast, back for
+    /// reviews.
+    #[arg(long, value_name = "N", default_value_t = 30, requires = "chart")]
+    pub chart_days: u32,
+}
+
+/// What `memo stats` shows besides the table.
+#[derive(Args)]
+pub struct ViewArgs {
+    /// Show a calendar of reviews per day over the past year instead.
+    #[arg(long, conflicts_with = "json")]
+    pub heatmap: bool,
+
+    /// Also show the recall of every deck by days since the review before,
+    /// against the retention its scheduler predicted.
+    #[arg(long, conflicts_with = "heatmap")]
+    pub retention: bool,
+}
+
+/// Charts `memo stats --chart` draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChartKind {
+    /// Mean estimated retention of every deck, if nothing is reviewed.
+    Retention,
+    /// Reviews per day.
+    Reviews,
+    /// Files falling due per day.
+    Forecast,
+}
+
+pub fn run(
+    config: &Config,
+    decks: &[String],
+    json: bool,
+    view: &ViewArgs,
+    chart: &ChartArgs,
+    format: Format,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let names = if decks.is_empty() {
+        config.decks()
+    } else {
+        decks.iter().map(|d| config.resolve_deck(d)).collect()
+    };
+
+    if let Some(path) = &chart.chart {
+        let decks = names
+            .iter()
+            .map(|name| Deck::open(config, name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let plot = plot(&decks, chart.chart_kind, chart.chart_days, today);
+        let svg = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+        let data = if svg {
+            plot.svg().into_bytes()
+        } else {
+            plot.png()
+        };
+        fs::write(path, data).map_err(Error::Storage)?;
+        outln!("wrote {}", path.display());
+        return Ok(());
+    }
+
+    if view.heatmap {
+        let events: Vec<_> = names
+            .iter()
+            .flat_map(|name| history::load(&history::path(&config.revs_dir(), name)))
+            .collect();
+        print_heatmap(&stats::daily_counts(&events), today);
+        return Ok(());
+    }
+
+    let stats = names
+        .iter()
+        .map(|name| Ok(DeckStats::compute(&Deck::open(config, name)?, today)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if json || format == Format::Json {
+        format::print_json(&stats);
+        return Ok(());
+    }
+    if format == Format::Tsv {
+        let rows: Vec<_> = stats
+            .iter()
+            .map(|s| {
+                vec![
+                    s.deck.clone(),
+                    s.files.to_string(),
+                    s.new.to_string(),
+                    s.reviewed_today.to_string(),
+                    s.reviewed_this_week.to_string(),
+                    s.average_interval
+                        .map_or_else(String::new, |i| format!("{:.1}", i)),
+                    s.average_duration
+                        .map_or_else(String::new, |d| format!("{:.0}", d)),
+                    s.overdue.to_string(),
+                    s.grades.again.to_string(),
+                    s.grades.hard.to_string(),
+                    s.grades.good.to_string(),
+                    s.grades.easy.to_string(),
+                ]
+            })
+            .collect();
+        format::print_tsv(
+            &[
+                "deck",
+                "files",
+                "new",
+                "today",
+                "week",
+                "average_interval",
+                "average_duration",
+                "overdue",
+                "again",
+                "hard",
+                "good",
+                "easy",
+            ],
+            &rows,
+        );
+        return Ok(());
+    }
+
+    let width = stats.iter().map(|s| s.deck.len()).max().unwrap_or(0).max(4);
+    outln!(
+        "{:>width$} | files |  new | today | week | avg ivl | avg time | overdue | again/hard/good/easy",
+        "deck"
+    );
+    for s in &stats {
+        outln!(
+            "{:>width$} | {:>5} | {:>4} | {:>5} | {:>4} | {:>7} | {:>8} | {:>7} | {}/{}/{}/{}",
+            s.deck,
+            s.files,
+            s.new,
+            s.reviewed_today,
+            s.reviewed_this_week,
+            s.average_interval
+                .map_or_else(|| "N/A".to_string(), |i| format!("{:.1}d", i)),
+            s.average_duration
+                .map_or_else(|| "N/A".to_string(), minutes),
+            s.overdue,
+            s.grades.again,
+            s.grades.hard,
+            s.grades.good,
+            s.grades.easy,
+        );
+    }
+    if view.retention {
+        for s in &stats {
+            print_retention(s);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the recall of `stats` by days since the review before, marking
+/// where it strays from the prediction.
+fn print_retention(stats: &DeckStats) {
+    outln!();
+    if stats.retention.is_empty() {
+        outln!("{}: no repeated reviews yet", stats.deck);
+        return;
+    }
+
+    outln!("{}: recall by days since the review before", stats.deck);
+    outln!("   days | reviews | recalled | predicted");
+    for bucket in &stats.retention {
+        let flag = match bucket.miscalibration() {
+            Some(gap) if gap < 0.0 => "  <- recalled less than predicted",
+            Some(_) => "  <- recalled more than predicted",
+            None => "",
+        };
+        outln!(
+            "{:>7} | {:>7} | {:>7.0}% | {:>9}{}",
+            bucket.label(),
+            bucket.reviews,
+            bucket.recall * 100.0,
+            bucket
+                .predicted
+                .map_or_else(|| "N/A".to_string(), |p| format!("{:.0}%", p * 100.0)),
+            flag
+        );
+    }
+}
+
+fn plot(decks: &[Deck], kind: ChartKind, days: u32, today: NaiveDate) -> Plot {
+    let (width, height) = (800, 300);
+    match kind {
+        ChartKind::Retention => {
+            let series: Vec<(String, BTreeMap<NaiveDate, f64>)> = decks
+                .iter()
+                .map(|deck| {
+                    let curve = (0..days)
+                        .filter_map(|d| {
+                            let day = today + Days::new(d.into());
+                            let estimates: Vec<f64> = deck
+                                .files
+                                .iter()
+                                .filter_map(|f| forecast::retention(deck, &scan::file_stem(f), day))
+                                .collect();
+                            (!estimates.is_empty()).then(|| {
+                                (day, estimates.iter().sum::<f64>() / estimates.len() as f64)
+                            })
+                        })
+                        .collect();
+                    (deck.name.clone(), curve)
+                })
+                .filter(|(_, curve): &(String, BTreeMap<_, _>)| !curve.is_empty())
+                .collect();
+            chart::lines(&series, width, height)
+        }
+        ChartKind::Reviews => {
+            let events: Vec<_> = decks
+                .iter()
+                .flat_map(|d| history::load(&d.history))
+                .collect();
+            let counts = stats::daily_counts(&events);
+            let days: BTreeMap<NaiveDate, usize> = (0..days)
+                .rev()
+                .map(|d| today - Days::new(d.into()))
+                .map(|day| (day, counts.get(&day).copied().unwrap_or(0)))
+                .collect();
+            chart::bars(&days, width, height)
+        }
+        ChartKind::Forecast => {
+            let mut due = BTreeMap::new();
+            for deck in decks {
+                for (day, count) in forecast::due_per_day(deck, today, days) {
+                    *due.entry(day).or_insert(0) += count;
+                }
+            }
+            chart::bars(&due, width, height)
+        }
+    }
+}
+
+/// `seconds` as minutes and seconds.
+fn minutes(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!("{}m{:02}s", seconds / 60, seconds % 60)
+}
+
+/// Prints a year of daily review counts as a grid of weeks, Mondays on top.
+fn print_heatmap(counts: &BTreeMap<NaiveDate, usize>, today: NaiveDate) {
+    let first = today - Days::new(52 * 7);
+    let start = first - Days::new(first.weekday().num_days_from_monday().into());
+    let weeks = (today - start).num_days() as usize / 7 + 1;
+    let max = counts
+        .range(start..=today)
+        .map(|(_, c)| *c)
+        .max()
+        .unwrap_or(0);
+
+    let mut months = vec![' '; weeks * 2];
+    let mut last_month = 0;
+    for week in 0..weeks {
+        let monday = start + Days::new(week as u64 * 7);
+        if monday.month() != last_month && week * 2 + 3 <= months.len() {
+            last_month = monday.month();
+            for (i, c) in monday.format("%b").to_string().chars().enumerate() {
+                months[week * 2 + i] = c;
+            }
+        }
+    }
+    outln!("    {}", months.into_iter().collect::<String>());
+
+    for weekday in 0..7 {
+        let label = match weekday {
+            0 => "Mon",
+            2 => "Wed",
+            4 => "Fri",
+            _ => "",
+        };
+        let mut line = format!("{:<4}", label);
+        for week in 0..weeks {
+            let date = start + Days::new((week * 7 + weekday) as u64);
+            if date > today {
+                break;
+            }
+            let count = counts.get(&date).copied().unwrap_or(0);
+            line.push_str(&cell(level(count, max)));
+        }
+        outln!("{}", line);
+    }
+
+    let legend: String = (0..HEAT.len()).map(cell).collect();
+    outln!();
+    outln!("    Less {}More", legend);
+    outln!(
+        "    {} review(s) in the last year",
+        counts.range(start..=today).map(|(_, c)| c).sum::<usize>()
+    );
+}
+
+fn level(count: usize, max: usize) -> usize {
+    if count == 0 || max == 0 {
+        0
+    } else {
+        (count * (HEAT.len() - 1)).div_ceil(max)
+    }
+}
+
+fn cell(level: usize) -> String {
+    format!("\x1b[38;5;{}m■\x1b[0m ", HEAT[level])
+}
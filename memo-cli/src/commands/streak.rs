@@ -0,0 +1,15 @@
+use chrono::NaiveDate;
+use memo_core::streak::Streak;
+use memo_core::{Config, Error};
+
+pub fn run(config: &Config, today: NaiveDate) -> Result<(), Error> {
+    let streak = Streak::load(&Streak::path(&config.revs_dir()));
+
+    outln!("current streak: {} day(s)", streak.current(today));
+    outln!("longest streak: {} day(s)", streak.longest);
+    if let Some(last) = streak.last_day {
+        outln!("last review: {}", last);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use memo_core::{Config, Error};
+
+use super::queue::queued;
+
+/// Puts files on hold, or back if `!suspended`, keeping their history.
+pub fn run(
+    config: &Config,
+    files: &[String],
+    deck: Option<&str>,
+    suspended: bool,
+    dry: bool,
+) -> Result<(), Error> {
+    for (mut deck, files) in queued(config, files, deck)? {
+        for file in &files {
+            deck.suspend(file, suspended)?;
+            if suspended {
+                outln!("suspended: {}", file);
+            } else {
+                outln!("unsuspended: {}", file);
+            }
+        }
+        if !dry {
+            deck.save()?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use chrono::NaiveDate;
+use memo_core::config::ConfigError;
+use memo_core::{Config, Error, git};
+
+pub fn run(
+    config: &Config,
+    remote: Option<String>,
+    dry: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let Some(remote) = remote.or_else(|| config.vault.remote.clone()) else {
+        return Err(ConfigError::Invalid(
+            "no remote to sync with, pass one or set `remote` in the `[vault]` table".to_string(),
+        )
+        .into());
+    };
+    let revs_dir = config.revs_dir();
+
+    if dry {
+        for file in git::changes(&revs_dir).map_err(Error::Storage)? {
+            outln!("commit {}", file);
+        }
+        outln!("then merge from and push to {}", remote);
+        return Ok(());
+    }
+
+    let synced =
+        git::sync(&revs_dir, &remote, &format!("memo sync {}", today)).map_err(Error::Storage)?;
+    outln!("committed {} file(s)", synced.committed);
+    if synced.merged {
+        outln!("merged changes from {}", remote);
+    }
+    outln!("pushed to {}", remote);
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chrono::NaiveDate;
+use memo_core::{Config, Error, Grade, scan};
+
+use super::review::DrawArgs;
+use super::session::post_digest;
+use crate::link::uri;
+use crate::open::open;
+use crate::seed::rng;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Todo,
+    Rated(Grade),
+    Skipped,
+}
+
+struct Item {
+    name: String,
+    path: PathBuf,
+    notes: Vec<String>,
+    status: Status,
+    /// When the file was last opened, its review is timed from then.
+    opened: Option<Instant>,
+}
+
+/// Works through today's queue interactively, drawing it first if needed.
+pub fn run(
+    config: &Config,
+    deck: &str,
+    draw: &DrawArgs,
+    dry: bool,
+    top: bool,
+    today: NaiveDate,
+) -> Result<(), Error> {
+    let (mut deck, budget) = draw.open(config, deck, today)?;
+
+    let queue = deck.pending();
+    let drawn = deck.draw(today, budget, top, &mut rng());
+    if !dry {
+        deck.save()?;
+    }
+
+    let mut items: Vec<Item> = queue
+        .iter()
+        .chain(&drawn)
+        .map(|f| {
+            let name = scan::file_stem(f);
+            let notes = deck
+                .info(&name)
+                .map(|i| i.notes.iter().map(|n| n.text.clone()).collect())
+                .unwrap_or_default();
+            Item {
+                name,
+                path: f.clone(),
+                notes,
+                status: Status::Todo,
+                opened: None,
+            }
+        })
+        .collect();
+    if items.is_empty() {
+        outln!("nothing to review in `{}`", deck.name);
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    let mut cursor = 0;
+    let mut message = String::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        render(&deck.name, &items, cursor, started, &message);
+        message.clear();
+
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        let name = items[cursor].name.clone();
+        let path = items[cursor].path.clone();
+        let mark = |items: &mut Vec<Item>, status| {
+            items[cursor].status = status;
+            next_todo(items, cursor)
+        };
+
+        match line.trim() {
+            "" | "o" => match open(&uri(config, &path)) {
+                Ok(()) => {
+                    items[cursor].opened = Some(Instant::now());
+                    message = format!("opened {}", name);
+                }
+                Err(e) => message = format!("cannot open {}: {}", name, e),
+            },
+            "j" | "n" => cursor = (cursor + 1) % items.len(),
+            "k" | "p" => cursor = (cursor + items.len() - 1) % items.len(),
+            // Rating again would count the review twice.
+            "d" | "h" | "a" | "e" | "s" if items[cursor].status != Status::Todo => {
+                message = format!("{} is done already", name);
+            }
+            "d" | "h" | "a" | "e" => {
+                let grade = match line.trim() {
+                    "h" => Grade::Hard,
+                    "a" => Grade::Again,
+                    "e" => Grade::Easy,
+                    _ => Grade::Good,
+                };
+                let duration = items[cursor].opened.map(|t| t.elapsed().as_secs());
+                let interval = deck.rate_timed(&name, grade, today, duration)?;
+                message = format!("{}: {}, next review in {} day(s)", name, grade, interval);
+                cursor = mark(&mut items, Status::Rated(grade));
+            }
+            "s" => {
+                deck.skip(&name)?;
+                message = format!("skipped {}", name);
+                cursor = mark(&mut items, Status::Skipped);
+            }
+            "q" => break,
+            other => match other.parse::<usize>() {
+                Ok(n) if (1..=items.len()).contains(&n) => cursor = n - 1,
+                _ => message = format!("unknown command `{}`", other),
+            },
+        }
+
+        if !dry {
+            deck.save()?;
+        }
+        if items.iter().all(|i| i.status != Status::Todo) {
+            render(&deck.name, &items, cursor, started, &message);
+            outln!("all done, well done!");
+            break;
+        }
+    }
+
+    if !dry {
+        let reviewed = items
+            .iter()
+            .filter_map(|i| match i.status {
+                Status::Rated(grade) => Some((i.name.clone(), grade)),
+                _ => None,
+            })
+            .collect();
+        post_digest(config, &deck, reviewed, today);
+    }
+
+    Ok(())
+}
+
+/// The next file still to review after `cursor`, wrapping around.
+fn next_todo(items: &[Item], cursor: usize) -> usize {
+    (1..=items.len())
+        .map(|i| (cursor + i) % items.len())
+        .find(|&i| items[i].status == Status::Todo)
+        .unwrap_or(cursor)
+}
+
+fn render(deck: &str, items: &[Item], cursor: usize, started: Instant, message: &str) {
+    let left = items.iter().filter(|i| i.status == Status::Todo).count();
+    let elapsed = started.elapsed().as_secs();
+
+    let mut out = io::stdout().lock();
+    let _ = write!(out, "\x1b[2J\x1b[H");
+    let _ = writeln!(
+        out,
+        "memo · {} · {} of {} left · {:02}:{:02}",
+        deck,
+        left,
+        items.len(),
+        elapsed / 60,
+        elapsed % 60
+    );
+    let _ = writeln!(out);
+
+    for (i, item) in items.iter().enumerate() {
+        let marker = match item.status {
+            Status::Todo => "[ ]".to_string(),
+            Status::Rated(Grade::Again) => "[a]".to_string(),
+            Status::Rated(Grade::Hard) => "[h]".to_string(),
+            Status::Rated(_) => "[x]".to_string(),
+            Status::Skipped => "[-]".to_string(),
+        };
+        let pointer = if i == cursor { ">" } else { " " };
+        let _ = writeln!(out, "{} {:>2}. {} {}", pointer, i + 1, marker, item.name);
+        for note in &item.notes {
+            let _ = writeln!(out, "         note: {}", note);
+        }
+    }
+
+    let _ = writeln!(out);
+    if !message.is_empty() {
+        let _ = writeln!(out, "{}", message);
+    }
+    let _ = writeln!(
+        out,
+        "enter/o open · d done · h hard · a again · e easy · s skip · j/k move · <n> jump · q quit"
+    );
+    let _ = write!(out, "> ");
+    let _ = out.flush();
+}
@@ -0,0 +1,37 @@
+use memo_core::undo::Journal;
+use memo_core::{Config, Deck, Error};
+
+/// Puts back what the last command that saved anything changed.
+pub fn run(config: &Config, dry: bool) -> Result<(), Error> {
+    let path = Journal::path(&config.revs_dir());
+    let journal = Journal::load(&path);
+    if journal.changes.is_empty() {
+        outln!("nothing to undo");
+        return Ok(());
+    }
+
+    // Newest first, so that a deck saved more than once ends up as before
+    // the first save.
+    for change in journal.changes.iter().rev() {
+        let mut deck = Deck::open(config, &change.deck)?;
+        outln!(
+            "{}: {} file(s) restored, {} review(s) dropped from the history{}",
+            deck.name,
+            change.previous.len() + change.added.len(),
+            change.events,
+            if change.drawn.is_some() {
+                ", draw restored"
+            } else {
+                ""
+            }
+        );
+        if !dry {
+            deck.undo(change)?;
+        }
+    }
+    if !dry {
+        Journal::clear(&path).map_err(Error::Storage)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// How listings are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// For reading, with colors and clickable links.
+    #[default]
+    Plain,
+    Json,
+    /// Tab-separated, a header line first.
+    Tsv,
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) {
+    outln!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_or_default()
+    );
+}
+
+/// Prints `rows` under `header`, tabs and line breaks in fields turned into spaces.
+pub fn print_tsv(header: &[&str], rows: &[Vec<String>]) {
+    outln!("{}", header.join("\t"));
+    for row in rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|f| f.replace(['\t', '\n', '\r'], " "))
+            .collect();
+        outln!("{}", fields.join("\t"));
+    }
+}
+
+/// `rows` under `header` as CSV, fields with commas, quotes or line breaks
+/// quoted.
+pub fn csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let line = |fields: Vec<&str>| -> String {
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|f| {
+                if f.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", f.replace('"', "\"\""))
+                } else {
+                    f.to_string()
+                }
+            })
+            .collect();
+        format!("{}\r\n", fields.join(","))
+    };
+
+    let mut out = line(header.to_vec());
+    for row in rows {
+        out.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+
+    out
+}
@@ -0,0 +1,126 @@
+//! Just enough HTTP/1.1 for `memo serve`: one request per connection, bodies
+//! by `Content-Length`.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+/// Bodies larger than this are refused.
+const MAX_BODY: usize = 1 << 20;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    /// Header names in lowercase.
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn read(stream: &TcpStream) -> io::Result<Self> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "malformed request line",
+            ));
+        };
+        let method = method.to_string();
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let path = decode(path);
+        let query = query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let (key, value) = p.split_once('=').unwrap_or((p, ""));
+                (decode(key), decode(value))
+            })
+            .collect();
+
+        let mut headers = BTreeMap::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let length: usize = headers
+            .get("content-length")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(0);
+        if length > MAX_BODY {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request body too large",
+            ));
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+
+        Ok(Self {
+            method,
+            path,
+            query,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Writes a response of `status` with a JSON `body`, open to the origin
+/// `cors` if given, e.g. that of Obsidian for plugins running in it.
+pub fn respond(
+    mut stream: &TcpStream,
+    cors: Option<&str>,
+    status: u16,
+    body: &str,
+) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        403 => "Forbidden",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    };
+    let cors = cors.map_or_else(String::new, |origin| {
+        format!(
+            "Access-Control-Allow-Origin: {}\r\n\
+             Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+             Access-Control-Allow-Headers: Authorization, Content-Type\r\n\
+             Vary: Origin\r\n",
+            origin
+        )
+    });
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         {}\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        cors,
+        body
+    )?;
+    stream.flush()
+}
+
+fn decode(text: &str) -> String {
+    let text = text.replace('+', " ");
+    urlencoding::decode(&text).map_or(text.clone(), |t| t.into_owned())
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::{self, Path};
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use memo_core::config::LinkScheme;
+use memo_core::{Config, scan, sections};
+
+/// How files are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Links {
+    /// The raw URI.
+    Plain,
+    /// The name, linked to its URI by an OSC 8 escape sequence.
+    Osc8,
+    /// The bare name.
+    None,
+}
+
+impl Links {
+    /// OSC 8 links on terminals, plain URIs when piped or on a dumb terminal.
+    pub fn detect() -> Self {
+        let dumb = env::var("TERM").is_ok_and(|t| t == "dumb");
+        if io::stdout().is_terminal() && !dumb {
+            Self::Osc8
+        } else {
+            Self::Plain
+        }
+    }
+}
+
+static LINKS: OnceLock<Links> = OnceLock::new();
+
+/// Sets how [`show_link`] prints files, detected if never set.
+pub fn set_links(links: Links) {
+    let _ = LINKS.set(links);
+}
+
+/// URI opening `file` by the `link_scheme` of the config. Obsidian ones
+/// name it by its path from the vault root, so that notes of the same name
+/// in different folders are told apart, and scroll to the heading of an
+/// item of a split note.
+pub fn uri(config: &Config, file: &Path) -> String {
+    let (note, heading) = sections::split(file);
+    let heading = urlencoding::encode(heading.as_deref().unwrap_or_default()).into_owned();
+    let vault = urlencoding::encode(&config.vault_name()).into_owned();
+    let vault_file = urlencoding::encode(&vault_path(config, &note)).into_owned();
+
+    match &config.vault.link_scheme {
+        LinkScheme::Obsidian if heading.is_empty() => {
+            format!("obsidian://open?vault={}&file={}", vault, vault_file)
+        }
+        LinkScheme::Obsidian => format!(
+            "obsidian://open?vault={}&file={}%23{}",
+            vault, vault_file, heading
+        ),
+        LinkScheme::Vscode => format!("vscode://file{}", uri_path(&note)),
+        LinkScheme::File => format!("file://{}", uri_path(&note)),
+        LinkScheme::Template(template) => template
+            .replace("{path}", &uri_path(&note))
+            .replace("{file}", &vault_file)
+            .replace("{vault}", &vault)
+            .replace("{name}", &urlencoding::encode(&scan::file_stem(file)))
+            .replace("{heading}", &heading),
+    }
+}
+
+/// The absolute path of `file` as a URI path: `/`-separated, percent-encoded
+/// and starting with `/`, Windows drives included.
+fn uri_path(file: &Path) -> String {
+    let file = path::absolute(file).unwrap_or_else(|_| file.to_path_buf());
+    let path = file.to_string_lossy().replace('\\', "/");
+    let encoded: Vec<String> = path
+        .split('/')
+        .map(|segment| {
+            if segment.len() == 2 && segment.ends_with(':') {
+                segment.to_string()
+            } else {
+                urlencoding::encode(segment).into_owned()
+            }
+        })
+        .collect();
+    let encoded = encoded.join("/");
+
+    if encoded.starts_with('/') {
+        encoded
+    } else {
+        format!("/{}", encoded)
+    }
+}
+
+/// Path of `file` from the vault root with `/` separators and no `.md`.
+pub fn vault_path(config: &Config, file: &Path) -> String {
+    let relative = file.strip_prefix(&config.vault.root).unwrap_or(file);
+    let relative = if relative.extension().is_some_and(|e| e == "md") {
+        relative.with_extension("")
+    } else {
+        relative.to_path_buf()
+    };
+
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Prints the file's stem as a link, see [`Links`], and returns the stem.
+pub fn show_link(file: &Path, config: &Config) -> String {
+    let file_name = scan::file_stem(file);
+    if !file_name.is_empty() {
+        let uri = uri(config, file);
+        match LINKS.get_or_init(Links::detect) {
+            Links::Plain => outln!("{}", uri),
+            Links::Osc8 => outln!("\x1b]8;;{0}\x1b\\{1}\x1b]8;;\x1b\\", uri, file_name),
+            Links::None => outln!("{}", file_name),
+        }
+
+        file_name
+    } else {
+        String::new()
+    }
+}
@@ -0,0 +1,850 @@
+#[macro_use]
+mod out;
+
+mod commands;
+mod format;
+mod http;
+mod link;
+mod markdown;
+mod notify;
+mod open;
+mod seed;
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{CommandFactory, Parser, Subcommand};
+use memo_core::config::{SchedulerKind, Seed};
+use memo_core::lock::Lock;
+use memo_core::{Config, Error, Grade};
+
+use crate::commands::review::{DrawArgs, ReviewArgs};
+use crate::commands::schedule::When;
+use crate::commands::session::Pomodoro;
+use crate::format::Format;
+use crate::link::{Links, set_links};
+use crate::seed::set_seed;
+
+#[derive(Parser)]
+#[command(
+    name = "memo",
+    about = "Scientific memorizing helper.",
+    after_help = "`memo <DECK>` is short for `memo review <DECK>`.\n\n\
+                  Exit status: 0 on success, 1 on errors, 2 when `review` or `all` find nothing to review."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[arg(long, global = true)]
+    dry: bool,
+
+    #[arg(long, global = true)]
+    top: bool,
+
+    #[arg(long, global = true, value_name = "DATE")]
+    date: Option<String>,
+
+    /// Output of `list`, `review`, `all` and `stats`.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    format: Format,
+
+    /// How files are printed, OSC 8 links on terminals and plain URIs otherwise by default.
+    #[arg(long, global = true, value_enum)]
+    links: Option<Links>,
+
+    /// Seed of random draws, a number or `date` for one derived from the day,
+    /// overrides `[review] seed`.
+    #[arg(long, global = true, value_name = "SEED")]
+    seed: Option<Seed>,
+
+    /// Wait for another memo using the vault to finish instead of failing.
+    #[arg(long, global = true)]
+    wait: bool,
+
+    /// Vault root, overrides `MEMO_VAULT` and the config file.
+    #[arg(long, global = true, value_name = "PATH")]
+    vault: Option<PathBuf>,
+
+    /// Vault of the `[vault.<NAME>]` table to use, overrides `MEMO_PROFILE`.
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Set up the config file, asking for the vault, its decks and their daily quotas.
+    Init,
+
+    /// Dive into a deck, either a subdir of the vault or a shortcut from the config.
+    Review {
+        deck: String,
+
+        #[command(flatten)]
+        args: ReviewArgs,
+    },
+
+    /// Draw from several decks at once, weighing their files together against
+    /// `[review] files_per_day`.
+    All {
+        /// Decks to draw from, all of them if omitted.
+        #[arg(long, value_delimiter = ',', value_name = "DECKS")]
+        decks: Vec<String>,
+
+        #[command(flatten)]
+        args: ReviewArgs,
+    },
+
+    /// Quiz yourself on the `question::answer` and `#flashcard` cards of a deck.
+    Quiz {
+        deck: String,
+
+        /// Number of cards to ask.
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+    },
+
+    /// List the files of a deck with their weights, without drawing.
+    List {
+        deck: String,
+
+        #[arg(long, value_enum, default_value_t)]
+        sort: commands::list::Sort,
+
+        /// Only files past their due date.
+        #[arg(long)]
+        overdue: bool,
+
+        /// Only never reviewed files.
+        #[arg(long)]
+        new: bool,
+    },
+
+    /// Draw files uniformly at random without recording anything, for a last-minute blitz.
+    Cram {
+        deck: String,
+
+        /// Only draw files with this frontmatter tag, repeat for any of several.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Files to draw, defaults to the deck's `files_per_day`.
+        #[arg(long, value_name = "N")]
+        count: Option<usize>,
+    },
+
+    /// Draw from several decks as `memo all` does and write the queue to
+    /// today's note in `[vault] daily` as a checklist.
+    Daily {
+        #[arg(long, value_delimiter = ',', value_name = "DECKS")]
+        decks: Vec<String>,
+
+        #[command(flatten)]
+        args: DrawArgs,
+    },
+
+    /// Mark the files ticked in today's daily note as reviewed.
+    SyncDaily {
+        /// Only files of these decks.
+        #[arg(long, value_delimiter = ',', value_name = "DECKS")]
+        decks: Vec<String>,
+    },
+
+    /// Grade a review, feeding the outcome into the deck's scheduler.
+    Rate {
+        file: String,
+
+        grade: Grade,
+
+        /// Deck of the file, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Print a note rendered for the terminal, to review it without opening it.
+    Read {
+        file: String,
+
+        /// Deck of the file, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Mark drawn files as reviewed, all of the queue if no file is given.
+    Done {
+        files: Vec<String>,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Revert what the last command changed: its draw, marks and grades.
+    Undo,
+
+    /// Rebuild a deck's review data from its review history.
+    Rebuild { deck: String },
+
+    /// Remove review data of files that no longer exist, see `--dry` first.
+    Prune { deck: String },
+
+    /// Carry review data over to renamed files with the same stable ID.
+    Relink { deck: String },
+
+    /// Show what the weight of every file of a deck is made of.
+    Explain {
+        deck: String,
+
+        /// Only these files.
+        files: Vec<String>,
+
+        /// Print as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Fit a deck's decay rate to its review history and save it to the config.
+    Optimize { deck: String },
+
+    /// Replay the review history of decks, all of them if none are given,
+    /// under other schedulers, comparing the retention they expected with
+    /// the recalled one and the workload they would have asked for.
+    Backtest {
+        decks: Vec<String>,
+
+        /// Scheduler to replay under, repeat to compare several. Defaults to
+        /// decay, sm2, fsrs and leitner.
+        #[arg(long = "scheduler", value_name = "SCHEDULER")]
+        schedulers: Vec<SchedulerKind>,
+    },
+
+    /// Show the files of a deck that are overdue, due today and coming up.
+    Due {
+        deck: String,
+
+        /// Days to look ahead for upcoming files.
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+
+        /// Count files as due once their estimated retention is below this,
+        /// overrides the deck's `due_retention`.
+        #[arg(long, value_name = "R")]
+        retention: Option<f64>,
+    },
+
+    /// Show how many files of a deck fall due on each of the coming days.
+    Forecast {
+        deck: String,
+
+        /// Number of days to look ahead.
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+
+    /// Take a break: once it is over, every schedule moves later by its length.
+    /// Lists the recorded pauses if `--to` is not given.
+    Pause {
+        /// First day of the pause, today if omitted.
+        #[arg(long, value_name = "DATE", requires = "to")]
+        from: Option<NaiveDate>,
+
+        /// Last day of the pause.
+        #[arg(long, value_name = "DATE")]
+        to: Option<NaiveDate>,
+    },
+
+    /// Show statistics of decks, all of them if none is given.
+    Stats {
+        decks: Vec<String>,
+
+        /// Print as JSON, same as `--format json`.
+        #[arg(long)]
+        json: bool,
+
+        #[command(flatten)]
+        view: commands::stats::ViewArgs,
+
+        #[command(flatten)]
+        chart: commands::stats::ChartArgs,
+    },
+
+    /// Show every review of a file, with the intervals between them.
+    History {
+        file: String,
+
+        /// Deck of the file, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Serve a JSON API for plugins and shortcuts: `GET /decks`, `GET /due`,
+    /// `POST /draw`, `POST /review` and `GET /stats`.
+    Serve {
+        /// Port to listen on.
+        #[arg(long, default_value_t = 8787)]
+        port: u16,
+
+        /// Address to listen on, only this machine by default.
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Require `Authorization: Bearer <TOKEN>` on every request, from
+        /// any origin. Needed to listen on other addresses than this
+        /// machine's.
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Also take requests from web pages of this origin, e.g.
+        /// `http://localhost:3000`, on top of Obsidian's. Repeatable.
+        #[arg(long, value_name = "ORIGIN")]
+        allow_origin: Vec<String>,
+    },
+
+    /// Stay resident, drawing daily at `[review] draw_at` with a notification
+    /// and taking in notes added, renamed or removed as they are.
+    Daemon {
+        /// Decks to draw from and watch, all of them if none are given.
+        decks: Vec<String>,
+
+        #[command(flatten)]
+        draw: DrawArgs,
+
+        /// Seconds between two looks over the vault.
+        #[arg(long, value_name = "SECS", default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+
+    /// Summarize the files up for review per deck, the streak and the most
+    /// overdue files, or mail that by `[email]`.
+    Digest {
+        /// Decks to sum up, all of them if none are given.
+        decks: Vec<String>,
+
+        /// Mail the digest instead of printing it.
+        #[arg(long)]
+        email: bool,
+    },
+
+    /// Send a desktop notification of the files up for review per deck, for
+    /// running from cron, launchd or Task Scheduler.
+    Remind {
+        /// Decks to count, all of them if none are given.
+        decks: Vec<String>,
+
+        /// Command run through the shell when the notification is clicked,
+        /// e.g. `"alacritty -e memo session 408"`. Opens the first file up
+        /// for review if omitted. Clicks are only seen with `notify-send`.
+        #[arg(long, value_name = "COMMAND")]
+        command: Option<String>,
+    },
+
+    /// Show the current and longest streaks of days with reviews.
+    Streak,
+
+    /// List the filter, weight and sink plugins of the `plugins` dir next to
+    /// the config file, run by every draw unless `--no-plugins`.
+    Plugins,
+
+    /// Review today's queue of a deck one file at a time, timing each from
+    /// opening it to grading it.
+    Session {
+        deck: String,
+
+        #[command(flatten)]
+        draw: DrawArgs,
+
+        /// Work in periods of this many minutes, counted down, waiting
+        /// between files and taking a break after each period.
+        #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+        pomodoro: Option<u64>,
+
+        /// Minutes of the breaks between pomodoros.
+        #[arg(
+            long = "break",
+            value_name = "MINUTES",
+            default_value_t = 5,
+            requires = "pomodoro"
+        )]
+        pause: u64,
+    },
+
+    /// Work through today's queue of a deck interactively.
+    Tui {
+        deck: String,
+
+        #[command(flatten)]
+        draw: DrawArgs,
+    },
+
+    /// Take drawn files out of the queue without reviewing them.
+    Skip {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Have files drawn next regardless of weight, until they are reviewed.
+    Pin {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Leave a note on a file, shown whenever it is drawn, or list its notes
+    /// if no text is given.
+    Note {
+        file: String,
+
+        text: Option<String>,
+
+        /// Deck of the file, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+
+        /// Remove the notes of the file.
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+    },
+
+    /// Mark files hard, the highest difficulty, weighing them up by the
+    /// `difficulty_weight` of their deck.
+    Hard {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+
+        /// Leave the difficulty to the grades again.
+        #[arg(long)]
+        clear: bool,
+    },
+
+    /// Stop drawing pinned files first.
+    Unpin {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Put off the next review of files by some days, without reviewing them.
+    Postpone {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Days to put the files off by, counted from when they are due.
+        #[arg(long)]
+        days: u64,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Make files due on a given day, without reviewing them.
+    Schedule {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        #[arg(long, value_name = "DATE")]
+        on: NaiveDate,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Keep files out of every draw until unsuspended.
+    Suspend {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Let suspended files be drawn again.
+    Unsuspend {
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Deck of the files, found by searching the vault if omitted.
+        #[arg(long)]
+        deck: Option<String>,
+    },
+
+    /// Check saved review data for problems, all decks if none are given.
+    Doctor {
+        decks: Vec<String>,
+
+        /// Ask to repair every problem found.
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Salvage the entries of revs files that don't parse, all decks if none
+    /// are given, moving the files aside.
+    Recover { decks: Vec<String> },
+
+    /// Export cards or review data for other tools.
+    Export {
+        #[command(subcommand)]
+        what: commands::export::Export,
+    },
+
+    /// Import reviews made with other tools.
+    Import {
+        #[command(subcommand)]
+        what: commands::import::Import,
+    },
+
+    /// Write a report of every deck, e.g. an HTML dashboard.
+    Report {
+        #[command(subcommand)]
+        what: commands::report::Report,
+    },
+
+    /// Inspect the config and set its keys.
+    Config {
+        #[command(subcommand)]
+        what: commands::config::ConfigCommand,
+    },
+
+    /// Back up review data and the config to a single archive, or restore it.
+    Backup {
+        #[command(subcommand)]
+        what: commands::backup::Backup,
+    },
+
+    /// Commit the revs dir, merge in what the git remote has and push it back.
+    Sync {
+        /// Remote to sync with, `remote` of the `[vault]` table if omitted.
+        remote: Option<String>,
+    },
+
+    /// Merge a revs file edited elsewhere, and the history next to it, into
+    /// the one of the same name.
+    Merge { file: PathBuf },
+
+    /// Copy review data between the JSON storage backend and the SQLite or
+    /// MessagePack one.
+    Migrate {
+        #[arg(value_enum)]
+        direction: commands::migrate::Direction,
+    },
+
+    /// Print a completion script completing subcommands, options, decks and files.
+    Completions {
+        #[arg(value_enum)]
+        shell: commands::completions::Shell,
+    },
+
+    /// Names for completion scripts: `decks`, or `files` of the review data.
+    #[command(name = "__complete", hide = true)]
+    Complete { what: String },
+}
+
+impl Commands {
+    /// Whether the command may save, and so holds the lock of the vault.
+    fn saves(&self) -> bool {
+        !matches!(
+            self,
+            Self::List { .. }
+                | Self::Cram { .. }
+                | Self::Read { .. }
+                | Self::Explain { .. }
+                | Self::Backtest { .. }
+                | Self::Due { .. }
+                | Self::Forecast { .. }
+                | Self::Stats { .. }
+                | Self::Streak
+                | Self::Plugins
+                | Self::History { .. }
+                | Self::Remind { .. }
+                | Self::Digest { .. }
+                | Self::Daemon { .. }
+                | Self::Serve { .. }
+                | Self::Report { .. }
+                | Self::Config { .. }
+                | Self::Export { .. }
+                | Self::Complete { .. }
+                | Self::Note {
+                    text: None,
+                    clear: false,
+                    ..
+                }
+        )
+    }
+}
+
+fn main() {
+    let Cli {
+        command,
+        dry,
+        top,
+        date,
+        format,
+        links,
+        seed,
+        wait,
+        vault,
+        profile,
+    } = parse_cli();
+    set_links(links.unwrap_or_else(Links::detect));
+
+    let today = if let Some(date_str) = date {
+        match NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => Local::now().date_naive(),
+        }
+    } else {
+        Local::now().date_naive()
+    };
+
+    match command {
+        Commands::Completions { shell } => {
+            commands::completions::run(shell, &Cli::command());
+            return;
+        }
+        Commands::Init => {
+            if let Err(e) = commands::init::run(vault, profile, dry) {
+                eprintln!("memo: {}", e);
+                process::exit(1);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let result = Config::load(vault, profile)
+        .map_err(Error::from)
+        .and_then(|config| {
+            set_seed(seed.or(config.review.seed).map(|s| s.value(today)));
+            let _lock = if command.saves() && !dry {
+                Some(Lock::for_saving(&config, wait)?)
+            } else {
+                None
+            };
+            match command {
+                Commands::Review { deck, args } => {
+                    commands::review::run(&config, &deck, &args, format, dry, top, today)
+                }
+                Commands::All { decks, args } => {
+                    commands::all::run(&config, &decks, &args, format, dry, top, today)
+                }
+                Commands::Quiz { deck, count } => {
+                    commands::quiz::run(&config, &deck, count, dry, top, today)
+                }
+                Commands::List {
+                    deck,
+                    sort,
+                    overdue,
+                    new,
+                } => commands::list::run(&config, &deck, sort, overdue, new, format, today),
+                Commands::Cram { deck, tags, count } => {
+                    commands::cram::run(&config, &deck, &tags, count)
+                }
+                Commands::Daily { decks, args } => {
+                    commands::daily::run(&config, &decks, &args, dry, top, today)
+                }
+                Commands::SyncDaily { decks } => commands::daily::sync(&config, &decks, dry, today),
+                Commands::Rate { file, grade, deck } => {
+                    commands::rate::run(&config, &file, grade, deck.as_deref(), dry, today)
+                }
+                Commands::Read { file, deck } => {
+                    commands::read::run(&config, &file, deck.as_deref())
+                }
+                Commands::Done { files, deck } => {
+                    commands::queue::done(&config, &files, deck.as_deref(), dry, today)
+                }
+                Commands::Undo => commands::undo::run(&config, dry),
+                Commands::Rebuild { deck } => commands::rebuild::run(&config, &deck, dry),
+                Commands::Prune { deck } => commands::prune::run(&config, &deck, dry),
+                Commands::Relink { deck } => commands::relink::run(&config, &deck, dry),
+                Commands::Explain { deck, files, json } => {
+                    commands::explain::run(&config, &deck, &files, json, today)
+                }
+                Commands::Optimize { deck } => commands::optimize::run(&config, &deck, dry),
+                Commands::Backtest { decks, schedulers } => {
+                    commands::backtest::run(&config, &decks, &schedulers, format)
+                }
+                Commands::Due {
+                    deck,
+                    days,
+                    retention,
+                } => commands::due::run(&config, &deck, days, retention, today),
+                Commands::Forecast { deck, days } => {
+                    commands::forecast::run(&config, &deck, days, today)
+                }
+                Commands::Pause { from, to } => commands::pause::run(&config, from, to, dry, today),
+                Commands::Stats {
+                    decks,
+                    json,
+                    view,
+                    chart,
+                } => commands::stats::run(&config, &decks, json, &view, &chart, format, today),
+                Commands::Streak => commands::streak::run(&config, today),
+                Commands::Plugins => commands::plugins::run(),
+                Commands::Serve {
+                    port,
+                    host,
+                    token,
+                    allow_origin,
+                } => {
+                    let access = commands::serve::Access {
+                        token,
+                        origins: allow_origin,
+                    };
+                    commands::serve::run(&config, &host, port, &access, dry, top)
+                }
+                Commands::Daemon {
+                    decks,
+                    draw,
+                    interval,
+                } => commands::daemon::run(
+                    &config,
+                    &decks,
+                    &draw,
+                    Duration::from_secs(interval),
+                    dry,
+                    top,
+                ),
+                Commands::Digest { decks, email } => {
+                    commands::digest::run(&config, &decks, email, dry, today)
+                }
+                Commands::Remind { decks, command } => {
+                    commands::remind::run(&config, &decks, command.as_deref(), dry, today)
+                }
+                Commands::History { file, deck } => {
+                    commands::history::run(&config, &file, deck.as_deref(), format, today)
+                }
+                Commands::Session {
+                    deck,
+                    draw,
+                    pomodoro,
+                    pause,
+                } => {
+                    let pomodoro = pomodoro.map(|work| Pomodoro {
+                        work: Duration::from_secs(work * 60),
+                        pause: Duration::from_secs(pause * 60),
+                    });
+                    commands::session::run(&config, &deck, &draw, pomodoro, dry, top, today)
+                }
+                Commands::Tui { deck, draw } => {
+                    commands::tui::run(&config, &deck, &draw, dry, top, today)
+                }
+                Commands::Skip { files, deck } => {
+                    commands::queue::skip(&config, &files, deck.as_deref(), dry)
+                }
+                Commands::Pin { files, deck } => {
+                    commands::pin::run(&config, &files, deck.as_deref(), true, dry)
+                }
+                Commands::Note {
+                    file,
+                    text,
+                    deck,
+                    clear,
+                } => commands::note::run(
+                    &config,
+                    &file,
+                    text.as_deref(),
+                    deck.as_deref(),
+                    clear,
+                    dry,
+                    today,
+                ),
+                Commands::Hard { files, deck, clear } => {
+                    commands::hard::run(&config, &files, deck.as_deref(), !clear, dry)
+                }
+                Commands::Unpin { files, deck } => {
+                    commands::pin::run(&config, &files, deck.as_deref(), false, dry)
+                }
+                Commands::Postpone { files, days, deck } => commands::schedule::run(
+                    &config,
+                    &files,
+                    deck.as_deref(),
+                    When::Later(days),
+                    dry,
+                    today,
+                ),
+                Commands::Schedule { files, on, deck } => commands::schedule::run(
+                    &config,
+                    &files,
+                    deck.as_deref(),
+                    When::On(on),
+                    dry,
+                    today,
+                ),
+                Commands::Suspend { files, deck } => {
+                    commands::suspend::run(&config, &files, deck.as_deref(), true, dry)
+                }
+                Commands::Unsuspend { files, deck } => {
+                    commands::suspend::run(&config, &files, deck.as_deref(), false, dry)
+                }
+                Commands::Doctor { decks, fix } => {
+                    commands::doctor::run(&config, &decks, fix, dry, today)
+                }
+                Commands::Recover { decks } => commands::recover::run(&config, &decks, dry),
+                Commands::Export { what } => commands::export::run(&config, &what, today),
+                Commands::Import { what } => commands::import::run(&config, &what, dry),
+                Commands::Report { what } => commands::report::run(&config, &what, dry, today),
+                Commands::Config { what } => commands::config::run(&config, &what, dry),
+                Commands::Backup { what } => commands::backup::run(&config, &what, dry, today),
+                Commands::Sync { remote } => commands::sync::run(&config, remote, dry, today),
+                Commands::Merge { file } => commands::merge::run(&config, &file, dry),
+                Commands::Migrate { direction } => commands::migrate::run(&config, direction, dry),
+                Commands::Completions { .. } | Commands::Init => Ok(()),
+                Commands::Complete { what } => {
+                    commands::completions::names(&config, &what);
+                    Ok(())
+                }
+            }
+        });
+
+    if let Err(e) = result {
+        eprintln!("memo: {}", e);
+        process::exit(e.exit_code());
+    }
+}
+
+/// Parses the command line, treating an unknown subcommand as a deck so that
+/// `memo 408 ...` means `memo review 408 ...`.
+fn parse_cli() -> Cli {
+    let mut args: Vec<String> = env::args().collect();
+    match Cli::try_parse_from(&args) {
+        Ok(cli) => cli,
+        Err(e) if e.kind() == ErrorKind::InvalidSubcommand => {
+            let Some(ContextValue::String(deck)) = e.get(ContextKind::InvalidSubcommand) else {
+                exit(e);
+            };
+            let Some(pos) = args.iter().position(|a| a == deck) else {
+                exit(e);
+            };
+            args.insert(pos, "review".to_string());
+            Cli::try_parse_from(args).unwrap_or_else(|e| exit(e))
+        }
+        Err(e) => exit(e),
+    }
+}
+
+/// Prints the usage error or help `e` and exits, with status 1 for errors
+/// rather than clap's 2, which stands for nothing to review.
+fn exit(e: clap::Error) -> ! {
+    let _ = e.print();
+    process::exit(if e.use_stderr() { 1 } else { 0 })
+}
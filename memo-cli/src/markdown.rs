@@ -0,0 +1,255 @@
+//! Rendering of Markdown notes for the terminal: headings, lists, quotes,
+//! code blocks, tables and inline emphasis, code and links.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+const BOLD: &str = "\x1b[1m";
+const ITALIC: &str = "\x1b[3m";
+const UNDERLINE: &str = "\x1b[4m";
+const DIM: &str = "\x1b[2m";
+const CODE: &str = "\x1b[36m";
+const HEADING: &str = "\x1b[1;35m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether to style with escape sequences: on terminals that aren't dumb,
+/// unless `NO_COLOR` is set.
+pub fn color() -> bool {
+    let dumb = env::var("TERM").is_ok_and(|t| t == "dumb");
+    io::stdout().is_terminal() && !dumb && env::var_os("NO_COLOR").is_none()
+}
+
+/// `text` rendered line by line, styled if `color`.
+pub fn render(text: &str, color: bool) -> String {
+    let style = Style(color);
+    let mut out = Vec::new();
+    let mut fence: Option<String> = None;
+    let mut table: Vec<Vec<String>> = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = &fence {
+            if trimmed.starts_with(marker.as_str()) {
+                fence = None;
+            } else {
+                out.push(format!("    {}", style.wrap(DIM, line)));
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(trimmed[..3].to_string());
+            let lang = trimmed[3..].trim();
+            if !lang.is_empty() {
+                out.push(format!("    {}", style.wrap(DIM, &format!("[{}]", lang))));
+            }
+            continue;
+        }
+
+        if trimmed.starts_with('|') {
+            table.push(cells(trimmed));
+            continue;
+        }
+        if !table.is_empty() {
+            out.extend(render_table(&table, style));
+            table.clear();
+        }
+
+        out.push(render_line(line, style));
+    }
+    if !table.is_empty() {
+        out.extend(render_table(&table, style));
+    }
+
+    out.join("\n")
+}
+
+#[derive(Clone, Copy)]
+struct Style(bool);
+
+impl Style {
+    fn wrap(self, code: &str, text: &str) -> String {
+        if self.0 {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn render_line(line: &str, style: Style) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    let depth = trimmed.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&depth) && trimmed[depth..].starts_with(' ') {
+        let title = trimmed[depth..].trim().trim_end_matches('#').trim();
+        let title = inline(title, style);
+        return match depth {
+            1 => style.wrap(&format!("{}{}", HEADING, UNDERLINE), &title),
+            _ => style.wrap(HEADING, &title),
+        };
+    }
+
+    let compact = trimmed.replace(' ', "");
+    if compact.len() >= 3
+        && compact.starts_with(['-', '*', '_'])
+        && compact.chars().all(|c| compact.starts_with(c))
+    {
+        return style.wrap(DIM, &"─".repeat(40));
+    }
+
+    if let Some(quote) = trimmed.strip_prefix('>') {
+        let quote = quote.strip_prefix(' ').unwrap_or(quote);
+        return format!(
+            "{}{} {}",
+            indent,
+            style.wrap(DIM, "│"),
+            style.wrap(ITALIC, &inline(quote, style))
+        );
+    }
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(bullet) {
+            let (mark, item) = if let Some(item) = item.strip_prefix("[ ] ") {
+                ("☐", item)
+            } else if let Some(item) = item
+                .strip_prefix("[x] ")
+                .or_else(|| item.strip_prefix("[X] "))
+            {
+                ("☑", item)
+            } else {
+                ("•", item)
+            };
+            return format!("{}  {} {}", indent, mark, inline(item, style));
+        }
+    }
+
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0
+        && let Some(item) = trimmed[digits..]
+            .strip_prefix(". ")
+            .or_else(|| trimmed[digits..].strip_prefix(") "))
+    {
+        return format!(
+            "{}  {}. {}",
+            indent,
+            &trimmed[..digits],
+            inline(item, style)
+        );
+    }
+
+    format!("{}{}", indent, inline(trimmed, style))
+}
+
+/// Inline code, emphasis, wikilinks and links of `text`.
+fn inline(text: &str, style: Style) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let rest: String = chars[i..].iter().collect();
+        if chars[i] == '`'
+            && let Some(end) = rest[1..].find('`')
+        {
+            out.push_str(&style.wrap(CODE, &rest[1..1 + end]));
+            i += rest[..end + 2].chars().count();
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix("[[")
+            && let Some(end) = inner.find("]]")
+        {
+            let link = &inner[..end];
+            let shown = link.split_once('|').map_or(link, |(_, alias)| alias);
+            out.push_str(&style.wrap(UNDERLINE, shown));
+            i += rest[..end + 4].chars().count();
+            continue;
+        }
+        if chars[i] == '['
+            && let Some(close) = rest.find("](")
+            && let Some(end) = rest[close..].find(')')
+        {
+            out.push_str(&style.wrap(UNDERLINE, &inline(&rest[1..close], style)));
+            i += rest[..close + end + 1].chars().count();
+            continue;
+        }
+        // Underscores inside words, as in `snake_case`, are no emphasis.
+        let in_word = i > 0 && chars[i - 1].is_alphanumeric();
+        let mut emphasized = false;
+        for (marker, code) in [("**", BOLD), ("__", BOLD), ("*", ITALIC), ("_", ITALIC)] {
+            if let Some(inner) = rest.strip_prefix(marker)
+                && !(in_word && marker.starts_with('_'))
+                && let Some(end) = inner.find(marker)
+                && end > 0
+                && !inner.starts_with(' ')
+            {
+                out.push_str(&style.wrap(code, &inline(&inner[..end], style)));
+                i += rest[..end + 2 * marker.len()].chars().count();
+                emphasized = true;
+                break;
+            }
+        }
+        if emphasized {
+            continue;
+        }
+
+        out.push(chars[i]);
+        i += 1;
+    }
+
+    out
+}
+
+/// The cells of a table row.
+fn cells(row: &str) -> Vec<String> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = row.strip_suffix('|').unwrap_or(row);
+
+    row.split('|').map(|c| c.trim().to_string()).collect()
+}
+
+/// A table with aligned columns, its header in bold and without the line
+/// under it.
+fn render_table(rows: &[Vec<String>], style: Style) -> Vec<String> {
+    let is_rule = |row: &Vec<String>| {
+        row.iter()
+            .all(|c| !c.is_empty() && c.chars().all(|ch| matches!(ch, '-' | ':')))
+    };
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .filter(|r| !is_rule(r))
+        .map(|r| r.iter().map(|c| inline(c, Style(false))).collect())
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .enumerate()
+        .map(|(n, row)| {
+            let line = widths
+                .iter()
+                .enumerate()
+                .map(|(i, width)| {
+                    let cell = row.get(i).map_or("", String::as_str);
+                    format!("{}{}", cell, " ".repeat(width - cell.chars().count()))
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+            let line = line.trim_end().to_string();
+            if n == 0 && rows.len() > 1 {
+                style.wrap(BOLD, &line)
+            } else {
+                line
+            }
+        })
+        .collect()
+}
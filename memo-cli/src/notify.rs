@@ -0,0 +1,70 @@
+//! Desktop notifications through what the OS ships: `notify-send` on Linux
+//! and the BSDs, `osascript` on macOS and a PowerShell balloon on Windows.
+
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Shows a notification, with a button labeled `action` where the OS lets
+/// one be waited for, which only `notify-send` does. Returns whether the
+/// button was clicked.
+pub fn notify(title: &str, body: &str, action: Option<&str>) -> io::Result<bool> {
+    if cfg!(target_os = "windows") {
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             $n = New-Object System.Windows.Forms.NotifyIcon; \
+             $n.Icon = [System.Drawing.SystemIcons]::Information; \
+             $n.Visible = $true; \
+             $n.ShowBalloonTip(10000, '{}', '{}', 'Info'); \
+             Start-Sleep 10; $n.Dispose()",
+            title.replace('\'', "''"),
+            body.replace('\'', "''")
+        );
+        run(Command::new("powershell").args(["-NoProfile", "-Command", &script]))?;
+        return Ok(false);
+    }
+    if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            quote(body),
+            quote(title)
+        );
+        run(Command::new("osascript").args(["-e", &script]))?;
+        return Ok(false);
+    }
+
+    let mut command = Command::new("notify-send");
+    command.args(["--app-name=memo", title, body]);
+    let Some(action) = action else {
+        run(&mut command)?;
+        return Ok(false);
+    };
+    // Actions came with libnotify 0.7.9, older ones refuse them.
+    let output = Command::new("notify-send")
+        .args(["--app-name=memo", "--wait"])
+        .arg(format!("--action=start={}", action))
+        .args([title, body])
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        run(&mut command)?;
+        return Ok(false);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "start")
+}
+
+fn quote(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn run(command: &mut Command) -> io::Result<()> {
+    let status = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("notifier exited with {}", status)))
+    }
+}
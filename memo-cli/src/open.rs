@@ -0,0 +1,26 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Opens a URI or path with the OS default handler.
+pub fn open(target: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = command
+        .arg(target)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("opener exited with {}", status)))
+    }
+}
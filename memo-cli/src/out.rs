@@ -0,0 +1,60 @@
+//! Printing to stdout that survives a closed pipe, like `memo list 408 | head`.
+//!
+//! Once the reader is gone the rest of the output is dropped, so the command
+//! still saves and releases the lock, and memo exits without a word about it.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether stdout failed, its reader gone or otherwise.
+static CLOSED: AtomicBool = AtomicBool::new(false);
+
+/// Like `print!`, see [`write`].
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::out::write(format_args!($($arg)*))
+    };
+}
+
+/// Like `println!`, see [`write`].
+macro_rules! outln {
+    () => {
+        $crate::out::write(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::out::write(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+/// Writes `args` to the locked stdout, nothing once it failed.
+pub fn write(args: fmt::Arguments) {
+    if CLOSED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = write!(stdout, "{}", args) {
+        fail(e);
+    }
+}
+
+/// Writes `bytes` to the locked stdout as is, nothing once it failed.
+pub fn write_all(bytes: &[u8]) {
+    if CLOSED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = stdout.write_all(bytes).and_then(|()| stdout.flush()) {
+        fail(e);
+    }
+}
+
+/// Stops printing, saying why unless the reader just left.
+fn fail(e: io::Error) {
+    CLOSED.store(true, Ordering::Relaxed);
+    if e.kind() != io::ErrorKind::BrokenPipe {
+        eprintln!("memo: cannot write to stdout: {}", e);
+    }
+}
@@ -0,0 +1,22 @@
+//! The random number generator of draws, seeded by `--seed` or `[review]
+//! seed` to make them reproducible.
+
+use std::sync::OnceLock;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+static SEED: OnceLock<Option<u64>> = OnceLock::new();
+
+/// Sets the seed [`rng`] starts from, random if never set or `None`.
+pub fn set_seed(seed: Option<u64>) {
+    let _ = SEED.set(seed);
+}
+
+/// A generator for a draw, the same one for the same seed.
+pub fn rng() -> StdRng {
+    match SEED.get().copied().flatten() {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    }
+}
@@ -0,0 +1,493 @@
+//! Export of flashcards to Anki, as a text file for its importer or as an
+//! `.apkg` package, which carries their schedules along, and import of what
+//! was reviewed in an Anki collection.
+//!
+//! Notes, note types and decks get IDs derived from what they are, so that
+//! importing again updates what was imported before instead of duplicating it.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use chrono::{DateTime, Days, Local, NaiveDate};
+use serde_json::{Value, json};
+
+use crate::cards::{Card, CardDeck};
+use crate::deck::{self, Deck};
+use crate::hash::{fnv1a, sha1};
+use crate::sqlite::{exec, quote};
+use crate::{frontmatter, scan, sections, zip};
+
+/// An Anki note made of a card.
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub guid: String,
+    /// Of the Cloze note type rather than Basic.
+    pub cloze: bool,
+    /// Front, or text with deletions for cloze notes, as HTML.
+    pub front: String,
+    /// Back, empty for cloze notes, as HTML.
+    pub back: String,
+    pub tags: Vec<String>,
+    pub schedule: Option<Schedule>,
+}
+
+/// Where a card is in its schedule, for cards reviewed before.
+#[derive(Debug, Clone, Copy)]
+pub struct Schedule {
+    pub due: NaiveDate,
+    pub interval: u32,
+    /// Ease in permille, as Anki keeps it.
+    pub factor: u32,
+    pub reviews: u32,
+}
+
+/// The Anki name of `deck`, its sub-decks separated by `::`.
+pub fn deck_name(deck: &str) -> String {
+    deck.split('/').collect::<Vec<_>>().join("::")
+}
+
+/// Every card of `deck` as an Anki note, tagged by the frontmatter tags of
+/// its note.
+pub fn notes(deck: &Deck, cards: &CardDeck) -> Vec<Note> {
+    let tags_of = |file_name: &str| -> Vec<String> {
+        let Some(file) = deck
+            .files
+            .iter()
+            .map(|f| sections::split(f).0)
+            .find(|f| scan::file_stem(f) == file_name)
+        else {
+            return Vec::new();
+        };
+        let text = fs::read_to_string(file).unwrap_or_default();
+        frontmatter::parse(&text)
+            .0
+            .tags()
+            .iter()
+            .map(|t| t.replace('/', "::").replace(char::is_whitespace, "_"))
+            .collect()
+    };
+
+    cards
+        .cards
+        .iter()
+        .map(|card| {
+            let schedule = cards.review_data.get(&card.id).and_then(|info| {
+                let last = info.last_reviewed?;
+                let interval = deck::interval(info, &cards.settings).unwrap_or(1);
+                Some(Schedule {
+                    due: last + Days::new(interval as u64),
+                    interval,
+                    factor: info.sm2.as_ref().map_or(2500, |s| (s.ease * 1000.0) as u32),
+                    reviews: info.review_count,
+                })
+            });
+            note(card, tags_of(&card.file_name), schedule)
+        })
+        .collect()
+}
+
+fn note(card: &Card, tags: Vec<String>, schedule: Option<Schedule>) -> Note {
+    let (front, back) = match &card.cloze {
+        Some(cloze) => (html(cloze), String::new()),
+        None => (html(&card.question), html(&card.answer)),
+    };
+
+    Note {
+        guid: format!("memo-{:016x}", fnv1a(card.id.as_bytes())),
+        cloze: card.cloze.is_some(),
+        front,
+        back,
+        tags,
+        schedule,
+    }
+}
+
+fn html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+/// `notes` as a file for Anki's text importer, which takes them into
+/// `anki_deck` without their schedules.
+pub fn tsv(notes: &[Note], anki_deck: &str) -> String {
+    let mut out = String::from(
+        "#separator:tab\n#html:true\n#guid column:1\n#notetype column:2\n#deck column:3\n#tags column:6\n",
+    );
+    for note in notes {
+        let fields = [
+            note.guid.as_str(),
+            if note.cloze { "Cloze" } else { "Basic" },
+            anki_deck,
+            &note.front,
+            &note.back,
+            &note.tags.join(" "),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| f.replace('\t', " ")).collect();
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+
+    out
+}
+
+/// `notes` as an `.apkg` package of `anki_deck`, their cards due as
+/// scheduled, new if never reviewed.
+pub fn apkg(notes: &[Note], anki_deck: &str, today: NaiveDate) -> io::Result<Vec<u8>> {
+    let created = today.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let now = created.timestamp();
+    let deck_id = id(&format!("deck {}", anki_deck));
+    let (basic_id, cloze_id) = (id("memo basic"), id("memo cloze"));
+
+    let mut sql = String::from(SCHEMA);
+    sql.push_str(&format!(
+        "INSERT INTO col VALUES (1, {}, {}, {}, 11, 0, 0, 0, {}, {}, {}, {}, '{{}}');\n",
+        now,
+        now * 1000,
+        now * 1000,
+        quote(&collection_conf(deck_id).to_string()),
+        quote(
+            &json!({
+                basic_id.to_string(): model(basic_id, false, deck_id, now),
+                cloze_id.to_string(): model(cloze_id, true, deck_id, now),
+            })
+            .to_string()
+        ),
+        quote(
+            &json!({
+                "1": deck_json(1, "Default", now),
+                deck_id.to_string(): deck_json(deck_id, anki_deck, now),
+            })
+            .to_string()
+        ),
+        quote(&json!({ "1": deck_conf() }).to_string()),
+    ));
+
+    for (position, note) in notes.iter().enumerate() {
+        let note_id = id(&note.guid);
+        let sort_field = strip_html(&note.front);
+        let digest = sha1(sort_field.as_bytes());
+        let checksum = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+        let tags = if note.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" {} ", note.tags.join(" "))
+        };
+        sql.push_str(&format!(
+            "INSERT INTO notes VALUES ({}, {}, {}, {}, -1, {}, {}, {}, {}, 0, '');\n",
+            note_id,
+            quote(&note.guid),
+            if note.cloze { cloze_id } else { basic_id },
+            now,
+            quote(&tags),
+            quote(&format!("{}\u{1f}{}", note.front, note.back)),
+            quote(&sort_field),
+            checksum,
+        ));
+
+        let ords: Vec<u32> = if note.cloze {
+            deletions(&note.front).into_iter().map(|n| n - 1).collect()
+        } else {
+            vec![0]
+        };
+        for ord in ords {
+            let (kind, due, interval, factor, reviews) = match note.schedule {
+                Some(s) => (
+                    2,
+                    (s.due - today).num_days(),
+                    s.interval,
+                    s.factor,
+                    s.reviews,
+                ),
+                None => (0, position as i64 + 1, 0, 0, 0),
+            };
+            sql.push_str(&format!(
+                "INSERT INTO cards VALUES ({}, {}, {}, {}, {}, -1, {}, {}, {}, {}, {}, {}, 0, 0, 0, 0, 0, '');\n",
+                note_id + ord as i64,
+                note_id,
+                deck_id,
+                ord,
+                now,
+                kind,
+                kind,
+                due,
+                interval,
+                factor,
+                reviews,
+            ));
+        }
+    }
+
+    let db = env::temp_dir().join(format!("memo-{}.anki2", process::id()));
+    let _ = fs::remove_file(&db);
+    let collection = exec(&db, &sql).and_then(|_| fs::read(&db));
+    let _ = fs::remove_file(&db);
+
+    zip::archive(&[("collection.anki2", &collection?), ("media", b"{}")])
+}
+
+/// A note of an Anki collection with how its cards were reviewed.
+#[derive(Debug, Clone)]
+pub struct Reviewed {
+    /// Names and text of its fields, without HTML.
+    pub fields: Vec<(String, String)>,
+    /// Reviews of its most reviewed card.
+    pub reviews: u32,
+    pub last_reviewed: Option<NaiveDate>,
+}
+
+/// The notes of the Anki `collection` with cards reviewed at least once.
+pub fn reviewed(collection: &Path) -> io::Result<Vec<Reviewed>> {
+    if !collection.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no Anki collection at {}", collection.display()),
+        ));
+    }
+    let out = exec(
+        collection,
+        ".mode json
+SELECT crt, models FROM col;
+SELECT n.mid AS mid, n.flds AS flds, max(c.reps) AS reps,
+    max(CASE WHEN c.type = 2 THEN c.due - c.ivl END) AS reviewed_day,
+    (SELECT max(r.id) FROM revlog r JOIN cards rc ON r.cid = rc.id WHERE rc.nid = n.id) AS reviewed_at
+FROM notes n JOIN cards c ON c.nid = n.id
+GROUP BY n.id HAVING max(c.reps) > 0;
+",
+    )?;
+    let mut results = serde_json::Deserializer::from_str(&out).into_iter::<Vec<Value>>();
+    let col = results.next().transpose()?.unwrap_or_default();
+    let notes = results.next().transpose()?.unwrap_or_default();
+    let col = col.first().cloned().unwrap_or_default();
+    let created = DateTime::from_timestamp(col["crt"].as_i64().unwrap_or_default(), 0)
+        .map(|t| t.with_timezone(&Local).date_naive());
+
+    // Field names by note type, kept in their own table by newer versions.
+    let mut names: Vec<(i64, Vec<String>)> =
+        serde_json::from_str::<Value>(col["models"].as_str().unwrap_or("{}"))
+            .ok()
+            .and_then(|m| m.as_object().cloned())
+            .unwrap_or_default()
+            .values()
+            .map(|m| {
+                let fields = m["flds"].as_array().cloned().unwrap_or_default();
+                let fields = fields
+                    .iter()
+                    .filter_map(|f| f["name"].as_str().map(str::to_string))
+                    .collect();
+                (m["id"].as_i64().unwrap_or_default(), fields)
+            })
+            .collect();
+    if names.is_empty()
+        && let Ok(out) = exec(
+            collection,
+            ".mode json\nSELECT ntid, name FROM fields ORDER BY ntid, ord;\n",
+        )
+    {
+        for field in serde_json::from_str::<Vec<Value>>(&out).unwrap_or_default() {
+            let ntid = field["ntid"].as_i64().unwrap_or_default();
+            let name = field["name"].as_str().unwrap_or_default().to_string();
+            match names.iter_mut().find(|(id, _)| *id == ntid) {
+                Some((_, fields)) => fields.push(name),
+                None => names.push((ntid, vec![name])),
+            }
+        }
+    }
+
+    Ok(notes
+        .iter()
+        .map(|n| {
+            let mid = n["mid"].as_i64().unwrap_or_default();
+            let names = names
+                .iter()
+                .find(|(id, _)| *id == mid)
+                .map(|(_, f)| f.as_slice())
+                .unwrap_or_default();
+            let fields = n["flds"]
+                .as_str()
+                .unwrap_or_default()
+                .split('\u{1f}')
+                .enumerate()
+                .map(|(i, text)| {
+                    let name = names.get(i).cloned().unwrap_or_else(|| (i + 1).to_string());
+                    (name, strip_html(text).trim().to_string())
+                })
+                .collect();
+            let reviewed_at = n["reviewed_at"]
+                .as_i64()
+                .and_then(DateTime::from_timestamp_millis)
+                .map(|t| t.with_timezone(&Local).date_naive());
+            let reviewed_day = n["reviewed_day"]
+                .as_i64()
+                .zip(created)
+                .and_then(|(day, created)| created.checked_add_signed(chrono::Duration::days(day)));
+
+            Reviewed {
+                fields,
+                reviews: n["reps"].as_u64().unwrap_or_default() as u32,
+                last_reviewed: reviewed_at.or(reviewed_day),
+            }
+        })
+        .collect())
+}
+
+/// An ID for `what`, positive and below 2^52 to stay exact in JavaScript,
+/// with room after it for the cards of a note.
+fn id(what: &str) -> i64 {
+    ((fnv1a(what.as_bytes()) >> 12) & !0xff) as i64
+}
+
+/// Numbers of the `{{c<N>::` deletions of `text`.
+fn deletions(text: &str) -> BTreeSet<u32> {
+    text.match_indices("{{c")
+        .filter_map(|(i, _)| {
+            let rest = &text[i + 3..];
+            let digits = rest.chars().take_while(char::is_ascii_digit).count();
+            rest[digits..]
+                .starts_with("::")
+                .then(|| rest[..digits].parse().ok())
+                .flatten()
+        })
+        .filter(|n| *n > 0)
+        .collect()
+}
+
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.replace("<br>", " ").replace("&nbsp;", " ").chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+const SCHEMA: &str = "CREATE TABLE col (id integer primary key, crt integer not null, mod integer not null, scm integer not null, ver integer not null, dty integer not null, usn integer not null, ls integer not null, conf text not null, models text not null, decks text not null, dconf text not null, tags text not null);
+CREATE TABLE notes (id integer primary key, guid text not null, mid integer not null, mod integer not null, usn integer not null, tags text not null, flds text not null, sfld integer not null, csum integer not null, flags integer not null, data text not null);
+CREATE TABLE cards (id integer primary key, nid integer not null, did integer not null, ord integer not null, mod integer not null, usn integer not null, type integer not null, queue integer not null, due integer not null, ivl integer not null, factor integer not null, reps integer not null, lapses integer not null, left integer not null, odue integer not null, odid integer not null, flags integer not null, data text not null);
+CREATE TABLE revlog (id integer primary key, cid integer not null, usn integer not null, ease integer not null, ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null, type integer not null);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+";
+
+fn collection_conf(deck_id: i64) -> serde_json::Value {
+    json!({
+        "activeDecks": [deck_id],
+        "curDeck": deck_id,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "curModel": null,
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+    })
+}
+
+fn model(id: i64, cloze: bool, deck_id: i64, now: i64) -> serde_json::Value {
+    let field = |name: &str, ord: u32| {
+        json!({
+            "name": name, "ord": ord, "sticky": false, "rtl": false,
+            "font": "Arial", "size": 20, "media": [],
+        })
+    };
+    let (name, fields, template) = if cloze {
+        (
+            "memo Cloze",
+            vec![field("Text", 0), field("Back Extra", 1)],
+            ("{{cloze:Text}}", "{{cloze:Text}}<br>\n{{Back Extra}}"),
+        )
+    } else {
+        (
+            "memo Basic",
+            vec![field("Front", 0), field("Back", 1)],
+            ("{{Front}}", "{{FrontSide}}\n\n<hr id=answer>\n\n{{Back}}"),
+        )
+    };
+
+    json!({
+        "id": id,
+        "name": name,
+        "type": if cloze { 1 } else { 0 },
+        "mod": now,
+        "usn": -1,
+        "sortf": 0,
+        "did": deck_id,
+        "tmpls": [{
+            "name": if cloze { "Cloze" } else { "Card 1" },
+            "ord": 0,
+            "qfmt": template.0,
+            "afmt": template.1,
+            "bqfmt": "",
+            "bafmt": "",
+            "did": null,
+        }],
+        "flds": fields,
+        "css": ".card { font-family: arial; font-size: 20px; text-align: center; }\n.cloze { font-weight: bold; color: blue; }",
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "latexsvg": false,
+        "tags": [],
+        "vers": [],
+        "req": [[0, "any", [0]]],
+    })
+}
+
+fn deck_json(id: i64, name: &str, now: i64) -> serde_json::Value {
+    json!({
+        "id": id,
+        "name": name,
+        "mod": now,
+        "usn": -1,
+        "lrnToday": [0, 0],
+        "revToday": [0, 0],
+        "newToday": [0, 0],
+        "timeToday": [0, 0],
+        "collapsed": false,
+        "browserCollapsed": false,
+        "desc": "",
+        "dyn": 0,
+        "conf": 1,
+        "extendNew": 0,
+        "extendRev": 0,
+    })
+}
+
+fn deck_conf() -> serde_json::Value {
+    json!({
+        "id": 1,
+        "name": "Default",
+        "mod": 0,
+        "usn": 0,
+        "maxTaken": 60,
+        "autoplay": true,
+        "timer": 0,
+        "replayq": true,
+        "dyn": false,
+        "new": {
+            "delays": [1.0, 10.0], "ints": [1, 4, 0], "initialFactor": 2500,
+            "order": 1, "perDay": 20, "bury": false,
+        },
+        "rev": {
+            "perDay": 200, "ease4": 1.3, "ivlFct": 1.0, "maxIvl": 36500,
+            "bury": false, "hardFactor": 1.2,
+        },
+        "lapse": {
+            "delays": [10.0], "mult": 0.0, "minInt": 1, "leechFails": 8, "leechAction": 1,
+        },
+    })
+}
@@ -0,0 +1,138 @@
+//! Replaying the review history under another scheduler, to see what it
+//! would have made of the same grades.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::config::DeckConfig;
+use crate::deck;
+use crate::history::Event;
+use crate::review::{Grade, ReviewInfo};
+use crate::scheduler;
+use crate::weight::DUE_RETENTION;
+
+/// A review following an earlier one of the same file, as the replayed
+/// scheduler saw it coming.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+    /// Days since the review before.
+    pub elapsed: i64,
+    /// Retention the scheduler expected at the review, `None` if it has no
+    /// notion of it.
+    pub predicted: Option<f64>,
+    pub recalled: bool,
+}
+
+/// What replaying some history under a scheduler came to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    pub predictions: Vec<Prediction>,
+    /// Days to the next review after every review replayed, if the scheduler
+    /// has intervals.
+    pub intervals: Vec<u32>,
+    /// Days to the next review of every file after its last review.
+    pub last_intervals: Vec<u32>,
+}
+
+/// Feeds `events` in order to the scheduler of `settings`, as if it had been
+/// the deck's all along.
+pub fn replay(events: &[Event], settings: &DeckConfig) -> Replay {
+    let scheduler = scheduler::of(settings);
+    let mut events: Vec<&Event> = events.iter().collect();
+    events.sort_by_key(|e| e.date);
+
+    let mut infos: HashMap<&str, ReviewInfo> = HashMap::new();
+    let mut replay = Replay::default();
+    for event in events {
+        let info = infos
+            .entry(&event.file_name)
+            .or_insert_with(|| ReviewInfo::new(event.file_name.clone()));
+        if let Some(last) = info.last_reviewed {
+            let elapsed = (event.date - last).num_days().max(0);
+            let predicted = scheduler
+                .breakdown(Some(info), 0, event.date, elapsed)
+                .retention
+                .or_else(|| {
+                    // Retention falling to the due one over the interval.
+                    let interval = scheduler.interval(info)?.max(1);
+                    Some(DUE_RETENTION.powf(elapsed as f64 / interval as f64))
+                });
+            replay.predictions.push(Prediction {
+                elapsed,
+                predicted,
+                recalled: event.grade != Grade::Again,
+            });
+        }
+
+        deck::record(info, event.grade, event.date, settings);
+        replay.intervals.extend(deck::interval(info, settings));
+    }
+    replay.last_intervals = infos
+        .values()
+        .filter_map(|i| deck::interval(i, settings))
+        .collect();
+
+    replay
+}
+
+/// The figures of a [`Replay`] to compare schedulers by.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct Outcome {
+    pub reviews: usize,
+    /// Share of the reviews recalled, graded anything but `again`.
+    pub recall: f64,
+    /// Mean retention the scheduler expected at those reviews.
+    pub predicted: Option<f64>,
+    /// Mean negative log-likelihood of the grades under the expected
+    /// retention, lower is better.
+    pub log_loss: Option<f64>,
+    /// Mean days to the next review the scheduler asked for.
+    pub mean_interval: Option<f64>,
+    /// Reviews a day it would ask for once every file is on its schedule.
+    pub daily_reviews: Option<f64>,
+}
+
+impl Replay {
+    /// Adds `other`, the replay of another deck, to this one.
+    pub fn extend(&mut self, other: Replay) {
+        self.predictions.extend(other.predictions);
+        self.intervals.extend(other.intervals);
+        self.last_intervals.extend(other.last_intervals);
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        let reviews = self.predictions.len();
+        let mean = |values: &[f64]| {
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        };
+        let predicted: Vec<(f64, bool)> = self
+            .predictions
+            .iter()
+            .filter_map(|p| Some((p.predicted?, p.recalled)))
+            .collect();
+        let losses: Vec<f64> = predicted
+            .iter()
+            .map(|(p, recalled)| {
+                let p = p.clamp(1e-6, 1.0 - 1e-6);
+                -if *recalled { p.ln() } else { (1.0 - p).ln() }
+            })
+            .collect();
+        let intervals: Vec<f64> = self.intervals.iter().map(|i| *i as f64).collect();
+
+        Outcome {
+            reviews,
+            recall: self.predictions.iter().filter(|p| p.recalled).count() as f64
+                / reviews.max(1) as f64,
+            predicted: mean(&predicted.iter().map(|(p, _)| *p).collect::<Vec<_>>()),
+            log_loss: mean(&losses),
+            mean_interval: mean(&intervals),
+            daily_reviews: (!self.last_intervals.is_empty()).then(|| {
+                self.last_intervals
+                    .iter()
+                    .map(|i| 1.0 / (*i).max(1) as f64)
+                    .sum()
+            }),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Spreading due dates so that reviews don't pile up on single days.
+
+use std::collections::HashMap;
+
+use chrono::{Days, NaiveDate};
+
+use crate::config::DeckConfig;
+
+/// Nearest days a due date may move to when the fuzz window is full.
+const MAX_PUSH: i64 = 7;
+
+/// The day a file reviewed `today` and scheduled for `interval` days later
+/// is due, given how many files are already due on each day.
+///
+/// The least busy day within `fuzz` of the interval wins, the nearest to
+/// the scheduled day on ties. If that day is at `max_due_per_day`, the
+/// nearest day with room within a week, or the interval if longer, is
+/// taken instead. Never earlier than tomorrow.
+pub fn due_day(
+    today: NaiveDate,
+    interval: u32,
+    load: &HashMap<NaiveDate, usize>,
+    settings: &DeckConfig,
+) -> NaiveDate {
+    let interval = i64::from(interval.max(1));
+    let count = |offset: i64| {
+        load.get(&(today + Days::new(offset as u64)))
+            .copied()
+            .unwrap_or(0)
+    };
+    // Offsets from today, nearest to the interval first, later before earlier.
+    let around = |spread: i64| {
+        (0..=spread)
+            .flat_map(move |d| [interval + d, interval - d])
+            .filter(|&o| o >= 1)
+    };
+
+    let spread = (interval as f64 * settings.fuzz).round() as i64;
+    let mut offset = around(spread).min_by_key(|&o| count(o)).unwrap_or(interval);
+    if let Some(cap) = settings.max_due_per_day
+        && count(offset) >= cap
+    {
+        offset = around(MAX_PUSH.max(interval))
+            .find(|&o| count(o) < cap)
+            .unwrap_or(offset);
+    }
+
+    today + Days::new(offset as u64)
+}
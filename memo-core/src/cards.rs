@@ -0,0 +1,285 @@
+//! Flashcards embedded in notes, scheduled one by one.
+//!
+//! Three forms are recognized: single `question::answer` lines, a line
+//! tagged `#flashcard` whose answer is the lines following it up to the next
+//! blank line, and lines with Anki's `{{c1::cloze}}` deletions, asked with
+//! the deletions hidden.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+
+use crate::config::{Config, DeckConfig};
+use crate::deck::{self, Deck};
+use crate::error::Error;
+use crate::hash::fnv1a;
+use crate::review::{Grade, ReviewData, ReviewInfo};
+use crate::store::{self, Storage};
+use crate::{scan, sections, sr, weight};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Card {
+    /// `<file stem>::<hash of the question>`, the key of its review data.
+    pub id: String,
+    pub file_name: String,
+    pub question: String,
+    pub answer: String,
+    /// The line with its deletions, for cloze cards.
+    pub cloze: Option<String>,
+    /// Index of the line the card ends on.
+    pub line: usize,
+    /// Whether its answer is on the lines below it.
+    pub multiline: bool,
+    /// The schedule the Spaced Repetition plugin for Obsidian left in an
+    /// `<!--SR:...-->` comment after the card, see [`sr`](crate::sr).
+    pub sr: Option<String>,
+}
+
+impl Card {
+    pub fn words(&self) -> usize {
+        self.question.split_whitespace().count() + self.answer.split_whitespace().count()
+    }
+}
+
+/// Every card in the Markdown `text` of the file named `file_name`.
+pub fn parse(file_name: &str, text: &str) -> Vec<Card> {
+    let mut cards = Vec::new();
+    let mut in_code = false;
+    let mut lines = text.lines().enumerate().peekable();
+
+    while let Some((i, line)) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let (trimmed, sr) = sr_comment(trimmed);
+
+        if let Some((question, answer)) = cloze(trimmed) {
+            let mut card = card(file_name, &question, &answer, i);
+            card.id = format!("{}::{:08x}", file_name, fnv1a(trimmed.as_bytes()) as u32);
+            card.cloze = Some(trimmed.to_string());
+            card.sr = sr;
+            cards.push(card);
+        } else if let Some(question) = trimmed.strip_suffix("#flashcard") {
+            let mut answer = Vec::new();
+            let mut last = i;
+            let mut sr = None;
+            while let Some((j, next)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+                match sr_comment(next.trim()) {
+                    ("", comment @ Some(_)) => sr = comment,
+                    (next, _) => {
+                        answer.push(next);
+                        last = j;
+                    }
+                }
+            }
+            let question = question.trim().trim_start_matches('#').trim();
+            if !question.is_empty() && !answer.is_empty() {
+                let mut card = card(file_name, question, &answer.join("\n"), last);
+                card.multiline = true;
+                card.sr = sr;
+                cards.push(card);
+            }
+        } else if let Some((question, answer)) = trimmed.split_once("::") {
+            let (question, answer) = (question.trim(), answer.trim());
+            if !question.is_empty() && !answer.is_empty() {
+                let mut card = card(file_name, question, answer, i);
+                card.sr = sr;
+                cards.push(card);
+            }
+        }
+    }
+
+    cards
+}
+
+/// `line` without the `<!--SR:...-->` comment at its end, and what is in it.
+fn sr_comment(line: &str) -> (&str, Option<String>) {
+    match line.rfind("<!--SR:") {
+        Some(start) if line.ends_with("-->") => (
+            line[..start].trim_end(),
+            Some(line[start + 7..line.len() - 3].to_string()),
+        ),
+        _ => (line, None),
+    }
+}
+
+fn card(file_name: &str, question: &str, answer: &str, line: usize) -> Card {
+    Card {
+        id: format!("{}::{:08x}", file_name, fnv1a(question.as_bytes()) as u32),
+        file_name: file_name.to_string(),
+        question: question.to_string(),
+        answer: answer.to_string(),
+        cloze: None,
+        line,
+        multiline: false,
+        sr: None,
+    }
+}
+
+/// The line with its `{{c<N>::text}}` or `{{c<N>::text::hint}}` deletions
+/// hidden, by their hints if any, and revealed, or `None` without any.
+fn cloze(line: &str) -> Option<(String, String)> {
+    let (mut hidden, mut revealed) = (String::new(), String::new());
+    let mut rest = line;
+    let mut found = false;
+
+    while let Some(start) = rest.find("{{c") {
+        let after = &rest[start + 3..];
+        let digits = after.chars().take_while(char::is_ascii_digit).count();
+        let Some(inner) = after[digits..].strip_prefix("::").filter(|_| digits > 0) else {
+            hidden.push_str(&rest[..start + 3]);
+            revealed.push_str(&rest[..start + 3]);
+            rest = after;
+            continue;
+        };
+        let Some(end) = inner.find("}}") else {
+            break;
+        };
+        let (text, hint) = inner[..end]
+            .split_once("::")
+            .unwrap_or((&inner[..end], "..."));
+        hidden.push_str(&rest[..start]);
+        hidden.push_str(&format!("[{}]", hint));
+        revealed.push_str(&rest[..start]);
+        revealed.push_str(text);
+        rest = &inner[end + 2..];
+        found = true;
+    }
+    hidden.push_str(rest);
+    revealed.push_str(rest);
+
+    found.then_some((hidden, revealed))
+}
+
+/// The cards of a deck together with their review data, kept as
+/// `cards-<deck>` next to the deck's revs data.
+pub struct CardDeck {
+    pub cards: Vec<Card>,
+    pub review_data: ReviewData,
+    pub storage: Box<dyn Storage>,
+    pub settings: DeckConfig,
+
+    /// The notes the cards are in.
+    notes: Vec<PathBuf>,
+
+    /// IDs of the cards rated since opening.
+    rated: Vec<String>,
+}
+
+impl CardDeck {
+    pub fn open(config: &Config, deck: &Deck) -> Result<Self, Error> {
+        let storage = store::open(config, &format!("cards-{}", deck::slug(&deck.name)));
+        let notes = notes(&deck.files);
+        let cards = collect(&deck.files);
+
+        let loaded = storage.load().map_err(Error::Storage)?;
+        let review_data = cards
+            .iter()
+            .map(|c| {
+                let info = loaded
+                    .iter()
+                    .find(|r| r.file_name == c.id)
+                    .cloned()
+                    .unwrap_or_else(|| ReviewInfo::new(c.id.clone()));
+                (c.id.clone(), info)
+            })
+            .collect();
+
+        Ok(Self {
+            cards,
+            review_data,
+            storage,
+            settings: deck.settings.clone(),
+            notes,
+            rated: Vec::new(),
+        })
+    }
+
+    /// Every card with its weight under the deck's scheduler.
+    pub fn weights(&self, today: NaiveDate) -> Vec<(Card, usize)> {
+        let max_out = weight::max_out(
+            self.cards.len(),
+            self.settings.files_per_day,
+            self.settings.max_out_factor,
+        );
+        self.cards
+            .iter()
+            .map(|c| {
+                let info = self.review_data.get(&c.id);
+                (
+                    c.clone(),
+                    deck::weight_of(info, c.words(), today, max_out, &self.settings),
+                )
+            })
+            .collect()
+    }
+
+    /// Feeds a grade into the scheduler, returning the days until the card is due.
+    pub fn rate(&mut self, card: &Card, grade: Grade, today: NaiveDate) -> u32 {
+        let info = self
+            .review_data
+            .entry(card.id.clone())
+            .or_insert_with(|| ReviewInfo::new(card.id.clone()));
+
+        self.rated.push(card.id.clone());
+        deck::record(info, grade, today, &self.settings)
+    }
+
+    pub fn save(&mut self) -> Result<(), Error> {
+        self.storage
+            .save(&self.review_data)
+            .map_err(Error::Storage)?;
+        if self.settings.write_sr {
+            for note in &self.notes {
+                let file_name = scan::file_stem(note);
+                let schedules: Vec<(String, sr::Schedule)> = self
+                    .cards
+                    .iter()
+                    .filter(|c| c.file_name == file_name && self.rated.contains(&c.id))
+                    .filter_map(|c| {
+                        let info = self.review_data.get(&c.id)?;
+                        let interval = deck::interval(info, &self.settings);
+                        Some((c.id.clone(), sr::Schedule::of_info(info, None, interval)?))
+                    })
+                    .collect();
+                if !schedules.is_empty() {
+                    sr::write_cards(note, &schedules).map_err(Error::Storage)?;
+                }
+            }
+        }
+        self.rated.clear();
+
+        Ok(())
+    }
+}
+
+/// The notes of `files`, once for those split by heading.
+fn notes(files: &[PathBuf]) -> Vec<PathBuf> {
+    let mut notes: Vec<PathBuf> = Vec::new();
+    for file in files {
+        let note = sections::split(file).0;
+        if !notes.contains(&note) {
+            notes.push(note);
+        }
+    }
+
+    notes
+}
+
+/// Every card in `files`, once per note for those split by heading.
+pub fn collect(files: &[PathBuf]) -> Vec<Card> {
+    notes(files)
+        .iter()
+        .flat_map(|f| parse(&scan::file_stem(f), &read(f)))
+        .collect()
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_default()
+}
@@ -0,0 +1,472 @@
+//! Charts of counts and retention over days, as SVG or PNG, and a calendar
+//! heatmap of reviews as SVG.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Datelike, Days, NaiveDate};
+
+use crate::png;
+
+/// Fill colors of the heatmap, from no reviews to the busiest days.
+const HEAT: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+
+const BAR: &str = "#4c78a8";
+
+/// Colors of the series of line charts, in turn.
+const PALETTE: [&str; 6] = [
+    "#4c78a8", "#f58518", "#54a24b", "#e45756", "#72b7b2", "#b279a2",
+];
+
+/// Glyphs of the font pictures are labeled in, three pixels wide and five
+/// high, a row a byte.
+const FONT: [(char, [u8; 5]); 42] = [
+    ('0', [7, 5, 5, 5, 7]),
+    ('1', [2, 6, 2, 2, 7]),
+    ('2', [7, 1, 7, 4, 7]),
+    ('3', [7, 1, 7, 1, 7]),
+    ('4', [5, 5, 7, 1, 1]),
+    ('5', [7, 4, 7, 1, 7]),
+    ('6', [7, 4, 7, 5, 7]),
+    ('7', [7, 1, 1, 1, 1]),
+    ('8', [7, 5, 7, 5, 7]),
+    ('9', [7, 5, 7, 1, 7]),
+    ('-', [0, 0, 7, 0, 0]),
+    ('.', [0, 0, 0, 0, 2]),
+    (':', [0, 2, 0, 2, 0]),
+    ('%', [5, 1, 2, 4, 5]),
+    ('/', [1, 1, 2, 4, 4]),
+    ('_', [0, 0, 0, 0, 7]),
+    ('a', [2, 5, 7, 5, 5]),
+    ('b', [6, 5, 6, 5, 6]),
+    ('c', [3, 4, 4, 4, 3]),
+    ('d', [6, 5, 5, 5, 6]),
+    ('e', [7, 4, 6, 4, 7]),
+    ('f', [7, 4, 6, 4, 4]),
+    ('g', [3, 4, 5, 5, 3]),
+    ('h', [5, 5, 7, 5, 5]),
+    ('i', [7, 2, 2, 2, 7]),
+    ('j', [1, 1, 1, 5, 2]),
+    ('k', [5, 5, 6, 5, 5]),
+    ('l', [4, 4, 4, 4, 7]),
+    ('m', [5, 7, 7, 5, 5]),
+    ('n', [6, 5, 5, 5, 5]),
+    ('o', [2, 5, 5, 5, 2]),
+    ('p', [6, 5, 6, 4, 4]),
+    ('q', [2, 5, 5, 6, 3]),
+    ('r', [6, 5, 6, 5, 5]),
+    ('s', [3, 4, 2, 1, 6]),
+    ('t', [7, 2, 2, 2, 2]),
+    ('u', [5, 5, 5, 5, 7]),
+    ('v', [5, 5, 5, 5, 2]),
+    ('w', [5, 5, 7, 7, 5]),
+    ('x', [5, 5, 2, 5, 5]),
+    ('y', [5, 5, 2, 2, 2]),
+    ('z', [7, 1, 2, 4, 7]),
+];
+
+/// Pixels a font pixel takes in pictures, making glyphs 10 high.
+const SCALE: usize = 2;
+
+/// A chart laid out once, to be written as SVG or as a PNG picture.
+pub struct Plot {
+    pub width: u32,
+    pub height: u32,
+    shapes: Vec<Shape>,
+}
+
+enum Shape {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        color: &'static str,
+        /// Shown on hover in SVG.
+        title: Option<String>,
+    },
+    Line {
+        points: Vec<(f64, f64)>,
+        color: &'static str,
+    },
+    /// Text by its baseline, ending at `x` if `end`.
+    Text {
+        x: f64,
+        y: f64,
+        text: String,
+        color: &'static str,
+        end: bool,
+    },
+}
+
+impl Plot {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            shapes: Vec::new(),
+        }
+    }
+
+    fn text(&mut self, x: f64, y: f64, text: String, end: bool) {
+        self.shapes.push(Shape::Text {
+            x,
+            y,
+            text,
+            color: "#222222",
+            end,
+        });
+    }
+
+    pub fn svg(&self) -> String {
+        let mut svg = open(self.width, self.height);
+        for shape in &self.shapes {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color,
+                    title,
+                } => {
+                    svg.push_str(&format!(
+                        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">",
+                        x, y, width, height, color
+                    ));
+                    if let Some(title) = title {
+                        svg.push_str(&format!("<title>{}</title>", escape(title)));
+                    }
+                    svg.push_str("</rect>");
+                }
+                Shape::Line { points, color } => {
+                    let points: Vec<String> = points
+                        .iter()
+                        .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                        .collect();
+                    svg.push_str(&format!(
+                        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+                        points.join(" "),
+                        color
+                    ));
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    text,
+                    color,
+                    end,
+                } => svg.push_str(&format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\" fill=\"{}\"{}>{}</text>",
+                    x,
+                    y,
+                    color,
+                    if *end { " text-anchor=\"end\"" } else { "" },
+                    escape(text)
+                )),
+            }
+        }
+        svg.push_str("</svg>");
+
+        svg
+    }
+
+    /// The chart drawn on a white picture, three bytes a pixel.
+    pub fn pixels(&self) -> Vec<u8> {
+        let mut canvas = Canvas {
+            width: self.width as usize,
+            height: self.height as usize,
+            rgb: vec![255; self.width as usize * self.height as usize * 3],
+        };
+        for shape in &self.shapes {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color,
+                    ..
+                } => canvas.fill(*x, *y, *width, *height, rgb(color)),
+                Shape::Line { points, color } => {
+                    for pair in points.windows(2) {
+                        canvas.line(pair[0], pair[1], rgb(color));
+                    }
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    text,
+                    color,
+                    end,
+                } => {
+                    let width = (text.chars().count() * 4 * SCALE) as f64;
+                    let left = if *end { x - width } else { *x };
+                    canvas.text(left, y - (5 * SCALE) as f64, text, rgb(color));
+                }
+            }
+        }
+
+        canvas.rgb
+    }
+
+    pub fn png(&self) -> Vec<u8> {
+        png::encode(self.width, self.height, &self.pixels())
+    }
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    rgb: Vec<u8>,
+}
+
+impl Canvas {
+    fn set(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let at = (y as usize * self.width + x as usize) * 3;
+        self.rgb[at..at + 3].copy_from_slice(&color);
+    }
+
+    fn fill(&mut self, x: f64, y: f64, width: f64, height: f64, color: [u8; 3]) {
+        for py in y.round() as i64..(y + height).round() as i64 {
+            for px in x.round() as i64..(x + width).round() as i64 {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    /// A line two pixels wide.
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), color: [u8; 3]) {
+        let steps = (to.0 - from.0)
+            .abs()
+            .max((to.1 - from.1).abs())
+            .ceil()
+            .max(1.0);
+        for i in 0..=steps as usize {
+            let t = i as f64 / steps;
+            let x = from.0 + (to.0 - from.0) * t;
+            let y = from.1 + (to.1 - from.1) * t;
+            self.fill(x - 1.0, y - 1.0, 2.0, 2.0, color);
+        }
+    }
+
+    fn text(&mut self, x: f64, top: f64, text: &str, color: [u8; 3]) {
+        for (i, c) in text.chars().enumerate() {
+            let c = c.to_ascii_lowercase();
+            let Some((_, rows)) = FONT.iter().find(|(g, _)| *g == c) else {
+                continue;
+            };
+            let left = x + (i * 4 * SCALE) as f64;
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (4 >> column) != 0 {
+                        self.fill(
+                            left + (column * SCALE) as f64,
+                            top + (row * SCALE) as f64,
+                            SCALE as f64,
+                            SCALE as f64,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `#rrggbb` as bytes.
+fn rgb(color: &str) -> [u8; 3] {
+    let channel = |at: usize| u8::from_str_radix(&color[at..at + 2], 16).unwrap_or(0);
+    [channel(1), channel(3), channel(5)]
+}
+
+/// Left, bottom and top margins of the plot area of bar and line charts.
+const MARGINS: (f64, f64, f64) = (40.0, 20.0, 14.0);
+
+/// A bar per day of `counts`, labeled by date every week, `width` by
+/// `height` pixels.
+pub fn bars(counts: &BTreeMap<NaiveDate, usize>, width: u32, height: u32) -> Plot {
+    let (left, bottom, top) = MARGINS;
+    let plot_width = width as f64 - left - 8.0;
+    let plot_height = height as f64 - bottom - top;
+    let max = counts.values().copied().max().unwrap_or(0).max(1);
+    let step = plot_width / counts.len().max(1) as f64;
+
+    let mut plot = Plot::new(width, height);
+    for tick in [0, max.div_ceil(2), max] {
+        let y = top + plot_height * (1.0 - tick as f64 / max as f64);
+        plot.shapes.push(Shape::Rect {
+            x: left,
+            y,
+            width: plot_width,
+            height: 1.0,
+            color: "#dddddd",
+            title: None,
+        });
+        plot.text(left - 4.0, y + 4.0, tick.to_string(), true);
+    }
+    for (i, (day, count)) in counts.iter().enumerate() {
+        let x = left + i as f64 * step;
+        let bar = plot_height * *count as f64 / max as f64;
+        plot.shapes.push(Shape::Rect {
+            x: x + step * 0.1,
+            y: top + plot_height - bar,
+            width: step * 0.8,
+            height: bar,
+            color: BAR,
+            title: Some(format!("{}: {}", day, count)),
+        });
+        if i % 7 == 0 && x + 40.0 <= width as f64 {
+            plot.text(
+                x,
+                height as f64 - 4.0,
+                day.format("%m-%d").to_string(),
+                false,
+            );
+        }
+    }
+
+    plot
+}
+
+/// A line per named series of values from 0 to 1 over days, shown as
+/// percentages, with a legend.
+pub fn lines(series: &[(String, BTreeMap<NaiveDate, f64>)], width: u32, height: u32) -> Plot {
+    let (left, bottom, top) = MARGINS;
+    let plot_width = width as f64 - left - 8.0;
+    let plot_height = height as f64 - bottom - top;
+    let days: Vec<NaiveDate> = series
+        .iter()
+        .flat_map(|(_, values)| values.keys().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let step = plot_width / (days.len().max(2) - 1) as f64;
+    let x_of =
+        |day: &NaiveDate| left + days.iter().position(|d| d == day).unwrap_or(0) as f64 * step;
+
+    let mut plot = Plot::new(width, height);
+    for tick in [0.0, 0.5, 1.0] {
+        let y = top + plot_height * (1.0 - tick);
+        plot.shapes.push(Shape::Rect {
+            x: left,
+            y,
+            width: plot_width,
+            height: 1.0,
+            color: "#dddddd",
+            title: None,
+        });
+        plot.text(left - 4.0, y + 4.0, format!("{:.0}%", tick * 100.0), true);
+    }
+    for (i, day) in days
+        .iter()
+        .enumerate()
+        .step_by(7)
+        .filter(|(i, _)| left + *i as f64 * step + 40.0 <= width as f64)
+    {
+        plot.text(
+            left + i as f64 * step,
+            height as f64 - 4.0,
+            day.format("%m-%d").to_string(),
+            false,
+        );
+    }
+    for (i, (name, values)) in series.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        plot.shapes.push(Shape::Line {
+            points: values
+                .iter()
+                .map(|(day, v)| (x_of(day), top + plot_height * (1.0 - v.clamp(0.0, 1.0))))
+                .collect(),
+            color,
+        });
+        let y = top + 14.0 * (i + 1) as f64;
+        plot.shapes.push(Shape::Rect {
+            x: width as f64 - 110.0,
+            y: y - 8.0,
+            width: 10.0,
+            height: 10.0,
+            color,
+            title: None,
+        });
+        plot.text(width as f64 - 96.0, y, name.clone(), false);
+    }
+
+    plot
+}
+
+/// A year of daily review `counts` up to `today` as a grid of weeks, Mondays
+/// on top.
+pub fn heatmap(counts: &BTreeMap<NaiveDate, usize>, today: NaiveDate) -> String {
+    let first = today - Days::new(52 * 7);
+    let start = first - Days::new(first.weekday().num_days_from_monday().into());
+    let weeks = (today - start).num_days() as u32 / 7 + 1;
+    let max = counts
+        .range(start..=today)
+        .map(|(_, c)| *c)
+        .max()
+        .unwrap_or(0);
+    let (cell, left, top) = (12, 28, 16);
+
+    let mut svg = open(left + weeks * cell, top + 7 * cell);
+    for (weekday, label) in [(0, "Mon"), (2, "Wed"), (4, "Fri")] {
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{}\" font-size=\"9\">{}</text>",
+            top + weekday * cell + 9,
+            label
+        ));
+    }
+    let mut last_month = 0;
+    for week in 0..weeks {
+        let monday = start + Days::new(week as u64 * 7);
+        if monday.month() != last_month && week + 3 <= weeks {
+            last_month = monday.month();
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"10\" font-size=\"9\">{}</text>",
+                left + week * cell,
+                monday.format("%b")
+            ));
+        }
+        for weekday in 0..7 {
+            let date = monday + Days::new(weekday as u64);
+            if date > today {
+                break;
+            }
+            let count = counts.get(&date).copied().unwrap_or(0);
+            let level = match (count, max) {
+                (0, _) | (_, 0) => 0,
+                _ => (count * (HEAT.len() - 1)).div_ceil(max),
+            };
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"10\" height=\"10\" rx=\"2\" fill=\"{}\">\
+                 <title>{}: {} review(s)</title></rect>",
+                left + week * cell,
+                top + weekday * cell,
+                HEAT[level],
+                date,
+                count
+            ));
+        }
+    }
+    svg.push_str("</svg>");
+
+    svg
+}
+
+/// `text` with the characters markup gives meaning to escaped.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn open(width: u32, height: u32) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\">",
+        width, height
+    )
+}
@@ -0,0 +1,1448 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::expr::Formula;
+use crate::ignore::Ignore;
+use crate::{hash, leitner, toml, weight};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub vault: VaultConfig,
+
+    /// Deck shortcuts, e.g. `os = "408/os"` lets `memo os` review that subdir.
+    #[serde(default)]
+    pub shortcuts: BTreeMap<String, String>,
+
+    /// Per-deck settings keyed by subdir, e.g. `[decks.408]`.
+    #[serde(default)]
+    pub decks: BTreeMap<String, DeckConfig>,
+
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    #[serde(default)]
+    pub review: ReviewConfig,
+
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// SMTP settings `memo digest --email` sends by.
+    pub email: Option<EmailConfig>,
+
+    /// Webhooks told of every session, `[webhooks.<NAME>]`.
+    #[serde(default)]
+    pub webhooks: BTreeMap<String, WebhookConfig>,
+
+    /// Named vaults, `[vault.<NAME>]`, picked with `--profile`.
+    #[serde(skip)]
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// Name of the profile in use.
+    #[serde(skip)]
+    pub profile: Option<String>,
+
+    /// The `[decks.<DECK>]` tables as written, merged down the deck paths
+    /// so that `[decks."408/os"]` inherits what it doesn't set from
+    /// `[decks.408]`.
+    #[serde(skip)]
+    pub deck_tables: BTreeMap<String, Map<String, Value>>,
+
+    /// Deck settings of `MEMO_*` variables, over those of the tables.
+    #[serde(skip)]
+    pub env_decks: Vec<EnvSetting>,
+}
+
+/// A deck setting given by a variable, see [`env_overrides`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvSetting {
+    /// The variable, for messages.
+    pub name: String,
+    /// Deck as named in the variable, upper case and `_` for `/`, empty
+    /// for every deck.
+    pub deck: String,
+    pub key: String,
+    pub value: Value,
+}
+
+impl EnvSetting {
+    /// Whether the variable names `deck`, the empty name being every deck.
+    fn names(&self, deck: &str) -> bool {
+        self.deck.eq_ignore_ascii_case(&deck.replace('/', "_"))
+    }
+}
+
+/// Tables whose keys `MEMO_<TABLE>_<KEY>` sets.
+const ENV_TABLES: [&str; 5] = ["vault", "review", "storage", "hooks", "email"];
+
+/// A setting of a table given by a variable: the table, the key and the
+/// value.
+pub type EnvTableSetting = (String, String, Value);
+
+/// Variables read on their own rather than as settings.
+const ENV_OTHERS: [&str; 2] = ["MEMO_VAULT", "MEMO_PROFILE"];
+
+/// Settings of the `MEMO_*` variables of `vars`, layered over the config
+/// file: `MEMO_<TABLE>_<KEY>` for the keys of [`ENV_TABLES`], like
+/// `MEMO_REVIEW_FILES_PER_DAY`, `MEMO_DECK_<DECK>_<KEY>` for a deck, like
+/// `MEMO_DECK_408_FUZZ`, and `MEMO_<KEY>` or `MEMO_DECK_DEFAULT_<KEY>` for
+/// every deck, like `MEMO_FILES_PER_DAY`. Values are read as TOML, strings
+/// needing no quotes. `MEMO_VAULT` and `MEMO_PROFILE` are the root and the
+/// profile, as before, and other `MEMO_*` variables an error. Returns the
+/// table settings and the deck ones, each of those checked to be of the
+/// type of its setting.
+pub fn env_overrides(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(Vec<EnvTableSetting>, Vec<EnvSetting>), ConfigError> {
+    let deck_keys: Vec<String> = match serde_json::to_value(DeckConfig::default()) {
+        Ok(Value::Object(map)) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    let value = |raw: &str| {
+        toml::parse(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut v| v.remove("value"))
+            .unwrap_or_else(|| Value::String(raw.to_string()))
+    };
+
+    let mut tables = Vec::new();
+    let mut decks = Vec::new();
+    for (name, raw) in vars {
+        let Some(rest) = name.strip_prefix("MEMO_") else {
+            continue;
+        };
+        if ENV_OTHERS.contains(&name.as_str()) {
+            continue;
+        }
+        let unknown = || {
+            ConfigError::Invalid(format!(
+                "`{}` is not a setting, expected `MEMO_<TABLE>_<KEY>` for a table of {}, or `MEMO_<KEY>`, `MEMO_DECK_DEFAULT_<KEY>` or `MEMO_DECK_<DECK>_<KEY>` for a deck setting",
+                name,
+                ENV_TABLES.join(", ")
+            ))
+        };
+        let rest = rest.to_ascii_lowercase();
+        if let Some((table, key)) = ENV_TABLES.iter().find_map(|t| {
+            let key = rest.strip_prefix(t)?.strip_prefix('_')?;
+            Some((t.to_string(), key.to_string()))
+        }) {
+            tables.push((table, key, value(&raw)));
+            continue;
+        }
+
+        let (deck, key) = match rest.strip_prefix("deck_") {
+            Some(rest) => match deck_keys.iter().find(|k| {
+                rest.strip_suffix(k.as_str())
+                    .is_some_and(|d| d.ends_with('_'))
+            }) {
+                Some(key) => (rest[..rest.len() - key.len() - 1].to_string(), key.clone()),
+                None => return Err(unknown()),
+            },
+            None if deck_keys.contains(&rest) => (String::new(), rest),
+            None => return Err(unknown()),
+        };
+        let setting = EnvSetting {
+            name,
+            deck: if deck == "default" {
+                String::new()
+            } else {
+                deck
+            },
+            key,
+            value: value(&raw),
+        };
+        let alone = Map::from_iter([(setting.key.clone(), setting.value.clone())]);
+        if let Err(e) = serde_json::from_value::<DeckConfig>(Value::Object(alone)) {
+            return Err(ConfigError::Invalid(format!("`{}`: {}", setting.name, e)));
+        }
+        decks.push(setting);
+    }
+
+    Ok((tables, decks))
+}
+
+/// A vault of its own, with `[vault.<NAME>]` in place of `[vault]` and its
+/// `decks` and `shortcuts` subtables on top of the top-level ones.
+#[derive(Debug, Default)]
+pub struct Profile {
+    pub vault: VaultConfig,
+    pub shortcuts: BTreeMap<String, String>,
+    pub decks: BTreeMap<String, DeckConfig>,
+    pub deck_tables: BTreeMap<String, Map<String, Value>>,
+}
+
+impl Profile {
+    fn from_table(mut table: Map<String, Value>) -> Result<Self, serde_json::Error> {
+        let shortcuts = table.remove("shortcuts").map(serde_json::from_value);
+        let deck_tables = table.remove("decks").map(serde_json::from_value);
+
+        let deck_tables: BTreeMap<String, Map<String, Value>> =
+            deck_tables.transpose()?.unwrap_or_default();
+        Ok(Self {
+            vault: serde_json::from_value(Value::Object(table))?,
+            shortcuts: shortcuts.transpose()?.unwrap_or_default(),
+            decks: deck_tables
+                .keys()
+                .map(|k| Ok((k.clone(), merged(&deck_tables, &[], k)?)))
+                .collect::<Result<_, serde_json::Error>>()?,
+            deck_tables,
+        })
+    }
+}
+
+/// Settings of `deck`, its table on top of those of its parent decks and
+/// `env` on top of those.
+fn merged(
+    tables: &BTreeMap<String, Map<String, Value>>,
+    env: &[EnvSetting],
+    deck: &str,
+) -> Result<DeckConfig, serde_json::Error> {
+    serde_json::from_value(Value::Object(merged_table(tables, env, deck)))
+}
+
+/// The keys set for `deck`, by its table, those of its parent decks or `env`.
+fn merged_table(
+    tables: &BTreeMap<String, Map<String, Value>>,
+    env: &[EnvSetting],
+    deck: &str,
+) -> Map<String, Value> {
+    let mut table = Map::new();
+    let mut path = String::new();
+    for part in deck.split('/') {
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path.push_str(part);
+        if let Some(level) = tables.get(&path) {
+            table.extend(level.clone());
+        }
+    }
+    // Every deck first, then down the path like the tables.
+    let mut path = String::new();
+    for part in std::iter::once("").chain(deck.split('/')) {
+        if !path.is_empty() && !part.is_empty() {
+            path.push('/');
+        }
+        path.push_str(part);
+        for setting in env.iter().filter(|s| s.names(&path)) {
+            table.insert(setting.key.clone(), setting.value.clone());
+        }
+    }
+
+    table
+}
+
+/// Checks the ranges of the settings of `decks`, keyed by deck.
+fn check_decks(decks: &BTreeMap<String, DeckConfig>) -> Result<(), ConfigError> {
+    if let Some((name, _)) = decks.iter().find(|(_, d)| d.leitner_intervals.is_empty()) {
+        return Err(ConfigError::Invalid(format!(
+            "`leitner_intervals` of deck `{}` is empty",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks.iter().find(|(_, d)| !valid_decay_rate(d.decay_rate)) {
+        return Err(ConfigError::Invalid(format!(
+            "`decay_rate` of deck `{}` must be between 0 and 1",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| d.scheduler == SchedulerKind::Formula && d.formula.is_none())
+    {
+        return Err(ConfigError::Invalid(format!(
+            "deck `{}` uses the formula scheduler but has no `formula`",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| d.due_retention.is_some_and(|r| !valid_decay_rate(r)))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "`due_retention` of deck `{}` must be between 0 and 1",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks.iter().find(|(_, d)| !(0.0..=0.5).contains(&d.fuzz)) {
+        return Err(ConfigError::Invalid(format!(
+            "`fuzz` of deck `{}` must be between 0 and 0.5",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks.iter().find(|(_, d)| d.max_due_per_day == Some(0)) {
+        return Err(ConfigError::Invalid(format!(
+            "`max_due_per_day` of deck `{}` must be at least 1",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| !(d.length_weight.is_finite() && d.difficulty_weight >= 0.0))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "`length_weight` of deck `{}` must be finite and `difficulty_weight` at least 0",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks.iter().find(|(_, d)| !d.basic_weight.is_finite()) {
+        return Err(ConfigError::Invalid(format!(
+            "`basic_weight` of deck `{}` must be a number",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| !(d.minimum_weight >= 1.0 && d.minimum_weight.is_finite()))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "`minimum_weight` of deck `{}` must be at least 1",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| !(d.max_out_factor > 0.0 && d.max_out_factor.is_finite()))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "`max_out_factor` of deck `{}` must be above 0",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| !(d.recent_boost > 0.0 && d.recent_boost.is_finite()))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "`recent_boost` of deck `{}` must be above 0",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| !(d.change_boost > 0.0 && d.change_boost.is_finite()))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "`change_boost` of deck `{}` must be above 0",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks
+        .iter()
+        .find(|(_, d)| !(d.priority > 0.0 && d.priority.is_finite()))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "`priority` of deck `{}` must be above 0",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks.iter().find(|(_, d)| {
+        d.extensions
+            .iter()
+            .all(|e| e.trim_start_matches('.').is_empty())
+    }) {
+        return Err(ConfigError::Invalid(format!(
+            "`extensions` of deck `{}` is empty",
+            name
+        )));
+    }
+    if let Some((name, _)) = decks.iter().find(|(_, d)| d.files_per_day == 0) {
+        return Err(ConfigError::Invalid(format!(
+            "`files_per_day` of deck `{}` must be at least 1",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReviewConfig {
+    /// Reading speed the time of a note is estimated by.
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: u32,
+
+    /// Open drawn notes by default, as with `memo review --open`.
+    pub open: Option<OpenTarget>,
+
+    /// Seconds between opening two notes.
+    #[serde(default)]
+    pub open_delay: f64,
+
+    /// Files drawn a day by `memo all`, the sum of the decks' `files_per_day`
+    /// if unset.
+    pub files_per_day: Option<usize>,
+
+    /// Seed of random draws, as with `--seed`. Draws are random if unset.
+    pub seed: Option<Seed>,
+
+    /// Time of day, `HH:MM`, `memo daemon` draws at.
+    pub draw_at: Option<String>,
+}
+
+impl ReviewConfig {
+    /// `draw_at` as a time, 08:00 if unset.
+    pub fn draw_at(&self) -> Option<NaiveTime> {
+        match &self.draw_at {
+            Some(at) => NaiveTime::parse_from_str(at, "%H:%M").ok(),
+            None => NaiveTime::from_hms_opt(8, 0, 0),
+        }
+    }
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            words_per_minute: default_words_per_minute(),
+            open: None,
+            open_delay: 0.0,
+            files_per_day: None,
+            seed: None,
+            draw_at: None,
+        }
+    }
+}
+
+/// What is handed to the OS opener to open a note.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenTarget {
+    /// Its link, by the `link_scheme` of the vault.
+    #[default]
+    Uri,
+    /// The file itself, opened by the default app for Markdown.
+    File,
+}
+
+impl FromStr for OpenTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uri" => Ok(Self::Uri),
+            "file" => Ok(Self::File),
+            _ => Err(format!("expected uri or file, got `{}`", s)),
+        }
+    }
+}
+
+/// Seed of random draws, making them reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seed {
+    Fixed(u64),
+    /// Derived from the day, so that drawing again the same day draws the
+    /// same.
+    Date,
+}
+
+impl Seed {
+    /// The seed on `today`.
+    pub fn value(self, today: NaiveDate) -> u64 {
+        match self {
+            Self::Fixed(seed) => seed,
+            Self::Date => hash::fnv1a(today.to_string().as_bytes()),
+        }
+    }
+}
+
+impl FromStr for Seed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "date" => Ok(Self::Date),
+            _ => s
+                .parse()
+                .map(Self::Fixed)
+                .map_err(|_| format!("expected a number or date, got `{}`", s)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Seed {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(seed) => Ok(Self::Fixed(seed)),
+            Raw::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+fn default_words_per_minute() -> u32 {
+    200
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub backend: Backend,
+
+    /// Database file of the SQLite backend, relative paths are resolved
+    /// against the revs dir.
+    pub path: Option<PathBuf>,
+
+    /// Number of previous versions kept of every JSON or MessagePack file.
+    #[serde(default = "default_backups")]
+    pub backups: usize,
+
+    /// Base URL of the WebDAV and S3 backends, a collection or a bucket with
+    /// an optional prefix, e.g. `https://s3.example.com/bucket/memo`.
+    pub url: Option<String>,
+
+    /// User name or access key of the remote backends.
+    pub user: Option<String>,
+
+    /// Password or secret key of the remote backends.
+    pub password: Option<String>,
+
+    /// Region of the S3 backend, `us-east-1` if omitted.
+    pub region: Option<String>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: Backend::default(),
+            path: None,
+            backups: default_backups(),
+            url: None,
+            user: None,
+            password: None,
+            region: None,
+        }
+    }
+}
+
+fn default_backups() -> usize {
+    3
+}
+
+/// Commands run through the shell at points of memo's work, handed JSON on
+/// stdin, see [`crate::hooks`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Before a draw, with the deck and the day. Failing stops the draw.
+    pub pre_draw: Option<String>,
+
+    /// After saving, once for every review saved, with its file and grade.
+    pub post_review: Option<String>,
+
+    /// After saving a deck, with the number of reviews saved.
+    pub post_save: Option<String>,
+}
+
+/// Where and how `memo digest --email` sends, through `curl`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailConfig {
+    /// SMTP server, `smtps://host:465` for TLS or `smtp://host:587`, which
+    /// is upgraded with STARTTLS.
+    pub url: String,
+
+    pub user: Option<String>,
+
+    pub password: Option<String>,
+
+    /// Sender address.
+    pub from: String,
+
+    /// Recipient addresses.
+    pub to: Vec<String>,
+}
+
+/// A webhook posted a digest after each session.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+
+    #[serde(default)]
+    pub kind: WebhookKind,
+
+    /// Decks whose sessions are posted, all of them if empty.
+    #[serde(default)]
+    pub decks: Vec<String>,
+}
+
+/// What a webhook expects to be posted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    /// The digest as JSON, text included.
+    #[default]
+    Generic,
+    /// A Discord message.
+    Discord,
+    /// A Slack message.
+    Slack,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// One JSON file per deck in the revs dir.
+    #[default]
+    Json,
+    /// A single SQLite database, needs the `sqlite3` shell.
+    Sqlite,
+    /// One MessagePack file per deck in the revs dir, smaller and faster to
+    /// load than JSON for large decks.
+    Msgpack,
+    /// One JSON file per deck on a WebDAV server, cached in the revs dir.
+    /// Needs `curl`.
+    Webdav,
+    /// One JSON file per deck in an S3-compatible bucket, cached in the revs
+    /// dir. Needs `curl`.
+    S3,
+}
+
+/// Where a vault's revs files and caches are kept, unless `revs` is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataLocation {
+    /// In the vault if it has a `revs` dir already, else the system's.
+    #[default]
+    Auto,
+    /// The `revs` dir of the vault, synced along with the notes.
+    Vault,
+    /// The data dir of the system, e.g. `~/.local/share/memo` on Linux,
+    /// with caches in its cache dir.
+    System,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Track {
+    /// The file stem, renaming a file starts it over.
+    #[default]
+    Name,
+    /// The `id` of the frontmatter or a hash of the content, following renames.
+    Id,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VaultConfig {
+    /// Root directory of the vault, one subdir per deck.
+    #[serde(default)]
+    pub root: PathBuf,
+
+    /// Vault name as known to Obsidian, defaults to the name of `root`.
+    pub name: Option<String>,
+
+    /// Directory of the revs files, relative paths are resolved against
+    /// `root`. Overrides `data`.
+    pub revs: Option<PathBuf>,
+
+    /// Where the revs files and caches are kept.
+    #[serde(default)]
+    pub data: DataLocation,
+
+    /// Globs of paths never drawn, on top of those in `.memoignore`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// What review data is tied to.
+    #[serde(default)]
+    pub track: Track,
+
+    /// What links to notes open.
+    #[serde(default)]
+    pub link_scheme: LinkScheme,
+
+    /// Directory of the notes written by `memo daily`, relative paths are
+    /// resolved against `root`. Defaults to `Memo/Daily`, it is never drawn.
+    pub daily: Option<PathBuf>,
+
+    /// Git remote `memo sync` keeps the revs dir in step with.
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LinkScheme {
+    /// `obsidian://open?vault=…&file=…`.
+    #[default]
+    Obsidian,
+    /// `vscode://file/…`.
+    Vscode,
+    /// `file:///…`.
+    File,
+    /// Any other URI, given with `{path}`, `{file}`, `{vault}`, `{name}` and
+    /// `{heading}` standing for the absolute path, the path from the vault
+    /// root, the vault name, the file stem and the heading of an item of a
+    /// split note, empty for whole notes, all percent-encoded.
+    Template(String),
+}
+
+impl<'de> Deserialize<'de> for LinkScheme {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let scheme = String::deserialize(deserializer)?;
+        match scheme.as_str() {
+            "obsidian" => Ok(Self::Obsidian),
+            "vscode" => Ok(Self::Vscode),
+            "file" => Ok(Self::File),
+            _ if scheme.contains('{') => Ok(Self::Template(scheme)),
+            _ => Err(serde::de::Error::custom(format!(
+                "unknown link scheme `{}`, expected obsidian, vscode, file or a template like `typora://open?path={{path}}`",
+                scheme
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeckConfig {
+    #[serde(default)]
+    pub scheduler: SchedulerKind,
+
+    /// Days between reviews of each Leitner box.
+    #[serde(default = "leitner::default_intervals")]
+    pub leitner_intervals: Vec<u32>,
+
+    /// Daily retention factor of the decay scheduler.
+    #[serde(default = "default_decay_rate")]
+    pub decay_rate: f64,
+
+    /// Weight of a file before its priority and penalty are added.
+    #[serde(default = "default_basic_weight")]
+    pub basic_weight: f64,
+
+    /// Least weight of a file, at least 1 so that every file can be drawn.
+    #[serde(default = "default_minimum_weight")]
+    pub minimum_weight: f64,
+
+    /// Days assumed since the last review of a reviewed file without a
+    /// date, in times the days it takes to draw every file once.
+    #[serde(default = "default_max_out_factor")]
+    pub max_out_factor: f64,
+
+    /// Files drawn a day.
+    #[serde(default = "default_files_per_day")]
+    pub files_per_day: usize,
+
+    /// Most never reviewed files drawn a day, no limit if unset.
+    pub max_new_per_day: Option<usize>,
+
+    /// Most reviewed files drawn a day, no limit if unset.
+    pub max_reviews_per_day: Option<usize>,
+
+    /// Weight of a file under the formula scheduler, see [`crate::expr`].
+    pub formula: Option<Formula>,
+
+    /// Share of an interval a due date may move by, either way, to land on
+    /// a less busy day.
+    #[serde(default)]
+    pub fuzz: f64,
+
+    /// Retention under which `memo due` counts a file as due, for
+    /// schedulers that estimate one. Their own due dates if unset.
+    pub due_retention: Option<f64>,
+
+    /// Most files due on one day, overflow is moved to the nearest day with
+    /// room. No limit if unset.
+    pub max_due_per_day: Option<usize>,
+
+    /// How much the deck's files weigh against those of other decks in
+    /// `memo all`, e.g. 3 for three times as much.
+    #[serde(default = "default_priority")]
+    pub priority: f64,
+
+    /// Extensions of the files that are notes, e.g. `["md", "txt", "org"]`.
+    #[serde(default = "default_extensions")]
+    pub extensions: Vec<String>,
+
+    /// Review each top-level heading (`#` in Markdown, `*` in Org) of a note
+    /// with two or more as an item of its own.
+    #[serde(default)]
+    pub split_headings: bool,
+
+    /// Review each second-level heading (`##` in Markdown, `**` in Org) of a
+    /// note of more than this many words as an item of its own, for notes
+    /// not split by `split_headings`. Never if unset.
+    pub split_above: Option<usize>,
+
+    /// Write schedules to the notes and cards reviewed, the way the Spaced
+    /// Repetition plugin for Obsidian keeps them, so that both agree.
+    #[serde(default)]
+    pub write_sr: bool,
+
+    /// Write `memo-last-reviewed` and `memo-review-count` to the frontmatter
+    /// of the notes reviewed, for Dataview queries and the like.
+    #[serde(default)]
+    pub write_frontmatter: bool,
+
+    /// What happens to the schedule of a file whose content changed since
+    /// memo last saw it.
+    #[serde(default)]
+    pub on_change: OnChange,
+
+    /// Factor the weight of a changed file is multiplied by until its next
+    /// review, with `on_change = "boost"`.
+    #[serde(default = "default_change_boost")]
+    pub change_boost: f64,
+
+    /// Days since a file was last modified, by its mtime, for which its
+    /// weight is multiplied by `recent_boost`. Never if unset.
+    pub recent_days: Option<u32>,
+
+    /// Factor the weight of files modified within `recent_days` is
+    /// multiplied by.
+    #[serde(default = "default_recent_boost")]
+    pub recent_boost: f64,
+
+    /// Exponent of the length of a file over the median of the deck its
+    /// weight is multiplied by, so that above 0 long notes are drawn more
+    /// often than short ones. 0, leaving length out, by default.
+    #[serde(default)]
+    pub length_weight: f64,
+
+    /// Weight of a file is multiplied by one plus this times its difficulty,
+    /// from 0 for easy files to 1 for hard ones. 0 by default.
+    #[serde(default)]
+    pub difficulty_weight: f64,
+}
+
+/// What a change to the content of a file does to its schedule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnChange {
+    /// Nothing, content is not tracked.
+    #[default]
+    Ignore,
+    /// Start it over as a new file, keeping its history.
+    Reset,
+    /// Multiply its weight by `change_boost` until its next review.
+    Boost,
+}
+
+fn default_change_boost() -> f64 {
+    2.0
+}
+
+fn default_recent_boost() -> f64 {
+    1.5
+}
+
+fn default_extensions() -> Vec<String> {
+    vec!["md".to_string()]
+}
+
+fn default_priority() -> f64 {
+    1.0
+}
+
+fn default_files_per_day() -> usize {
+    weight::FILES_PER_DAY
+}
+
+/// Whether `rate` makes retention decay, rather than vanish or grow.
+pub fn valid_decay_rate(rate: f64) -> bool {
+    rate > 0.0 && rate < 1.0
+}
+
+fn default_decay_rate() -> f64 {
+    weight::DECAY_RATE
+}
+
+fn default_basic_weight() -> f64 {
+    weight::BASIC_WEIGHT
+}
+
+fn default_minimum_weight() -> f64 {
+    weight::MINIMUM_WEIGHT
+}
+
+fn default_max_out_factor() -> f64 {
+    weight::MAX_OUT_FACTOR
+}
+
+impl Default for DeckConfig {
+    fn default() -> Self {
+        Self {
+            scheduler: SchedulerKind::default(),
+            decay_rate: default_decay_rate(),
+            basic_weight: default_basic_weight(),
+            minimum_weight: default_minimum_weight(),
+            max_out_factor: default_max_out_factor(),
+            files_per_day: default_files_per_day(),
+            max_new_per_day: None,
+            max_reviews_per_day: None,
+            formula: None,
+            fuzz: 0.0,
+            due_retention: None,
+            max_due_per_day: None,
+            priority: default_priority(),
+            extensions: default_extensions(),
+            split_headings: false,
+            split_above: None,
+            write_sr: false,
+            write_frontmatter: false,
+            on_change: OnChange::default(),
+            change_boost: default_change_boost(),
+            recent_days: None,
+            recent_boost: default_recent_boost(),
+            length_weight: 0.0,
+            difficulty_weight: 0.0,
+            leitner_intervals: leitner::default_intervals(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerKind {
+    /// Exponential decay of retention since the last review.
+    #[default]
+    Decay,
+    /// SM-2, fed by `memo rate`.
+    Sm2,
+    /// FSRS, fed by `memo rate`.
+    Fsrs,
+    /// Leitner boxes, fed by `memo rate`.
+    Leitner,
+    /// Every file equally likely.
+    Flat,
+    /// The longer since the last review, the likelier.
+    Oldest,
+    /// The deck's `formula`.
+    Formula,
+}
+
+impl fmt::Display for SchedulerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Decay => "decay",
+            Self::Sm2 => "sm2",
+            Self::Fsrs => "fsrs",
+            Self::Leitner => "leitner",
+            Self::Flat => "flat",
+            Self::Oldest => "oldest",
+            Self::Formula => "formula",
+        })
+    }
+}
+
+impl FromStr for SchedulerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decay" => Ok(Self::Decay),
+            "sm2" => Ok(Self::Sm2),
+            "fsrs" => Ok(Self::Fsrs),
+            "leitner" => Ok(Self::Leitner),
+            "flat" => Ok(Self::Flat),
+            "oldest" => Ok(Self::Oldest),
+            "formula" => Ok(Self::Formula),
+            _ => Err(format!(
+                "expected decay, sm2, fsrs, leitner, flat, oldest or formula, got `{}`",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    NoVault(Option<PathBuf>),
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoVault(Some(path)) => write!(
+                f,
+                "no vault given, pass `--vault` or `--profile`, set `MEMO_VAULT`, or set `root` in the `[vault]` table of {}",
+                path.display()
+            ),
+            Self::NoVault(None) => write!(
+                f,
+                "no vault given, pass `--vault` or set `MEMO_VAULT` (no config dir found, is $HOME set?)"
+            ),
+            Self::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            Self::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            Self::Invalid(msg) => write!(f, "invalid config: {}", msg),
+        }
+    }
+}
+
+impl Config {
+    /// `~/.config/memo/config.toml`, or `%APPDATA%\memo\config.toml` on Windows.
+    pub fn path() -> Option<PathBuf> {
+        let base = if cfg!(target_os = "windows") {
+            env::var_os("APPDATA").map(PathBuf::from)
+        } else {
+            env::var_os("XDG_CONFIG_HOME")
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| home_dir().map(|h| h.join(".config")))
+        };
+
+        base.map(|b| b.join("memo").join("config.toml"))
+    }
+
+    /// Loads the config file if there is one, switches to `profile` (the
+    /// `--profile` flag) or `MEMO_PROFILE` if given, then resolves the vault
+    /// root from, in order of precedence, `vault` (the `--vault` flag),
+    /// `MEMO_VAULT`, and the config file.
+    pub fn load(vault: Option<PathBuf>, profile: Option<String>) -> Result<Self, ConfigError> {
+        let path = Self::path();
+        let config = match &path {
+            Some(path) => Self::from_file(path)?,
+            None => None,
+        };
+        // Without a file, the variables are all there is.
+        let config = match config {
+            Some(config) => config,
+            None => Self::from_text(path.as_deref().unwrap_or(Path::new("")), "")?,
+        };
+
+        config.resolve(path, vault, profile)
+    }
+
+    /// [`Config::load`] past reading the file: `self` as read from `path`,
+    /// switched to the profile, with the vault root resolved and checked.
+    pub fn resolve(
+        self,
+        path: Option<PathBuf>,
+        vault: Option<PathBuf>,
+        profile: Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = self;
+
+        let profile = profile.or_else(|| env::var("MEMO_PROFILE").ok().filter(|p| !p.is_empty()));
+        if let Some(name) = profile {
+            let Some(profile) = config.profiles.remove(&name) else {
+                let known: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+                return Err(ConfigError::Invalid(if known.is_empty() {
+                    format!(
+                        "no profile `{}`, there are no `[vault.<NAME>]` tables",
+                        name
+                    )
+                } else {
+                    format!(
+                        "no profile `{}`, expected one of {}",
+                        name,
+                        known.join(", ")
+                    )
+                }));
+            };
+            config.vault = profile.vault;
+            config.shortcuts.extend(profile.shortcuts);
+            config.deck_tables.extend(profile.deck_tables);
+            config.profile = Some(name);
+        }
+        config.decks = config
+            .deck_tables
+            .keys()
+            .map(|k| {
+                Ok((
+                    k.clone(),
+                    merged(&config.deck_tables, &config.env_decks, k)?,
+                ))
+            })
+            .collect::<Result<_, serde_json::Error>>()
+            .map_err(|e| ConfigError::Invalid(e.to_string()))?;
+
+        if matches!(config.storage.backend, Backend::Webdav | Backend::S3)
+            && config.storage.url.is_none()
+        {
+            return Err(ConfigError::Invalid(
+                "the remote backends need a `url` in the `[storage]` table".to_string(),
+            ));
+        }
+        check_decks(&config.decks)?;
+
+        if config.review.files_per_day == Some(0) {
+            return Err(ConfigError::Invalid(
+                "`files_per_day` of [review] must be at least 1".to_string(),
+            ));
+        }
+        if let Some((name, _)) = config
+            .webhooks
+            .iter()
+            .find(|(_, w)| !w.url.starts_with("http://") && !w.url.starts_with("https://"))
+        {
+            return Err(ConfigError::Invalid(format!(
+                "`url` of webhook `{}` must be an http(s) URL",
+                name
+            )));
+        }
+        if let Some(email) = &config.email
+            && (!email.url.starts_with("smtp://") && !email.url.starts_with("smtps://")
+                || email.to.is_empty())
+        {
+            return Err(ConfigError::Invalid(
+                "[email] needs an smtp(s):// `url` and at least one address in `to`".to_string(),
+            ));
+        }
+        if config.review.draw_at().is_none() {
+            return Err(ConfigError::Invalid(
+                "`draw_at` of [review] must be a time like \"08:00\"".to_string(),
+            ));
+        }
+
+        if let Some(root) = vault.or_else(|| env::var_os("MEMO_VAULT").map(PathBuf::from)) {
+            config.vault.root = root;
+        }
+        if config.vault.root.as_os_str().is_empty() {
+            return Err(ConfigError::NoVault(path));
+        }
+
+        config.vault.root = expand_tilde(&config.vault.root);
+        if !config.vault.root.is_dir() {
+            return Err(ConfigError::Invalid(format!(
+                "vault root {} is not a directory",
+                config.vault.root.display()
+            )));
+        }
+        // Decks without a table get settings from the variables too, and
+        // so does a nested deck a variable names.
+        let mut names: Vec<String> = config.decks();
+        for setting in config.env_decks.iter().filter(|s| !s.deck.is_empty()) {
+            let known = config
+                .deck_tables
+                .keys()
+                .chain(&names)
+                .find(|d| setting.names(d));
+            if known.is_none() {
+                names.push(setting.deck.clone());
+            }
+        }
+        let untabled = names
+            .into_iter()
+            .filter(|d| !config.decks.contains_key(d))
+            .map(|d| {
+                let settings = merged(&config.deck_tables, &config.env_decks, &d)
+                    .map_err(|e| ConfigError::Invalid(format!("deck `{}`: {}", d, e)))?;
+                Ok((d, settings))
+            })
+            .collect::<Result<_, ConfigError>>()?;
+        check_decks(&untabled)?;
+
+        Ok(config)
+    }
+
+    /// Reads the config file at `path`, a missing file is not an error.
+    pub fn from_file(path: &Path) -> Result<Option<Self>, ConfigError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+
+        Self::from_text(path, &text).map(Some)
+    }
+
+    /// Parses `text` as the config file at `path`.
+    pub fn from_text(path: &Path, text: &str) -> Result<Self, ConfigError> {
+        let mut table =
+            toml::parse(text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))?;
+        let (env_tables, env_decks) = env_overrides(env::vars())?;
+        for (name, key, value) in env_tables {
+            let Value::Object(sub) = table
+                .entry(name.clone())
+                .or_insert_with(|| Value::Object(Map::new()))
+            else {
+                continue;
+            };
+            // Profiles have a vault of their own, which the variables set too.
+            if name == "vault" {
+                for profile in sub.values_mut().filter_map(Value::as_object_mut) {
+                    profile.insert(key.clone(), value.clone());
+                }
+            }
+            sub.insert(key, value);
+        }
+
+        // Subtables of `[vault]` are profiles, its other keys are all scalars.
+        let mut profiles = BTreeMap::new();
+        if let Some(Value::Object(vault)) = table.get_mut("vault") {
+            let names: Vec<String> = vault
+                .iter()
+                .filter(|(_, v)| v.is_object())
+                .map(|(k, _)| k.clone())
+                .collect();
+            for name in names {
+                let Some(Value::Object(profile)) = vault.remove(&name) else {
+                    continue;
+                };
+                let profile = Profile::from_table(profile).map_err(|e| {
+                    ConfigError::Parse(path.to_path_buf(), format!("vault.{}: {}", name, e))
+                })?;
+                profiles.insert(name, profile);
+            }
+        }
+
+        let deck_tables = match table.get("decks") {
+            Some(decks) => serde_json::from_value(decks.clone())
+                .map_err(|e| ConfigError::Parse(path.to_path_buf(), format!("decks: {}", e)))?,
+            None => BTreeMap::new(),
+        };
+        let mut config: Self = serde_json::from_value(Value::Object(table))
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))?;
+        config.profiles = profiles;
+        config.deck_tables = deck_tables;
+        config.env_decks = env_decks;
+
+        Ok(config)
+    }
+
+    /// The excludes of the vault.
+    pub fn ignore(&self) -> Ignore {
+        let mut patterns = self.vault.exclude.clone();
+        if let Ok(daily) = self.daily_dir().strip_prefix(&self.vault.root) {
+            patterns.push(format!(
+                "/{}/**",
+                daily.to_string_lossy().replace('\\', "/")
+            ));
+        }
+
+        Ignore::load(&self.vault.root, &patterns)
+    }
+
+    pub fn vault_name(&self) -> String {
+        self.vault.name.clone().unwrap_or_else(|| {
+            self.vault
+                .root
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Resolves a shortcut to its subdir, anything else is taken as a subdir as is.
+    pub fn resolve_deck(&self, deck: &str) -> String {
+        self.shortcuts
+            .get(deck)
+            .cloned()
+            .unwrap_or_else(|| deck.to_string())
+    }
+
+    /// Settings of `deck`, inherited from its parent decks, e.g. of `408`
+    /// for `408/os`, where it doesn't set them.
+    pub fn deck(&self, deck: &str) -> DeckConfig {
+        // Every table and variable was checked to be of the right types
+        // when loading, so that merging them can't fail.
+        merged(&self.deck_tables, &self.env_decks, deck).unwrap_or_default()
+    }
+
+    /// The settings of `deck` set in the config, the rest being defaults.
+    pub fn deck_table(&self, deck: &str) -> Map<String, Value> {
+        merged_table(&self.deck_tables, &self.env_decks, deck)
+    }
+
+    /// Settings of the dir `dir` of `deck` if it or a dir between them has
+    /// a table of its own, e.g. `[decks."408/os"]` for `408/os/mem`.
+    pub fn nested_deck(&self, deck: &str, dir: &str) -> Option<DeckConfig> {
+        let has_table = self.deck_tables.keys().any(|k| {
+            k.strip_prefix(deck)
+                .is_some_and(|rest| rest.starts_with('/'))
+                && (dir == k
+                    || dir
+                        .strip_prefix(k.as_str())
+                        .is_some_and(|rest| rest.starts_with('/')))
+        });
+
+        has_table.then(|| self.deck(dir))
+    }
+
+    /// Top-level subdirs of the vault, except the revs dir, hidden and
+    /// ignored ones.
+    pub fn decks(&self) -> Vec<String> {
+        let (revs_dir, daily_dir) = (self.revs_dir(), self.daily_dir());
+        let ignore = self.ignore();
+        let mut decks: Vec<String> = fs::read_dir(&self.vault.root)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            // Nor the dirs holding memo's own, like `Memo` of `Memo/Daily`.
+            .filter(|p| {
+                p.is_dir()
+                    && !revs_dir.starts_with(p)
+                    && !daily_dir.starts_with(p)
+                    && !ignore.is_ignored(p)
+            })
+            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .filter(|n| !n.starts_with('.'))
+            .collect();
+        decks.sort();
+
+        decks
+    }
+
+    pub fn sqlite_path(&self) -> PathBuf {
+        let path = self
+            .storage
+            .path
+            .as_deref()
+            .unwrap_or(Path::new("memo.sqlite"));
+        self.revs_dir().join(expand_tilde(path))
+    }
+
+    pub fn daily_dir(&self) -> PathBuf {
+        match &self.vault.daily {
+            Some(daily) => self.vault.root.join(expand_tilde(daily)),
+            None => self.vault.root.join("Memo").join("Daily"),
+        }
+    }
+
+    pub fn revs_dir(&self) -> PathBuf {
+        if let Some(revs) = &self.vault.revs {
+            return self.vault.root.join(expand_tilde(revs));
+        }
+
+        let in_vault = self.vault.root.join("revs");
+        match self.system_dir(data_home()) {
+            Some(dir) if self.in_system(&in_vault) => dir,
+            _ => in_vault,
+        }
+    }
+
+    /// Makes the revs and cache dirs in the system's dirs if there are none
+    /// yet, before anything is saved in them. The vault's own revs dir is
+    /// made by `memo init`.
+    pub fn create_dirs(&self) -> io::Result<()> {
+        for dir in [self.revs_dir(), self.cache_dir()] {
+            if !dir.starts_with(&self.vault.root) && !dir.is_dir() {
+                fs::create_dir_all(&dir).map_err(|e| {
+                    io::Error::new(e.kind(), format!("cannot create {}: {}", dir.display(), e))
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dir of caches like the dir index, the revs dir unless that is the
+    /// system's.
+    pub fn cache_dir(&self) -> PathBuf {
+        let revs_dir = self.revs_dir();
+        if self.vault.revs.is_some() || !self.in_system(&self.vault.root.join("revs")) {
+            return revs_dir;
+        }
+
+        self.system_dir(cache_home()).unwrap_or(revs_dir)
+    }
+
+    /// Whether the data of the vault is kept in the system's dirs, `in_vault`
+    /// being its revs dir in the vault.
+    fn in_system(&self, in_vault: &Path) -> bool {
+        match self.vault.data {
+            DataLocation::Vault => false,
+            DataLocation::System => true,
+            DataLocation::Auto => !in_vault.is_dir(),
+        }
+    }
+
+    /// The dir of the vault in `base`, the system's dir of some kind, named
+    /// after the vault and told apart from others of that name by its root.
+    fn system_dir(&self, base: Option<PathBuf>) -> Option<PathBuf> {
+        let root = self.vault.root.to_string_lossy();
+        let name = format!(
+            "{}-{:08x}",
+            self.vault_name()
+                .replace(|c: char| !c.is_alphanumeric(), "-"),
+            hash::fnv1a(root.as_bytes()) as u32
+        );
+
+        Some(base?.join("memo").join(name))
+    }
+}
+
+/// Where programs keep their data: `$XDG_DATA_HOME` or `~/.local/share` on
+/// Linux, `~/Library/Application Support` on macOS, `%LOCALAPPDATA%` on
+/// Windows.
+fn data_home() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("LOCALAPPDATA")
+            .or_else(|| env::var_os("APPDATA"))
+            .map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|h| h.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| h.join(".local").join("share")))
+    }
+}
+
+/// Where programs keep their caches, like [`data_home`]: `$XDG_CACHE_HOME`
+/// or `~/.cache`, `~/Library/Caches`, and `%LOCALAPPDATA%` again.
+fn cache_home() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        data_home()
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|h| h.join("Library").join("Caches"))
+    } else {
+        env::var_os("XDG_CACHE_HOME")
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| h.join(".cache")))
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+}
+
+/// `path` with a leading `~` replaced by the home dir.
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decks_leave_out_the_dirs_of_memo() {
+        let root = env::temp_dir().join(format!("memo-decks-{}", std::process::id()));
+        for dir in ["408/os", "Memo/Daily", "data/revs", ".obsidian", "101"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let mut config = Config::default();
+        config.vault.root = root.clone();
+        config.vault.revs = Some(PathBuf::from("data/revs"));
+        assert_eq!(config.decks(), ["101", "408"]);
+
+        config.vault.daily = Some(PathBuf::from("101"));
+        config.vault.revs = None;
+        config.vault.data = DataLocation::Vault;
+        fs::create_dir_all(root.join("revs")).unwrap();
+        assert_eq!(config.decks(), ["408", "Memo", "data"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
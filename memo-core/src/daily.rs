@@ -0,0 +1,36 @@
+//! Today's draw of a deck, so that running `memo` again on the same day
+//! shows the same queue instead of drawing more.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{deck, store};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DailyDraw {
+    pub date: Option<NaiveDate>,
+    pub files: Vec<String>,
+}
+
+impl DailyDraw {
+    /// `draw-<deck>.json` in the revs dir.
+    pub fn path(revs_dir: &Path, deck: &str) -> PathBuf {
+        revs_dir.join(format!("draw-{}.json", deck::slug(deck)))
+    }
+
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        store::write_atomic(path, json.as_bytes(), 0)
+    }
+}
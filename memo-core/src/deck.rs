@@ -0,0 +1,1247 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Days, Duration, NaiveDate};
+use rand::Rng;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::config::{Config, DeckConfig, HooksConfig, OnChange, Track};
+use crate::daily::DailyDraw;
+use crate::error::Error;
+use crate::frontmatter::{self, Overrides};
+use crate::history::{self, Event};
+use crate::index::Index;
+use crate::pause::{Pause, Pauses};
+use crate::plugin::{Kind, Plugin};
+use crate::review::{self, Annotation, Grade, ReviewData, ReviewInfo};
+use crate::store::{self, Storage};
+use crate::streak::Streak;
+use crate::undo::{Change, Journal};
+use crate::weight::Breakdown;
+use crate::{balance, hooks, scan, scheduler, sections, select, sr, weight};
+
+/// A subdir of the vault together with its review data.
+pub struct Deck {
+    pub name: String,
+    pub dir: PathBuf,
+    pub storage: Box<dyn Storage>,
+    pub history: PathBuf,
+    pub streak: PathBuf,
+    pub daily: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub review_data: ReviewData,
+    pub settings: DeckConfig,
+
+    /// Saved entries without a file, kept until pruned.
+    pub orphans: Vec<ReviewInfo>,
+
+    /// Saved entries of the files of the top-level deck outside a nested
+    /// deck's subdir, left as they are.
+    outside: Vec<ReviewInfo>,
+
+    /// Settings of the files in subdirs with settings of their own.
+    nested: HashMap<String, DeckConfig>,
+
+    /// Frontmatter overrides of the files declaring any.
+    pub overrides: HashMap<String, Overrides>,
+
+    /// Word count of every file.
+    pub words: HashMap<String, usize>,
+
+    /// Day every file was last modified.
+    pub modified: HashMap<String, NaiveDate>,
+
+    /// Stable ID of the files having one, see [`Deck::relink`].
+    ids: HashMap<String, String>,
+
+    /// Frontmatter tags of the files tagged.
+    tags: HashMap<String, Vec<String>>,
+
+    /// Renames followed when opening, old and new file name.
+    pub relinked: Vec<(String, String)>,
+
+    /// Files whose content changed since last seen, by `on_change`.
+    pub rewritten: Vec<String>,
+
+    /// The last draw, saved along with the review data.
+    pub drawn: DailyDraw,
+
+    pub pauses: PathBuf,
+    pub undo: PathBuf,
+
+    /// Hooks run after saving.
+    pub hooks: HooksConfig,
+
+    /// Factors weight plugins scale files by, see [`Deck::apply_plugins`].
+    pub plugin_factors: HashMap<String, f64>,
+
+    /// Reviews since opening, appended to the history on save.
+    events: Vec<Event>,
+
+    /// Review data and draw as last saved, to record what saving changes.
+    saved: ReviewData,
+    saved_draw: DailyDraw,
+
+    /// Pauses whose length was added to the schedules since opening,
+    /// recorded as applied on save.
+    resumed: Vec<Pause>,
+}
+
+impl Deck {
+    /// Scans the deck's subdir and loads the review data of every file in it.
+    /// A nested deck like `408/os` is the part of its top-level deck in that
+    /// subdir, sharing its review data and history.
+    pub fn open(config: &Config, name: &str) -> Result<Self, Error> {
+        let name = name.trim_end_matches(['/', '\\']).replace('\\', "/");
+        let name = name.as_str();
+        let dir = config.vault.root.join(name);
+        if !dir.is_dir() {
+            return Err(Error::NoDeck(name.to_string(), config.vault.root.clone()));
+        }
+        let top = top_level(name);
+        let storage = store::open(config, &format!("revs-{}", slug(top)));
+        let daily = DailyDraw::path(&config.revs_dir(), name);
+
+        let loaded = storage.load().map_err(Error::Storage)?;
+        let ignore = config.ignore();
+        let settings = config.deck(name);
+        let index = Index::path(&config.cache_dir());
+        let mut cache = Index::load(&index);
+        let files = scan::items(&dir, &ignore, &settings, &mut cache);
+
+        let by_name: HashMap<&str, &ReviewInfo> = loaded
+            .iter()
+            .rev()
+            .map(|r| (r.file_name.as_str(), r))
+            .collect();
+        let mut review_data = ReviewData::new();
+        files.iter().for_each(|p| {
+            let file_name = scan::file_stem(p);
+            let ri = if let Some(r) = by_name.get(file_name.as_str()) {
+                (*r).clone()
+            } else {
+                ReviewInfo::new(file_name.clone())
+            };
+            review_data.insert(file_name, ri);
+        });
+        let elsewhere: Vec<String> = if top == name {
+            Vec::new()
+        } else {
+            scan::items(
+                &config.vault.root.join(top),
+                &ignore,
+                &config.deck(top),
+                &mut cache,
+            )
+            .iter()
+            .map(|f| scan::file_stem(f))
+            .collect()
+        };
+        let (outside, orphans) = loaded
+            .into_iter()
+            .filter(|r| !review_data.contains_key(&r.file_name))
+            .partition(|r| elsewhere.contains(&r.file_name));
+        let nested = files
+            .iter()
+            .filter_map(|f| {
+                let parent = f.parent()?.strip_prefix(&config.vault.root).ok()?;
+                let parent = parent.to_string_lossy().replace('\\', "/");
+                Some((scan::file_stem(f), config.nested_deck(name, &parent)?))
+            })
+            .collect();
+        let mut overrides = HashMap::new();
+        let mut words = HashMap::new();
+        let mut hashes = HashMap::new();
+        let mut modified = HashMap::new();
+        let mut ids = HashMap::new();
+        let mut tags = HashMap::new();
+        for file in &files {
+            let file_name = scan::file_stem(file);
+            let Some(item) = cache.item(file) else {
+                words.insert(file_name, 0);
+                continue;
+            };
+            modified.insert(file_name.clone(), item.modified);
+            if let Some(id) = item.id {
+                let id = match sections::split(file).1 {
+                    Some(heading) => format!("{}#{}", id, heading),
+                    None => id,
+                };
+                ids.insert(file_name.clone(), id);
+            }
+            if !item.tags.is_empty() {
+                tags.insert(file_name.clone(), item.tags);
+            }
+            if item.overrides != Overrides::default() {
+                overrides.insert(file_name.clone(), item.overrides);
+            }
+            words.insert(file_name.clone(), item.words);
+            hashes.insert(file_name, item.hash);
+        }
+        cache.retain_items(&dir, &files);
+        // A cache that can't be saved is read again next time.
+        let _ = cache.save(&index);
+
+        let mut deck = Self {
+            name: name.to_string(),
+            dir,
+            storage,
+            history: history::path(&config.revs_dir(), top),
+            streak: Streak::path(&config.revs_dir()),
+            daily: daily.clone(),
+            drawn: DailyDraw::load(&daily),
+            pauses: Pauses::path(&config.revs_dir()),
+            undo: Journal::path(&config.revs_dir()),
+            hooks: config.hooks.clone(),
+            plugin_factors: HashMap::new(),
+            files,
+            review_data,
+            settings,
+            orphans,
+            outside,
+            nested,
+            overrides,
+            words,
+            modified,
+            ids,
+            tags,
+            relinked: Vec::new(),
+            rewritten: Vec::new(),
+            events: Vec::new(),
+            saved: ReviewData::new(),
+            saved_draw: DailyDraw::default(),
+            resumed: Vec::new(),
+        };
+        deck.saved = deck.data();
+        deck.saved_draw = deck.drawn.clone();
+        if config.vault.track == Track::Id {
+            deck.relinked = deck.relink();
+        }
+        deck.rewritten = deck.track_content(&hashes);
+
+        Ok(deck)
+    }
+
+    /// Records the content `hashes` of the reviewed files of decks tracking
+    /// them, applying `on_change` to those changed since. Returns the names
+    /// of those.
+    fn track_content(&mut self, hashes: &HashMap<String, String>) -> Vec<String> {
+        let mut rewritten = Vec::new();
+        for (file_name, hash) in hashes {
+            let settings = self.settings_for(file_name);
+            if settings.on_change == OnChange::Ignore {
+                continue;
+            }
+            let Some(info) = self.review_data.get_mut(file_name) else {
+                continue;
+            };
+            if info.review_count == 0 || info.hash.as_ref() == Some(hash) {
+                continue;
+            }
+
+            let known = info.hash.replace(hash.clone()).is_some();
+            if !known {
+                continue;
+            }
+            match settings.on_change {
+                OnChange::Reset => {
+                    *info = ReviewInfo {
+                        id: info.id.take(),
+                        hash: info.hash.take(),
+                        pending: info.pending,
+                        pinned: info.pinned,
+                        suspended: info.suspended,
+                        hard: info.hard,
+                        notes: std::mem::take(&mut info.notes),
+                        extra: std::mem::take(&mut info.extra),
+                        ..ReviewInfo::new(file_name.clone())
+                    };
+                }
+                OnChange::Boost => info.changed = true,
+                OnChange::Ignore => {}
+            }
+            rewritten.push(file_name.clone());
+        }
+        rewritten.sort();
+
+        rewritten
+    }
+
+    /// Records the stable ID of every file and moves the review data of
+    /// orphans over to the new files with the same ID, returning the old and
+    /// new names of those.
+    pub fn relink(&mut self) -> Vec<(String, String)> {
+        let mut relinked = Vec::new();
+
+        for file in &self.files {
+            let file_name = scan::file_stem(file);
+            let id = self.ids.get(&file_name).cloned();
+
+            let pos = self
+                .orphans
+                .iter()
+                .position(|o| o.id.is_some() && o.id == id);
+            let Some(info) = self.review_data.get_mut(&file_name) else {
+                continue;
+            };
+            if let Some(pos) = pos.filter(|_| info.last_reviewed.is_none()) {
+                let orphan = self.orphans.remove(pos);
+                relinked.push((orphan.file_name.clone(), file_name.clone()));
+                *info = ReviewInfo {
+                    file_name,
+                    pending: info.pending.or(orphan.pending),
+                    ..orphan
+                };
+            }
+            info.id = id;
+        }
+
+        relinked
+    }
+
+    /// Restricts the files drawn from to those with any of `tags` in their
+    /// frontmatter, keeping the rest's review data untouched.
+    pub fn retain_tags(&mut self, tags: &[String]) {
+        if tags.is_empty() {
+            return;
+        }
+
+        self.files.retain(|f| {
+            self.tags.get(&scan::file_stem(f)).is_some_and(|t| {
+                t.iter()
+                    .any(|t| tags.iter().any(|w| frontmatter::tag_matches(t, w)))
+            })
+        });
+    }
+
+    /// Runs the filter plugins of `plugins` on the files drawn from, keeping
+    /// those they name, then the weight ones, whose factors the weights are
+    /// multiplied by from then on.
+    pub fn apply_plugins(&mut self, plugins: &[Plugin], today: NaiveDate) -> Result<(), Error> {
+        for plugin in plugins {
+            let payload = self.plugin_payload(plugin.kind, today);
+            match plugin.kind {
+                Kind::Filter => {
+                    let kept = plugin
+                        .filter(&payload)
+                        .map_err(|e| Error::Plugin(plugin.name.clone(), e))?;
+                    self.files.retain(|f| kept.contains(&scan::file_stem(f)));
+                }
+                Kind::Weight => {
+                    let factors = plugin
+                        .weigh(&payload)
+                        .map_err(|e| Error::Plugin(plugin.name.clone(), e))?;
+                    for (file_name, factor) in factors {
+                        *self.plugin_factors.entry(file_name).or_insert(1.0) *= factor;
+                    }
+                }
+                Kind::Sink => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// What a plugin is handed: the deck, the day and every file drawn from
+    /// with its weight and review data.
+    fn plugin_payload(&self, kind: Kind, today: NaiveDate) -> serde_json::Value {
+        let files: Vec<_> = self
+            .explain(today)
+            .into_iter()
+            .map(|e| {
+                let file_name = scan::file_stem(&e.file);
+                json!({
+                    "file": file_name,
+                    "path": e.file,
+                    "weight": e.weight,
+                    "review": self.info(&file_name),
+                })
+            })
+            .collect();
+
+        json!({
+            "kind": kind.name(),
+            "deck": self.name,
+            "date": today,
+            "files": files,
+        })
+    }
+
+    /// Finds the top-level deck containing a file named `file_name`.
+    pub fn locate(config: &Config, file_name: &str) -> Result<String, Error> {
+        let ignore = config.ignore();
+        let index = Index::path(&config.cache_dir());
+        let mut cache = Index::load(&index);
+        let mut decks: Vec<String> = config
+            .decks()
+            .into_iter()
+            .filter(|d| {
+                scan::items(
+                    &config.vault.root.join(d),
+                    &ignore,
+                    &config.deck(d),
+                    &mut cache,
+                )
+                .iter()
+                .any(|f| scan::file_stem(f) == file_name)
+            })
+            .collect();
+        // A cache that can't be saved is read again next time.
+        let _ = cache.save(&index);
+
+        match decks.len() {
+            0 => Err(Error::NoFile(file_name.to_string())),
+            1 => Ok(decks.remove(0)),
+            _ => Err(Error::AmbiguousFile(file_name.to_string(), decks)),
+        }
+    }
+
+    /// Every file neither suspended nor postponed with its weight, sorted by
+    /// weight ascending.
+    pub fn weights(&self, today: NaiveDate) -> Vec<(PathBuf, usize)> {
+        self.explain(today)
+            .into_iter()
+            .filter(|e| !e.suspended && e.postponed.is_none())
+            .map(|e| (e.file, e.weight))
+            .collect()
+    }
+
+    /// How the weight of every file comes about, sorted by weight ascending.
+    pub fn explain(&self, today: NaiveDate) -> Vec<Explanation> {
+        let max_out = weight::max_out(
+            self.files.len(),
+            self.settings.files_per_day,
+            self.settings.max_out_factor,
+        );
+        let mut lengths: Vec<usize> = self.words.values().copied().collect();
+        lengths.sort_unstable();
+        let median = lengths.get(lengths.len() / 2).copied().unwrap_or(0).max(1) as f64;
+        let mut explanations: Vec<Explanation> = self
+            .files
+            .iter()
+            .map(|f| {
+                let file_name = scan::file_stem(f);
+                let overrides = self.overrides.get(&file_name).copied().unwrap_or_default();
+                let info = self.review_data.get(&file_name);
+                let words = self.words.get(&file_name).copied().unwrap_or(0);
+                let settings = self.settings_for(&file_name);
+                let breakdown = breakdown_of(info, words, today, max_out, &settings);
+                let mut multiplier = overrides.priority;
+                if info.is_some_and(|i| i.changed) {
+                    multiplier *= settings.change_boost;
+                }
+                if let Some(days) = settings.recent_days
+                    && self
+                        .modified
+                        .get(&file_name)
+                        .is_some_and(|m| (today - *m).num_days() < days as i64)
+                {
+                    multiplier *= settings.recent_boost;
+                }
+                if settings.length_weight != 0.0 {
+                    multiplier *= (words.max(1) as f64 / median).powf(settings.length_weight);
+                }
+                multiplier *=
+                    1.0 + settings.difficulty_weight * info.map_or(0.0, ReviewInfo::difficulty);
+                if let Some(factor) = self.plugin_factors.get(&file_name) {
+                    multiplier *= factor;
+                }
+                let weight = (breakdown.weight(settings.basic_weight, settings.minimum_weight)
+                    as f64
+                    * multiplier)
+                    .round() as usize;
+
+                Explanation {
+                    file: f.clone(),
+                    suspended: self.is_suspended(&file_name),
+                    postponed: info.and_then(|i| i.postponed).filter(|d| *d > today),
+                    multiplier,
+                    decay_rate: overrides.decay,
+                    weight: weight.max(settings.minimum_weight.round() as usize),
+                    breakdown,
+                }
+            })
+            .collect();
+        explanations.sort_by_key(|e| e.weight);
+
+        explanations
+    }
+
+    /// Whether the file is suspended, by `memo suspend` or its frontmatter.
+    pub fn is_suspended(&self, file_name: &str) -> bool {
+        self.info(file_name).is_some_and(|i| i.suspended)
+            || self.overrides.get(file_name).is_some_and(|o| o.suspend)
+    }
+
+    /// Files kept out of draws.
+    pub fn suspended(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|f| self.is_suspended(&scan::file_stem(f)))
+            .cloned()
+            .collect()
+    }
+
+    /// Has a file drawn next, or not if `!pinned`.
+    pub fn pin(&mut self, file_name: &str, pinned: bool) -> Result<(), Error> {
+        let info = self
+            .review_data
+            .get_mut(file_name)
+            .ok_or_else(|| Error::NoFile(file_name.to_string()))?;
+        info.pinned = pinned;
+
+        Ok(())
+    }
+
+    /// Marks a file hard, or no longer if `!hard`.
+    pub fn set_hard(&mut self, file_name: &str, hard: bool) -> Result<(), Error> {
+        let info = self
+            .review_data
+            .get_mut(file_name)
+            .ok_or_else(|| Error::NoFile(file_name.to_string()))?;
+        info.hard = hard;
+
+        Ok(())
+    }
+
+    /// Adds a note on a file, or removes all of them if `text` is `None`.
+    pub fn annotate(
+        &mut self,
+        file_name: &str,
+        text: Option<&str>,
+        today: NaiveDate,
+    ) -> Result<(), Error> {
+        let info = self
+            .review_data
+            .get_mut(file_name)
+            .ok_or_else(|| Error::NoFile(file_name.to_string()))?;
+        match text {
+            Some(text) => info.notes.push(Annotation {
+                date: today,
+                text: text.to_string(),
+            }),
+            None => info.notes.clear(),
+        }
+
+        Ok(())
+    }
+
+    /// Puts a file on hold or back, suspending one takes it out of the queue.
+    pub fn suspend(&mut self, file_name: &str, suspended: bool) -> Result<(), Error> {
+        let info = self
+            .review_data
+            .get_mut(file_name)
+            .ok_or_else(|| Error::NoFile(file_name.to_string()))?;
+        info.suspended = suspended;
+        if suspended {
+            info.pending = None;
+        }
+
+        Ok(())
+    }
+
+    /// Takes over reviews made with another tool: the later of the last
+    /// reviews and the higher of the counts. Returns whether that changed
+    /// anything.
+    pub fn seed(
+        &mut self,
+        file_name: &str,
+        review_count: u32,
+        last_reviewed: Option<NaiveDate>,
+    ) -> Result<bool, Error> {
+        let info = self
+            .review_data
+            .get_mut(file_name)
+            .ok_or_else(|| Error::NoFile(file_name.to_string()))?;
+        let before = (info.review_count, info.last_reviewed);
+        info.review_count = info.review_count.max(review_count);
+        info.last_reviewed = info.last_reviewed.max(last_reviewed);
+
+        Ok((info.review_count, info.last_reviewed) != before)
+    }
+
+    /// The settings of the file's subdir with its overrides applied.
+    pub fn settings_for(&self, file_name: &str) -> DeckConfig {
+        let mut settings = self.nested.get(file_name).unwrap_or(&self.settings).clone();
+        if let Some(decay) = self.overrides.get(file_name).and_then(|o| o.decay) {
+            settings.decay_rate = decay;
+        }
+
+        settings
+    }
+
+    pub fn info(&self, file_name: &str) -> Option<&ReviewInfo> {
+        self.review_data.get(file_name)
+    }
+
+    /// Date the file is next due, the later of its interval and the day it
+    /// was postponed to. `None` if it was never reviewed nor postponed.
+    pub fn due(&self, file_name: &str) -> Option<NaiveDate> {
+        let info = self.info(file_name)?;
+        let scheduled = interval(info, &self.settings_for(file_name))
+            .and_then(|i| Some(info.last_reviewed? + Days::new(i.into())));
+
+        scheduled.max(info.postponed)
+    }
+
+    /// Makes a file due `on`, without counting as a review: it is not drawn
+    /// before then, and weighs as if its interval ended on that day.
+    pub fn schedule(
+        &mut self,
+        file_name: &str,
+        on: NaiveDate,
+        today: NaiveDate,
+    ) -> Result<(), Error> {
+        let settings = self.settings_for(file_name);
+        let info = self
+            .review_data
+            .get_mut(file_name)
+            .ok_or_else(|| Error::NoFile(file_name.to_string()))?;
+
+        info.postponed = Some(on).filter(|d| *d > today);
+        info.due = None;
+        if interval(info, &settings).is_some() {
+            info.due = Some(on);
+        }
+        if info.postponed.is_some() {
+            info.pending = None;
+        }
+
+        Ok(())
+    }
+
+    /// Number of files due on each day, except `except` and suspended files.
+    fn load(&self, except: &str) -> HashMap<NaiveDate, usize> {
+        let mut load = HashMap::new();
+        for file_name in self.review_data.keys() {
+            if file_name == except || self.is_suspended(file_name) {
+                continue;
+            }
+            if let Some(due) = self.due(file_name) {
+                *load.entry(due).or_default() += 1;
+            }
+        }
+
+        load
+    }
+
+    /// Adds the length of every pause over before `today`, and not yet
+    /// applied to the (top-level) deck, to its schedules. Returns those
+    /// pauses and how many files each moved.
+    pub fn resume(&mut self, today: NaiveDate) -> Vec<(Pause, usize)> {
+        let mut resumed = Vec::new();
+        for pause in Pauses::load(&self.pauses).pauses {
+            if pause.to >= today || pause.applied.iter().any(|d| d == top_level(&self.name)) {
+                continue;
+            }
+            let moved = pause.shift(&mut self.review_data);
+            let mut outside: ReviewData = self
+                .outside
+                .drain(..)
+                .map(|r| (r.file_name.clone(), r))
+                .collect();
+            pause.shift(&mut outside);
+            self.outside = outside.into_values().collect();
+            self.resumed.push(pause.clone());
+            resumed.push((pause, moved));
+        }
+
+        resumed
+    }
+
+    /// Files drawn but not yet rated, oldest draws first.
+    pub fn pending(&self) -> Vec<PathBuf> {
+        let mut pending: Vec<(NaiveDate, &PathBuf)> = self
+            .files
+            .iter()
+            .filter_map(|f| Some((self.info(&scan::file_stem(f))?.pending?, f)))
+            .collect();
+        pending.sort();
+
+        pending.into_iter().map(|(_, f)| f.clone()).collect()
+    }
+
+    /// Takes a file out of the queue without reviewing it.
+    pub fn skip(&mut self, file_name: &str) -> Result<(), Error> {
+        match self.review_data.get_mut(file_name) {
+            Some(info) if info.pending.is_some() => {
+                info.pending = None;
+                Ok(())
+            }
+            Some(_) => Err(Error::NotPending(file_name.to_string())),
+            None => Err(Error::NoFile(file_name.to_string())),
+        }
+    }
+
+    /// Puts back the files of today's draw that are still pending, so that
+    /// the next draw picks anew.
+    pub fn redraw(&mut self, today: NaiveDate) {
+        if self.drawn.date != Some(today) {
+            return;
+        }
+
+        for file_name in &self.drawn.files {
+            if let Some(info) = self.review_data.get_mut(file_name)
+                && info.pending == Some(today)
+            {
+                info.pending = None;
+            }
+        }
+        self.drawn = DailyDraw::default();
+    }
+
+    /// Tops the queue up to `budget`, drawing files at random by weight or
+    /// the heaviest ones if `top`, marking them pending. Pinned files come
+    /// first and are drawn even past the budget. Returns the new files, only
+    /// pinned ones if the deck was drawn from today already.
+    pub fn draw<R: Rng + ?Sized>(
+        &mut self,
+        today: NaiveDate,
+        budget: Budget,
+        top: bool,
+        rng: &mut R,
+    ) -> Vec<PathBuf> {
+        draw_all(std::slice::from_mut(self), today, budget, top, rng)
+            .pop()
+            .unwrap_or_default()
+    }
+
+    /// Pinned files that are neither suspended nor queued.
+    fn pinned(&self, queue: &[PathBuf]) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|f| {
+                let file_name = scan::file_stem(f);
+                self.info(&file_name).is_some_and(|i| i.pinned)
+                    && !self.is_suspended(&file_name)
+                    && !queue.contains(f)
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn is_new(&self, file: &Path) -> bool {
+        self.info(&scan::file_stem(file))
+            .is_none_or(|i| i.review_count == 0)
+    }
+
+    /// New and reviewed files drawn on `today`, whether still pending or
+    /// already rated.
+    fn drawn_on(&self, today: NaiveDate) -> (usize, usize) {
+        let mut counts = (0, 0);
+        for info in self.review_data.values() {
+            if info.pending == Some(today) {
+                if info.review_count == 0 {
+                    counts.0 += 1;
+                } else {
+                    counts.1 += 1;
+                }
+            } else if info.last_reviewed == Some(today) {
+                if info.review_count == 1 {
+                    counts.0 += 1;
+                } else {
+                    counts.1 += 1;
+                }
+            }
+        }
+
+        counts
+    }
+
+    /// Marks a drawn file as pending until it is rated.
+    pub fn mark_pending(&mut self, file_name: String, today: NaiveDate) {
+        review::mark_pending(&mut self.review_data, file_name, today);
+    }
+
+    /// Feeds a grade into the deck's scheduler, returning the days until the
+    /// file is due again. The decay scheduler only takes note that the file
+    /// was reviewed.
+    pub fn rate(&mut self, file_name: &str, grade: Grade, today: NaiveDate) -> Result<u32, Error> {
+        self.rate_timed(file_name, grade, today, None)
+    }
+
+    /// [`Deck::rate`], logging that the review took `duration` seconds.
+    pub fn rate_timed(
+        &mut self,
+        file_name: &str,
+        grade: Grade,
+        today: NaiveDate,
+        duration: Option<u64>,
+    ) -> Result<u32, Error> {
+        let settings = self.settings_for(file_name);
+        let info = self
+            .review_data
+            .get_mut(file_name)
+            .ok_or_else(|| Error::NoFile(file_name.to_string()))?;
+
+        let scheduled = record(info, grade, today, &settings);
+        let balanced = interval(info, &settings).is_some()
+            && (settings.fuzz > 0.0 || settings.max_due_per_day.is_some());
+        info.pending = None;
+
+        let mut days = scheduled;
+        if balanced {
+            let due = balance::due_day(today, scheduled, &self.load(file_name), &settings);
+            days = (due - today).num_days() as u32;
+            if days != scheduled
+                && let Some(info) = self.review_data.get_mut(file_name)
+            {
+                info.due = Some(due);
+            }
+        }
+        self.events.push(Event {
+            date: today,
+            deck: self.name.clone(),
+            file_name: file_name.to_string(),
+            grade,
+            duration,
+            id: Some(history::new_id()),
+        });
+
+        Ok(days)
+    }
+
+    /// Replaces the review data of every file with history by what replaying
+    /// the history gives, files without any keep theirs. Returns how many
+    /// files were rebuilt.
+    pub fn rebuild(&mut self) -> usize {
+        let replayed = history::replay(&history::load(&self.history), &self.settings);
+        let mut rebuilt = 0;
+
+        for (file_name, info) in self.review_data.iter_mut() {
+            if let Some(r) = replayed.get(file_name) {
+                *info = ReviewInfo {
+                    pending: info.pending,
+                    postponed: info.postponed,
+                    extra: info.extra.clone(),
+                    ..r.clone()
+                };
+                rebuilt += 1;
+            }
+        }
+
+        rebuilt
+    }
+
+    /// Drops the entries without a file, returning their names.
+    pub fn prune(&mut self) -> Vec<String> {
+        let mut names: Vec<String> = self.orphans.drain(..).map(|r| r.file_name).collect();
+        names.sort();
+
+        names
+    }
+
+    /// Writes the review data, then appends new reviews to the history and
+    /// the streak. What changed is recorded for `memo undo`.
+    pub fn save(&mut self) -> Result<(), Error> {
+        let data = self.data();
+        let mut change = Change::between(&self.name, &self.saved, &data);
+        change.events = self.events.len();
+        if self.drawn != self.saved_draw {
+            change.drawn = Some(self.saved_draw.clone());
+        }
+        if !change.is_empty() {
+            Journal::record(&self.undo, change).map_err(Error::Storage)?;
+        }
+
+        self.write(data)
+    }
+
+    /// Writes `memo-last-reviewed` and `memo-review-count` to the
+    /// frontmatter of the notes of `file_names`, those of split notes
+    /// summing up all their items.
+    fn write_frontmatter(&self, file_names: &[String]) -> io::Result<()> {
+        let mut notes: Vec<PathBuf> = Vec::new();
+        for file in &self.files {
+            let note = sections::split(file).0;
+            if file_names.contains(&scan::file_stem(file)) && !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+
+        for note in notes {
+            let items: Vec<&ReviewInfo> = self
+                .files
+                .iter()
+                .filter(|f| sections::split(f).0 == note)
+                .filter_map(|f| self.info(&scan::file_stem(f)))
+                .collect();
+            let Some(last_reviewed) = items.iter().filter_map(|i| i.last_reviewed).max() else {
+                continue;
+            };
+            let review_count: u32 = items.iter().map(|i| i.review_count).sum();
+
+            let text = fs::read_to_string(&note)?;
+            let updated = frontmatter::set(
+                &text,
+                &[
+                    ("memo-last-reviewed", last_reviewed.to_string()),
+                    ("memo-review-count", review_count.to_string()),
+                ],
+            );
+            if updated != text {
+                store::write_atomic(&note, updated.as_bytes(), 0)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Puts back what the last command changed, see [`Journal`].
+    pub fn undo(&mut self, change: &Change) -> Result<(), Error> {
+        let mut data = self.data();
+        change.revert(&mut data);
+        for (name, info) in self.review_data.iter_mut() {
+            if let Some(reverted) = data.get(name) {
+                *info = reverted.clone();
+            }
+        }
+        if let Some(drawn) = &change.drawn {
+            self.drawn = drawn.clone();
+        }
+        history::truncate(&self.history, change.events).map_err(Error::Storage)?;
+        self.events.clear();
+
+        self.write(data)
+    }
+
+    /// Every entry of the deck, orphans and those outside a nested deck
+    /// included.
+    fn data(&self) -> ReviewData {
+        let mut data = self.review_data.clone();
+        for orphan in self.orphans.iter().chain(&self.outside) {
+            data.entry(orphan.file_name.clone())
+                .or_insert_with(|| orphan.clone());
+        }
+
+        data
+    }
+
+    /// Runs the `post_review` hook for every review being saved, then the
+    /// `post_save` one, warning of those failing: what they follow is done.
+    fn run_hooks(&self) {
+        let mut runs = Vec::new();
+        if let Some(command) = &self.hooks.post_review {
+            for event in &self.events {
+                runs.push((
+                    "post_review",
+                    command,
+                    json!({
+                        "event": "post_review",
+                        "deck": self.name,
+                        "file": event.file_name,
+                        "grade": event.grade,
+                        "date": event.date,
+                        "duration": event.duration,
+                    }),
+                ));
+            }
+        }
+        if let Some(command) = &self.hooks.post_save {
+            runs.push((
+                "post_save",
+                command,
+                json!({
+                    "event": "post_save",
+                    "deck": self.name,
+                    "reviews": self.events.len(),
+                    "files": self.files.len(),
+                }),
+            ));
+        }
+
+        for (name, command, payload) in runs {
+            if let Err(e) = hooks::run(command, &payload) {
+                eprintln!("memo: {}", Error::Hook(name, e));
+            }
+        }
+    }
+
+    fn write(&mut self, data: ReviewData) -> Result<(), Error> {
+        // Nothing to save, like after a keypress of `memo tui` changing none.
+        if data != self.saved {
+            self.storage.save(&data).map_err(Error::Storage)?;
+        }
+        history::append(&self.history, &self.events).map_err(Error::Storage)?;
+        if self.drawn != self.saved_draw {
+            self.drawn.save(&self.daily).map_err(Error::Storage)?;
+        }
+        if !self.resumed.is_empty() {
+            let mut pauses = Pauses::load(&self.pauses);
+            for pause in &mut pauses.pauses {
+                let resumed = self
+                    .resumed
+                    .iter()
+                    .any(|r| r.from == pause.from && r.to == pause.to);
+                let top = top_level(&self.name).to_string();
+                if resumed && !pause.applied.contains(&top) {
+                    pause.applied.push(top);
+                }
+            }
+            pauses.save(&self.pauses).map_err(Error::Storage)?;
+            self.resumed.clear();
+        }
+
+        let file_names: Vec<String> = self.events.iter().map(|e| e.file_name.clone()).collect();
+        if self.settings.write_sr && !file_names.is_empty() {
+            sr::write_notes(self, &file_names).map_err(Error::Storage)?;
+        }
+        if self.settings.write_frontmatter && !file_names.is_empty() {
+            self.write_frontmatter(&file_names)
+                .map_err(Error::Storage)?;
+        }
+        if !self.events.is_empty() {
+            let mut streak = Streak::load(&self.streak);
+            let mut days: Vec<_> = self.events.iter().map(|e| e.date).collect();
+            days.sort();
+            days.into_iter().for_each(|d| streak.record(d));
+            streak.save(&self.streak).map_err(Error::Storage)?;
+        }
+        self.run_hooks();
+        self.events.clear();
+        self.saved = data;
+        self.saved_draw = self.drawn.clone();
+
+        Ok(())
+    }
+}
+
+/// The weight of a file with the terms it is made of.
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub file: PathBuf,
+    pub breakdown: Breakdown,
+
+    /// `memo-priority` of the frontmatter, times `change_boost` for files
+    /// changed since their last review, `recent_boost` for those modified
+    /// within `recent_days`, by `length_weight` and `difficulty_weight`, and
+    /// by the factors of weight plugins.
+    pub multiplier: f64,
+    /// `memo-decay` of the frontmatter.
+    pub decay_rate: Option<f64>,
+    pub suspended: bool,
+    /// Day the file was postponed to, if that is after today.
+    pub postponed: Option<NaiveDate>,
+    pub weight: usize,
+}
+
+/// How much to draw a day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Budget {
+    Files(usize),
+    /// Minutes of reading at the given words per minute.
+    Minutes(f64, u32),
+}
+
+/// [`Deck::draw`] over several decks at once: their files are weighed
+/// together, scaled by the `priority` of their deck, against one `budget`,
+/// each deck keeping its own daily limits. Returns the new files of every
+/// deck, in the order of `decks`.
+pub fn draw_all<R: Rng + ?Sized>(
+    decks: &mut [Deck],
+    today: NaiveDate,
+    budget: Budget,
+    top: bool,
+    rng: &mut R,
+) -> Vec<Vec<PathBuf>> {
+    let cost = |f: &PathBuf| match budget {
+        Budget::Files(_) => 1.0,
+        Budget::Minutes(_, words_per_minute) => scan::reading_minutes(f, words_per_minute),
+    };
+    let mut left = match budget {
+        Budget::Files(count) => count as f64,
+        Budget::Minutes(minutes, _) => minutes,
+    };
+
+    // Candidates as (cost, deck, file, weight), and the new and review
+    // limits left of every deck.
+    let mut candidates = Vec::new();
+    let mut limits = Vec::new();
+    let mut selected = Vec::new();
+    let pooled = decks.len() > 1;
+    for (i, deck) in decks.iter_mut().enumerate() {
+        let queue = deck.pending();
+        let pinned = deck.pinned(&queue);
+        left -= queue.iter().chain(&pinned).map(cost).sum::<f64>();
+
+        if deck.drawn.date == Some(today) {
+            for file in &pinned {
+                deck.mark_pending(scan::file_stem(file), today);
+                deck.drawn.files.push(scan::file_stem(file));
+            }
+            limits.push((Some(0), Some(0)));
+            selected.push(pinned);
+            continue;
+        }
+
+        candidates.extend(
+            deck.weights(today)
+                .into_iter()
+                .filter(|(f, _)| !queue.contains(f) && !pinned.contains(f))
+                .map(|(f, w)| {
+                    let w = if pooled {
+                        prioritized(w, deck.settings.priority)
+                    } else {
+                        w
+                    };
+                    (cost(&f), i, f, w)
+                }),
+        );
+
+        let (new_today, reviews_today) = deck.drawn_on(today);
+        let mut new_left = deck
+            .settings
+            .max_new_per_day
+            .map(|m| m.saturating_sub(new_today));
+        let mut reviews_left = deck
+            .settings
+            .max_reviews_per_day
+            .map(|m| m.saturating_sub(reviews_today));
+        for file in &pinned {
+            let limit = if deck.is_new(file) {
+                &mut new_left
+            } else {
+                &mut reviews_left
+            };
+            *limit = limit.map(|l| l.saturating_sub(1));
+        }
+        limits.push((new_left, reviews_left));
+        selected.push(pinned);
+    }
+
+    // One at a time, so that a file only counts against the budget and
+    // the limits once drawn.
+    loop {
+        candidates.retain(|(cost, i, f, _)| {
+            let limit = if decks[*i].is_new(f) {
+                limits[*i].0
+            } else {
+                limits[*i].1
+            };
+            *cost <= left && limit != Some(0)
+        });
+        let weights: Vec<_> = candidates
+            .iter()
+            .map(|(_, i, f, w)| ((*i, f.clone()), *w))
+            .collect();
+        let picked = if top {
+            select::top(&weights, 1)
+        } else {
+            select::random(&weights, 1, rng)
+        };
+        let Some((i, file)) = picked.into_iter().next() else {
+            break;
+        };
+
+        let limit = if decks[i].is_new(&file) {
+            &mut limits[i].0
+        } else {
+            &mut limits[i].1
+        };
+        *limit = limit.map(|l| l - 1);
+        left -= cost(&file);
+        candidates.retain(|(_, j, f, _)| (*j, f) != (i, &file));
+        selected[i].push(file);
+    }
+
+    for (deck, selected) in decks.iter_mut().zip(&selected) {
+        if deck.drawn.date == Some(today) {
+            continue;
+        }
+        for file in selected {
+            deck.mark_pending(scan::file_stem(file), today);
+        }
+        deck.drawn = DailyDraw {
+            date: Some(today),
+            files: selected.iter().map(|f| scan::file_stem(f)).collect(),
+        };
+    }
+
+    selected
+}
+
+/// `weight` scaled by a deck's `priority`, a file with any weight keeps some.
+fn prioritized(weight: usize, priority: f64) -> usize {
+    if weight == 0 {
+        return 0;
+    }
+
+    ((weight as f64 * priority).round() as usize).max(1)
+}
+
+/// The top-level deck of a deck, `408` for `408/os`.
+pub fn top_level(deck: &str) -> &str {
+    deck.split('/').next().unwrap_or(deck)
+}
+
+/// A deck name usable in file names, nested decks get dashes for slashes.
+pub fn slug(deck: &str) -> String {
+    deck.replace(['/', '\\'], "-")
+}
+
+/// Weight of a file of `words` words under the scheduler of `settings`.
+pub fn weight_of(
+    info: Option<&ReviewInfo>,
+    words: usize,
+    today: NaiveDate,
+    max_out: i64,
+    settings: &DeckConfig,
+) -> usize {
+    breakdown_of(info, words, today, max_out, settings)
+        .weight(settings.basic_weight, settings.minimum_weight)
+}
+
+/// The terms of [`weight_of`]. A file whose due date was moved is weighed
+/// as if that many days more or less had passed.
+pub fn breakdown_of(
+    info: Option<&ReviewInfo>,
+    words: usize,
+    today: NaiveDate,
+    max_out: i64,
+    settings: &DeckConfig,
+) -> Breakdown {
+    let scheduler = scheduler::of(settings);
+    let moved = info.and_then(|i| {
+        let scheduled = i.last_reviewed? + Days::new(scheduler.interval(i)?.into());
+        Some((i.due? - scheduled).num_days()).filter(|d| *d != 0)
+    });
+    let Some(moved) = moved else {
+        return scheduler.breakdown(info, words, today, max_out);
+    };
+
+    let mut breakdown = scheduler.breakdown(info, words, today - Duration::days(moved), max_out);
+    breakdown.days_since = info
+        .and_then(|i| i.last_reviewed)
+        .map(|d| (today - d).num_days().max(0));
+    breakdown
+}
+
+/// Feeds a grade into the scheduler of `settings`, see [`Deck::rate`].
+pub fn record(info: &mut ReviewInfo, grade: Grade, today: NaiveDate, settings: &DeckConfig) -> u32 {
+    scheduler::of(settings).review(info, grade, today);
+    info.last_reviewed = Some(today);
+    info.due = None;
+    info.postponed = None;
+    info.pinned = false;
+    info.changed = false;
+    info.grade_difficulty(grade);
+    info.review_count += 1;
+
+    interval(info, settings).unwrap_or(1)
+}
+
+/// Days between the last review of a file and its next one, `None` if it
+/// was never reviewed or the scheduler has no intervals. Follows a due date
+/// moved by load balancing.
+pub fn interval(info: &ReviewInfo, settings: &DeckConfig) -> Option<u32> {
+    let last = info.last_reviewed?;
+    let scheduled = scheduler::of(settings).interval(info)?;
+
+    Some(
+        info.due
+            .map_or(scheduled, |due| (due - last).num_days().max(1) as u32),
+    )
+}
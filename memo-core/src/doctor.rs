@@ -0,0 +1,94 @@
+//! Consistency checks of saved review data, and their repairs.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::review::{ReviewData, ReviewInfo};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Several entries for one file, with how many there are.
+    Duplicate(String, usize),
+    /// An entry whose file no longer exists.
+    Orphan(String),
+    /// An entry reviewed or drawn after today.
+    Future(String, NaiveDate),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate(name, count) => write!(f, "`{}` has {} entries", name, count),
+            Self::Orphan(name) => write!(f, "`{}` has no file", name),
+            Self::Future(name, date) => write!(f, "`{}` is dated in the future, {}", name, date),
+        }
+    }
+}
+
+/// Problems of the saved `entries`, `known` being the names that still exist.
+pub fn check(entries: &[ReviewInfo], known: &HashSet<String>, today: NaiveDate) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+
+    for entry in entries {
+        let name = &entry.file_name;
+        if !seen.insert(name) {
+            continue;
+        }
+
+        let count = entries.iter().filter(|e| &e.file_name == name).count();
+        if count > 1 {
+            problems.push(Problem::Duplicate(name.clone(), count));
+        }
+        if !known.contains(name) {
+            problems.push(Problem::Orphan(name.clone()));
+        }
+        let latest = entries
+            .iter()
+            .filter(|e| &e.file_name == name)
+            .flat_map(|e| [e.last_reviewed, e.pending])
+            .flatten()
+            .max();
+        if let Some(date) = latest.filter(|d| *d > today) {
+            problems.push(Problem::Future(name.clone(), date));
+        }
+    }
+
+    problems
+}
+
+/// The entries with `fixes` applied: duplicates are merged into the most
+/// reviewed one, orphans dropped and future dates moved to today.
+pub fn repair(entries: Vec<ReviewInfo>, fixes: &[Problem], today: NaiveDate) -> ReviewData {
+    let mut data = ReviewData::new();
+
+    for entry in entries {
+        let name = entry.file_name.clone();
+        if fixes.contains(&Problem::Orphan(name.clone())) {
+            continue;
+        }
+        let merge = fixes
+            .iter()
+            .any(|p| matches!(p, Problem::Duplicate(n, _) if *n == name));
+        match data.get(&name) {
+            Some(kept) if !merge || kept.review_count >= entry.review_count => {}
+            _ => {
+                data.insert(name, entry);
+            }
+        }
+    }
+
+    for info in data.values_mut() {
+        let fix = fixes
+            .iter()
+            .any(|p| matches!(p, Problem::Future(n, _) if *n == info.file_name));
+        if fix {
+            info.last_reviewed = info.last_reviewed.map(|d| d.min(today));
+            info.pending = info.pending.map(|d| d.min(today));
+        }
+    }
+
+    data
+}
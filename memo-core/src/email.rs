@@ -0,0 +1,72 @@
+//! Sending mail over SMTP, through `curl`.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use chrono::Local;
+
+use crate::config::EmailConfig;
+
+/// Sends a plain-text mail of `subject` and `body` as `email` says, the
+/// message staged in `outbox` meanwhile. Credentials go through stdin, out
+/// of sight of other users.
+pub fn send(email: &EmailConfig, subject: &str, body: &str, outbox: &Path) -> io::Result<()> {
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+        email.from,
+        email.to.join(", "),
+        subject,
+        Local::now().to_rfc2822()
+    );
+    // curl escapes leading dots itself.
+    for line in body.lines() {
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    fs::write(outbox, message)?;
+
+    let mut command = Command::new("curl");
+    command
+        .args(["-sS", "-K", "-", "--ssl-reqd", "--max-time", "30", "--url"])
+        .arg(&email.url)
+        .arg("--mail-from")
+        .arg(&email.from)
+        .arg("--upload-file")
+        .arg(outbox);
+    for to in &email.to {
+        command.arg("--mail-rcpt").arg(to);
+    }
+    let result = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run curl: {}", e)))
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take()
+                && let Some(user) = &email.user
+            {
+                let credentials =
+                    format!("{}:{}", user, email.password.as_deref().unwrap_or_default());
+                writeln!(
+                    stdin,
+                    "user = \"{}\"",
+                    credentials.replace('\\', "\\\\").replace('"', "\\\"")
+                )?;
+            }
+            child.wait_with_output()
+        });
+    let _ = fs::remove_file(outbox);
+
+    let output = result?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
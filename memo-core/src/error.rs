@@ -0,0 +1,81 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+use crate::config::ConfigError;
+
+#[derive(Debug)]
+pub enum Error {
+    Config(ConfigError),
+    NoDeck(String, PathBuf),
+    NoFile(String),
+    AmbiguousFile(String, Vec<String>),
+    NotPending(String),
+    /// A pause ending before it starts, or overlapping a recorded one.
+    InvalidPause(NaiveDate, NaiveDate),
+    Storage(io::Error),
+    /// Nothing up for review, in the deck if given.
+    NothingDue(Option<String>),
+    /// The lock file of the vault, held by the memo process of the PID.
+    /// The lock file and its owner's PID, if it could be read.
+    Locked(PathBuf, Option<u32>),
+    /// A hook of the config failed, by its name.
+    Hook(&'static str, io::Error),
+    /// A plugin failed, by its name.
+    Plugin(String, io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(e) => e.fmt(f),
+            Self::NoDeck(name, root) => write!(f, "no deck named `{}` in {}", name, root.display()),
+            Self::NoFile(name) => write!(f, "no file named `{}`", name),
+            Self::AmbiguousFile(name, decks) => write!(
+                f,
+                "`{}` is in several decks ({}), pick one with `--deck`",
+                name,
+                decks.join(", ")
+            ),
+            Self::NotPending(name) => write!(f, "`{}` is not in the queue", name),
+            Self::InvalidPause(from, to) if to < from => {
+                write!(f, "pause from {} to {} ends before it starts", from, to)
+            }
+            Self::InvalidPause(from, to) => {
+                write!(f, "pause from {} to {} overlaps a recorded one", from, to)
+            }
+            Self::Storage(e) => write!(f, "storage error: {}", e),
+            Self::NothingDue(Some(deck)) => write!(f, "nothing to review in `{}`", deck),
+            Self::NothingDue(None) => write!(f, "nothing to review"),
+            Self::Locked(path, pid) => write!(
+                f,
+                "another memo{} is using the vault, pass `--wait` to wait for it, or remove {} if it is gone",
+                pid.map(|p| format!(" (PID {})", p)).unwrap_or_default(),
+                path.display()
+            ),
+            Self::Hook(name, e) => write!(f, "hook `{}` failed: {}", name, e),
+            Self::Plugin(name, e) => write!(f, "plugin `{}` failed: {}", name, e),
+        }
+    }
+}
+
+impl Error {
+    /// Exit status of memo failing with the error: 2 when there is nothing
+    /// to review, 1 otherwise.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::NothingDue(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Self::Config(e)
+    }
+}
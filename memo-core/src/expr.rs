@@ -0,0 +1,545 @@
+//! A small arithmetic expression language for user-defined weights, e.g.
+//! `formula = "10 + days_since * 2 - ln(review_count + 1)"`.
+//!
+//! Supported are numbers, the variables of [`Vars`], `+ - * / % ^`,
+//! comparisons giving 1 or 0, parentheses and the functions `ln`, `log10`,
+//! `exp`, `sqrt`, `abs`, `floor`, `ceil`, `min`, `max`, `pow` and
+//! `if(cond, then, else)`.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Nesting deeper than this, of parentheses, calls and operators alike, is
+/// refused rather than overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// What a formula can refer to, per file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Vars {
+    /// Days since the last review, `max_out` for files without a date.
+    pub days_since: f64,
+    pub review_count: f64,
+    pub word_count: f64,
+    /// Retention under the decay scheduler with the deck's decay rate.
+    pub retention: f64,
+    pub decay_rate: f64,
+    /// 1 for files never reviewed, 0 otherwise.
+    pub is_new: f64,
+    pub max_out: f64,
+    /// From 0 for easy files to 1 for hard ones, see [`ReviewInfo::difficulty`].
+    ///
+    /// [`ReviewInfo::difficulty`]: crate::review::ReviewInfo::difficulty
+    pub difficulty: f64,
+}
+
+const VARS: [&str; 8] = [
+    "days_since",
+    "review_count",
+    "word_count",
+    "retention",
+    "decay_rate",
+    "is_new",
+    "max_out",
+    "difficulty",
+];
+
+impl Vars {
+    fn get(&self, index: usize) -> f64 {
+        [
+            self.days_since,
+            self.review_count,
+            self.word_count,
+            self.retention,
+            self.decay_rate,
+            self.is_new,
+            self.max_out,
+            self.difficulty,
+        ][index]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(f64),
+    Var(usize),
+    Neg(Box<Node>),
+    Bin(Op, Box<Node>, Box<Node>),
+    Call(String, Vec<Node>),
+}
+
+/// A parsed formula, keeping its source for display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let root = parser.comparison()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(ParseError(format!("unexpected `{}`", token)));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the formula refers to the variable `name`.
+    pub fn uses(&self, name: &str) -> bool {
+        let Some(index) = VARS.iter().position(|v| *v == name) else {
+            return false;
+        };
+        uses(&self.root, index)
+    }
+
+    /// The value of the formula, `NaN` and infinities made 0.
+    pub fn eval(&self, vars: &Vars) -> f64 {
+        let value = eval(&self.root, vars);
+        if value.is_finite() { value } else { 0.0 }
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Serialize for Formula {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Formula {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Formula::parse(&source)
+            .map_err(|e| serde::de::Error::custom(format!("formula `{}`: {}", source, e)))
+    }
+}
+
+fn uses(node: &Node, index: usize) -> bool {
+    match node {
+        Node::Num(_) => false,
+        Node::Var(i) => *i == index,
+        Node::Neg(n) => uses(n, index),
+        Node::Bin(_, a, b) => uses(a, index) || uses(b, index),
+        Node::Call(_, args) => args.iter().any(|a| uses(a, index)),
+    }
+}
+
+fn eval(node: &Node, vars: &Vars) -> f64 {
+    let bool = |b: bool| if b { 1.0 } else { 0.0 };
+
+    match node {
+        Node::Num(n) => *n,
+        Node::Var(i) => vars.get(*i),
+        Node::Neg(n) => -eval(n, vars),
+        Node::Bin(op, a, b) => {
+            let (a, b) = (eval(a, vars), eval(b, vars));
+            match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                Op::Div => a / b,
+                Op::Rem => a % b,
+                Op::Pow => a.powf(b),
+                Op::Lt => bool(a < b),
+                Op::Le => bool(a <= b),
+                Op::Gt => bool(a > b),
+                Op::Ge => bool(a >= b),
+                Op::Eq => bool(a == b),
+                Op::Ne => bool(a != b),
+            }
+        }
+        Node::Call(name, args) => {
+            if name == "if" {
+                return if eval(&args[0], vars) != 0.0 {
+                    eval(&args[1], vars)
+                } else {
+                    eval(&args[2], vars)
+                };
+            }
+            let args: Vec<f64> = args.iter().map(|a| eval(a, vars)).collect();
+            match name.as_str() {
+                "ln" => args[0].ln(),
+                "log10" => args[0].log10(),
+                "exp" => args[0].exp(),
+                "sqrt" => args[0].sqrt(),
+                "abs" => args[0].abs(),
+                "floor" => args[0].floor(),
+                "ceil" => args[0].ceil(),
+                "min" => args.iter().copied().fold(f64::INFINITY, f64::min),
+                "max" => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                "pow" => args[0].powf(args[1]),
+                _ => unreachable!("unknown functions are rejected when parsing"),
+            }
+        }
+    }
+}
+
+/// Number of arguments of each function, `None` for any but at least one.
+fn arity(name: &str) -> Option<Option<usize>> {
+    match name {
+        "ln" | "log10" | "exp" | "sqrt" | "abs" | "floor" | "ceil" => Some(Some(1)),
+        "pow" => Some(Some(2)),
+        "if" => Some(Some(3)),
+        "min" | "max" => Some(None),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Sym(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Num(n) => write!(f, "{}", n),
+            Self::Ident(s) => f.write_str(s),
+            Self::Sym(s) => f.write_str(s),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 16] = [
+    "<=", ">=", "==", "!=", "+", "-", "*", "/", "%", "^", "<", ">", "(", ")", ",", "=",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_digit() || c == '.' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let n = rest[..end]
+                .parse()
+                .map_err(|_| ParseError(format!("invalid number `{}`", &rest[..end])))?;
+            tokens.push(Token::Num(n));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else if let Some(sym) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            if *sym == "=" {
+                return Err(ParseError("`=` is not an operator, use `==`".to_string()));
+            }
+            tokens.push(Token::Sym(sym));
+            rest = &rest[sym.len()..];
+        } else {
+            return Err(ParseError(format!("unexpected `{}`", c)));
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Levels of the tree above the node being parsed.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, sym: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Sym(s)) if *s == sym);
+        if found {
+            self.pos += 1;
+        }
+
+        found
+    }
+
+    fn expect(&mut self, sym: &str) -> Result<(), ParseError> {
+        if self.eat(sym) {
+            Ok(())
+        } else {
+            Err(ParseError(match self.peek() {
+                Some(t) => format!("expected `{}`, found `{}`", sym, t),
+                None => format!("expected `{}` at the end", sym),
+            }))
+        }
+    }
+
+    /// Goes a level deeper into the tree, failing past [`MAX_DEPTH`].
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParseError(format!(
+                "nested deeper than {} levels",
+                MAX_DEPTH
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Each operator of a chain like `1 + 2 + 3` is a level of the tree.
+    fn binary(
+        &mut self,
+        ops: &[(&str, Op)],
+        next: fn(&mut Self) -> Result<Node, ParseError>,
+    ) -> Result<Node, ParseError> {
+        let depth = self.depth;
+        let mut node = next(self)?;
+        'outer: loop {
+            for (sym, op) in ops {
+                if self.eat(sym) {
+                    self.nest()?;
+                    node = Node::Bin(*op, Box::new(node), Box::new(next(self)?));
+                    continue 'outer;
+                }
+            }
+            self.depth = depth;
+            return Ok(node);
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Node, ParseError> {
+        self.binary(
+            &[
+                ("<=", Op::Le),
+                (">=", Op::Ge),
+                ("==", Op::Eq),
+                ("!=", Op::Ne),
+                ("<", Op::Lt),
+                (">", Op::Gt),
+            ],
+            Self::sum,
+        )
+    }
+
+    fn sum(&mut self) -> Result<Node, ParseError> {
+        self.binary(&[("+", Op::Add), ("-", Op::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Node, ParseError> {
+        self.binary(
+            &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Node, ParseError> {
+        self.nest()?;
+        let node = if self.eat("-") {
+            Node::Neg(Box::new(self.unary()?))
+        } else {
+            self.power()?
+        };
+        self.depth -= 1;
+
+        Ok(node)
+    }
+
+    /// `^` binds tighter than a leading `-` and to the right.
+    fn power(&mut self) -> Result<Node, ParseError> {
+        let base = self.primary()?;
+        if self.eat("^") {
+            Ok(Node::Bin(Op::Pow, Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, ParseError> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| ParseError("unexpected end of formula".to_string()))?;
+        self.pos += 1;
+
+        match token {
+            Token::Num(n) => Ok(Node::Num(n)),
+            Token::Sym("(") => {
+                let node = self.comparison()?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Token::Ident(name) if self.eat("(") => {
+                let arity = arity(&name)
+                    .ok_or_else(|| ParseError(format!("unknown function `{}`", name)))?;
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.comparison()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                let valid = match arity {
+                    Some(n) => args.len() == n,
+                    None => !args.is_empty(),
+                };
+                if !valid {
+                    return Err(ParseError(format!(
+                        "wrong number of arguments to `{}`",
+                        name
+                    )));
+                }
+                Ok(Node::Call(name, args))
+            }
+            Token::Ident(name) => VARS
+                .iter()
+                .position(|v| *v == name)
+                .map(Node::Var)
+                .ok_or_else(|| {
+                    ParseError(format!(
+                        "unknown variable `{}`, expected one of {}",
+                        name,
+                        VARS.join(", ")
+                    ))
+                }),
+            token => Err(ParseError(format!("unexpected `{}`", token))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, vars: &Vars) -> f64 {
+        Formula::parse(source).unwrap().eval(vars)
+    }
+
+    #[test]
+    fn precedence() {
+        let vars = Vars::default();
+        assert_eq!(eval("1 + 2 * 3", &vars), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", &vars), 9.0);
+        assert_eq!(eval("2 ^ 3 ^ 2", &vars), 512.0);
+        assert_eq!(eval("-2 ^ 2", &vars), -4.0);
+        assert_eq!(eval("7 % 4 - 1", &vars), 2.0);
+        assert_eq!(eval("1 + 1 == 2", &vars), 1.0);
+        assert_eq!(eval("3 < 2", &vars), 0.0);
+    }
+
+    #[test]
+    fn variables_and_functions() {
+        let vars = Vars {
+            days_since: 4.0,
+            review_count: 2.0,
+            ..Vars::default()
+        };
+        assert_eq!(eval("10 + days_since * 2 - review_count", &vars), 16.0);
+        assert_eq!(eval("sqrt(days_since) + pow(2, 3)", &vars), 10.0);
+        assert_eq!(
+            eval("min(3, days_since, 1) + max(review_count)", &vars),
+            3.0
+        );
+        assert_eq!(eval("if(is_new, 100, days_since)", &vars), 4.0);
+        assert_eq!(eval("floor(2.7) + ceil(0.2) + abs(-1)", &vars), 4.0);
+
+        let formula = Formula::parse("ln(review_count + 1)").unwrap();
+        assert!(formula.uses("review_count"));
+        assert!(!formula.uses("days_since"));
+        assert!(!formula.uses("nonsense"));
+    }
+
+    #[test]
+    fn non_finite_is_zero() {
+        assert_eq!(eval("1 / 0", &Vars::default()), 0.0);
+        assert_eq!(eval("sqrt(-1)", &Vars::default()), 0.0);
+    }
+
+    #[test]
+    fn errors() {
+        for source in [
+            "", "1 +", "(1", "1 2", "x", "foo(1)", "pow(1)", "min()", "a = 1", "1..2", "#",
+        ] {
+            assert!(Formula::parse(source).is_err(), "{}", source);
+        }
+        assert_eq!(
+            Formula::parse("days_since = 1").unwrap_err().to_string(),
+            "`=` is not an operator, use `==`"
+        );
+    }
+
+    #[test]
+    fn depth_is_limited() {
+        let nested =
+            |open: &str, close: &str, n: usize| format!("{}1{}", open.repeat(n), close.repeat(n));
+        assert!(Formula::parse(&nested("(", ")", 40)).is_ok());
+        assert!(Formula::parse(&nested("-", "", 40)).is_ok());
+
+        for source in [
+            nested("(", ")", 100_000),
+            nested("abs(", ")", 100_000),
+            nested("-", "", 100_000),
+            nested("2^", "", 100_000),
+            vec!["1"; 100_000].join(" + "),
+        ] {
+            let e = Formula::parse(&source).unwrap_err();
+            assert_eq!(e.to_string(), "nested deeper than 128 levels");
+        }
+    }
+
+    #[test]
+    fn serde_keeps_source() {
+        let formula: Formula = serde_json::from_str("\"days_since*2\"").unwrap();
+        assert_eq!(formula.source(), "days_since*2");
+        assert_eq!(serde_json::to_string(&formula).unwrap(), "\"days_since*2\"");
+        assert!(serde_json::from_str::<Formula>("\"1 +\"").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Log of the time spent reviewing in `memo session`, one JSON record per
+//! session, shared by every deck.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub date: NaiveDate,
+    pub deck: String,
+    /// Seconds spent on files, breaks and waits left out.
+    pub focused: u64,
+    /// Pomodoros completed, 0 for sessions without.
+    #[serde(default)]
+    pub pomodoros: u32,
+}
+
+/// `sessions.jsonl` in the revs dir.
+pub fn path(revs_dir: &Path) -> PathBuf {
+    revs_dir.join("sessions.jsonl")
+}
+
+/// Every session of the log, skipping lines that don't parse.
+pub fn load(path: &Path) -> Vec<Session> {
+    fs::read_to_string(path)
+        .map(|data| {
+            data.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn append(path: &Path, session: &Session) -> io::Result<()> {
+    let mut line = serde_json::to_string(session).map_err(io::Error::other)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    file.sync_data()
+}
+
+/// Seconds focused on `deck` over `sessions`, and on `today` alone.
+pub fn focused(sessions: &[Session], deck: &str, today: NaiveDate) -> (u64, u64) {
+    sessions
+        .iter()
+        .filter(|s| s.deck == deck)
+        .fold((0, 0), |(total, on_day), s| {
+            let on_day = on_day + if s.date == today { s.focused } else { 0 };
+            (total + s.focused, on_day)
+        })
+}
@@ -0,0 +1,85 @@
+//! Simulating a deck's scheduler forward to see how many files fall due.
+
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate};
+
+use crate::deck::{self, Deck};
+use crate::review::Grade;
+use crate::{scan, weight};
+
+/// Files due on each of the `days` days from `today`, assuming every file
+/// is reviewed on the day it is due and graded `good`. Overdue files count
+/// as due today; never reviewed and suspended files are left out.
+pub fn due_per_day(deck: &Deck, today: NaiveDate, days: u32) -> BTreeMap<NaiveDate, usize> {
+    due_files(deck, today, days)
+        .into_iter()
+        .map(|(day, files)| (day, files.len()))
+        .collect()
+}
+
+/// The names of the files due on each of the `days` days from `today`, as
+/// [`due_per_day`] counts them.
+pub fn due_files(deck: &Deck, today: NaiveDate, days: u32) -> BTreeMap<NaiveDate, Vec<String>> {
+    let end = today + Days::new(days.into());
+    let mut due_files: BTreeMap<NaiveDate, Vec<String>> = (0..days)
+        .map(|d| (today + Days::new(d.into()), Vec::new()))
+        .collect();
+
+    for file in &deck.files {
+        let file_name = scan::file_stem(file);
+        if deck.is_suspended(&file_name) {
+            continue;
+        }
+        let (Some(info), Some(due)) = (deck.info(&file_name), deck.due(&file_name)) else {
+            continue;
+        };
+
+        let settings = deck.settings_for(&file_name);
+        let mut info = info.clone();
+        let mut day = due.max(today);
+        while day < end {
+            due_files.entry(day).or_default().push(file_name.clone());
+            let interval = deck::record(&mut info, Grade::Good, day, &settings);
+            day = day + Days::new(interval.max(1).into());
+        }
+    }
+
+    due_files
+}
+
+/// The first day the estimated retention of a reviewed file is below
+/// `threshold`, looking up to `until`, or its due date if the scheduler
+/// estimates none or `threshold` is `None`. Never before the day it was
+/// postponed to.
+pub fn due_below(
+    deck: &Deck,
+    file_name: &str,
+    threshold: Option<f64>,
+    until: NaiveDate,
+) -> Option<NaiveDate> {
+    let info = deck.info(file_name)?;
+    let last = info.last_reviewed?;
+    let (Some(threshold), Some(_)) = (threshold, retention(deck, file_name, last)) else {
+        return deck.due(file_name);
+    };
+    let below = last
+        .iter_days()
+        .take_while(|d| *d <= until)
+        .find(|d| retention(deck, file_name, *d).is_some_and(|r| r < threshold))?;
+
+    Some(below).max(info.postponed)
+}
+
+/// Estimated retention of a file on `day`, for schedulers that have one.
+pub fn retention(deck: &Deck, file_name: &str, day: NaiveDate) -> Option<f64> {
+    let settings = deck.settings_for(file_name);
+    let words = deck.words.get(file_name).copied().unwrap_or(0);
+    let max_out = weight::max_out(
+        deck.files.len(),
+        settings.files_per_day,
+        settings.max_out_factor,
+    );
+
+    deck::breakdown_of(deck.info(file_name), words, day, max_out, &settings).retention
+}
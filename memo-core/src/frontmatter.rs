@@ -0,0 +1,255 @@
+//! YAML frontmatter of notes, the `---` fenced block at their very top.
+//!
+//! Only the flat subset notes use in practice is understood: `key: value`
+//! pairs, with lists either inline as `[a, b]` or as `- item` lines below
+//! the key. Anything else is ignored.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::hash::fnv1a;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Frontmatter {
+    pub fields: BTreeMap<String, Value>,
+}
+
+impl Frontmatter {
+    /// A scalar field, or the first item of a list.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match self.fields.get(key)? {
+            Value::Scalar(s) => Some(s),
+            Value::List(items) => items.first().map(String::as_str),
+        }
+    }
+
+    /// A list field, a scalar counting as a list of one.
+    pub fn list(&self, key: &str) -> Vec<String> {
+        match self.fields.get(key) {
+            Some(Value::Scalar(s)) => vec![s.clone()],
+            Some(Value::List(items)) => items.clone(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Frontmatter {
+    /// The `tags` (or `tag`) of the note without any leading `#`, a scalar
+    /// may list several separated by commas or spaces.
+    pub fn tags(&self) -> Vec<String> {
+        ["tags", "tag"]
+            .iter()
+            .flat_map(|key| self.list(key))
+            .flat_map(|t| {
+                t.split([',', ' '])
+                    .map(|t| t.trim().trim_start_matches('#').to_string())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+}
+
+/// Scheduling overrides a note declares with `memo-priority`, `memo-suspend`
+/// and `memo-decay` in its frontmatter.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Overrides {
+    /// Factor the weight of the note is multiplied by.
+    pub priority: f64,
+    pub suspend: bool,
+    /// Decay rate used instead of the deck's.
+    pub decay: Option<f64>,
+}
+
+impl Default for Overrides {
+    fn default() -> Self {
+        Self {
+            priority: 1.0,
+            suspend: false,
+            decay: None,
+        }
+    }
+}
+
+impl Frontmatter {
+    /// The overrides of the note, ignoring values that aren't valid.
+    pub fn overrides(&self) -> Overrides {
+        let number = |key| self.get(key).and_then(|v| v.parse::<f64>().ok());
+
+        Overrides {
+            priority: number("memo-priority")
+                .filter(|p| p.is_finite() && *p >= 0.0)
+                .unwrap_or(1.0),
+            suspend: self
+                .get("memo-suspend")
+                .is_some_and(|v| matches!(v, "true" | "yes" | "on")),
+            decay: number("memo-decay").filter(|r| config::valid_decay_rate(*r)),
+        }
+    }
+}
+
+/// Whether `tag` is `wanted` or nested below it, ignoring case, so that
+/// `os/process` matches `os`.
+pub fn tag_matches(tag: &str, wanted: &str) -> bool {
+    let (tag, wanted) = (
+        tag.to_lowercase(),
+        wanted.trim_start_matches('#').to_lowercase(),
+    );
+
+    tag == wanted || tag.starts_with(&format!("{}/", wanted))
+}
+
+/// The frontmatter of `text` and the rest of it.
+pub fn parse(text: &str) -> (Frontmatter, &str) {
+    let mut frontmatter = Frontmatter::default();
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (frontmatter, text);
+    };
+
+    let mut body = "";
+    let mut block = rest;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            block = &rest[..offset];
+            body = &rest[offset + line.len()..];
+            break;
+        }
+        offset += line.len();
+    }
+    if offset == rest.len() {
+        // Never closed, so not frontmatter after all.
+        return (frontmatter, text);
+    }
+
+    let mut last_key: Option<String> = None;
+    for line in block.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            if let Some(key) = &last_key {
+                let value = frontmatter
+                    .fields
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::List(Vec::new()));
+                if let Value::List(items) = value {
+                    items.push(unquote(item));
+                }
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim();
+        last_key = Some(key.clone());
+
+        if value.is_empty() {
+            continue;
+        }
+        let value = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Some(items) => Value::List(
+                items
+                    .split(',')
+                    .map(unquote)
+                    .filter(|i| !i.is_empty())
+                    .collect(),
+            ),
+            None => Value::Scalar(unquote(value)),
+        };
+        frontmatter.fields.insert(key, value);
+    }
+
+    (frontmatter, body)
+}
+
+/// `text` with the frontmatter `fields` set to scalar values, replacing the
+/// lines of those it has and adding a frontmatter if there is none.
+pub fn set(text: &str, fields: &[(&str, String)]) -> String {
+    let fenced = text.starts_with("---\n") || text.starts_with("---\r\n");
+    let lines: Vec<&str> = text.lines().collect();
+    let close = lines
+        .iter()
+        .skip(1)
+        .position(|l| l.trim_end() == "---")
+        .map(|i| i + 1)
+        .filter(|_| fenced);
+    let Some(close) = close else {
+        let block: Vec<String> = fields
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v))
+            .collect();
+        return format!("---\n{}\n---\n{}", block.join("\n"), text);
+    };
+
+    let mut out: Vec<String> = vec![lines[0].to_string()];
+    let mut done = vec![false; fields.len()];
+    let mut replacing = false;
+    for line in &lines[1..close] {
+        if replacing && line.trim_start().starts_with("- ") {
+            continue;
+        }
+        replacing = false;
+        let key = line.split_once(':').map(|(k, _)| k.trim());
+        match fields.iter().position(|(k, _)| Some(*k) == key) {
+            Some(i) => {
+                out.push(format!("{}: {}", fields[i].0, fields[i].1));
+                done[i] = true;
+                replacing = true;
+            }
+            None => out.push(line.to_string()),
+        }
+    }
+    for (i, (key, value)) in fields.iter().enumerate() {
+        if !done[i] {
+            out.push(format!("{}: {}", key, value));
+        }
+    }
+    out.extend(lines[close..].iter().map(|l| l.to_string()));
+
+    let mut joined = out.join("\n");
+    if text.ends_with('\n') {
+        joined.push('\n');
+    }
+
+    joined
+}
+
+/// A stable ID of a note, the `id` of its frontmatter or else a hash of its
+/// body, so that it survives renames. Empty notes have none.
+pub fn note_id(text: &str) -> Option<String> {
+    let (frontmatter, body) = parse(text);
+
+    match frontmatter.get("id") {
+        Some(id) => Some(id.to_string()),
+        None if body.trim().is_empty() => None,
+        None => Some(format!("hash:{:016x}", fnv1a(body.trim().as_bytes()))),
+    }
+}
+
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    let quoted = s.len() >= 2
+        && ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')));
+
+    if quoted {
+        s[1..s.len() - 1].to_string()
+    } else {
+        s.to_string()
+    }
+}
@@ -0,0 +1,165 @@
+//! FSRS-4.5 (Free Spaced Repetition Scheduler) with its default parameters.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::review::{Grade, ReviewInfo};
+use crate::weight::Breakdown;
+
+const W: [f64; 17] = [
+    0.4072, 1.1829, 3.1262, 15.4722, 7.2102, 0.5316, 1.0651, 0.0234, 1.616, 0.1544, 1.0824, 1.9813,
+    0.0953, 0.2975, 2.2042, 0.2407, 2.9466,
+];
+const DECAY: f64 = -0.5;
+const FACTOR: f64 = 19.0 / 81.0;
+
+/// Retrievability at which a review is due.
+pub const DESIRED_RETENTION: f64 = 0.9;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FsrsState {
+    /// Days for retrievability to fall to 90%.
+    pub stability: f64,
+    /// Between 1 and 10.
+    pub difficulty: f64,
+}
+
+impl FsrsState {
+    pub fn new(grade: Grade) -> Self {
+        Self {
+            stability: W[g(grade) as usize - 1],
+            difficulty: initial_difficulty(g(grade)),
+        }
+    }
+
+    pub fn review(&mut self, grade: Grade, elapsed_days: i64) {
+        let r = retrievability(elapsed_days as f64, self.stability);
+        let (s, d) = (self.stability, self.difficulty);
+
+        self.stability = if grade == Grade::Again {
+            W[11] * d.powf(-W[12]) * ((s + 1.0).powf(W[13]) - 1.0) * (W[14] * (1.0 - r)).exp()
+        } else {
+            let hard_penalty = if grade == Grade::Hard { W[15] } else { 1.0 };
+            let easy_bonus = if grade == Grade::Easy { W[16] } else { 1.0 };
+            s * (W[8].exp()
+                * (11.0 - d)
+                * s.powf(-W[9])
+                * ((W[10] * (1.0 - r)).exp() - 1.0)
+                * hard_penalty
+                * easy_bonus
+                + 1.0)
+        };
+
+        let next = d - W[6] * (g(grade) - 3.0);
+        self.difficulty = (W[7] * initial_difficulty(3.0) + (1.0 - W[7]) * next).clamp(1.0, 10.0);
+    }
+
+    /// Days until retrievability falls to [`DESIRED_RETENTION`].
+    pub fn interval(&self) -> u32 {
+        let days = self.stability / FACTOR * (DESIRED_RETENTION.powf(1.0 / DECAY) - 1.0);
+        days.round().max(1.0) as u32
+    }
+}
+
+fn g(grade: Grade) -> f64 {
+    match grade {
+        Grade::Again => 1.0,
+        Grade::Hard => 2.0,
+        Grade::Good => 3.0,
+        Grade::Easy => 4.0,
+    }
+}
+
+fn initial_difficulty(g: f64) -> f64 {
+    (W[4] - (g - 3.0) * W[5]).clamp(1.0, 10.0)
+}
+
+/// Probability of recalling a file `elapsed_days` after its last review.
+pub fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+    (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+}
+
+/// Weight of a file, growing as its retrievability falls below [`DESIRED_RETENTION`].
+pub fn breakdown(info: Option<&ReviewInfo>, today: NaiveDate) -> Breakdown {
+    let Some(d) = info.and_then(|i| i.last_reviewed) else {
+        return Breakdown::new_file();
+    };
+    let days_since_last = (today - d).num_days().max(0);
+
+    match info.and_then(|i| i.fsrs.as_ref()) {
+        // Reviewed before the deck switched to FSRS, treat it as due.
+        None => Breakdown {
+            days_since: Some(days_since_last),
+            priority: 50.0,
+            ..Default::default()
+        },
+        Some(s) => {
+            let r = retrievability(days_since_last as f64, s.stability);
+            Breakdown {
+                days_since: Some(days_since_last),
+                retention: Some(r),
+                priority: ((1.0 - r) / (1.0 - DESIRED_RETENTION)).min(2.0) * 50.0,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_keep_the_desired_retention() {
+        for (grade, days) in [
+            (Grade::Again, 0),
+            (Grade::Hard, 1),
+            (Grade::Good, 3),
+            (Grade::Easy, 15),
+        ] {
+            let state = FsrsState::new(grade);
+            assert_eq!(state.interval(), days.max(1), "{:?}", grade);
+            let r = retrievability(state.stability, state.stability);
+            assert!((r - DESIRED_RETENTION).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn reviews_move_stability_and_difficulty() {
+        let mut state = FsrsState::new(Grade::Good);
+        let first = state.interval();
+        state.review(Grade::Good, first as i64);
+        let second = state.interval();
+        assert!(second > first, "{} > {}", second, first);
+
+        let (stability, difficulty) = (state.stability, state.difficulty);
+        state.review(Grade::Again, second as i64);
+        assert!(state.stability < stability);
+        assert!(state.difficulty > difficulty);
+
+        for _ in 0..50 {
+            state.review(Grade::Again, 1);
+        }
+        assert!(state.difficulty <= 10.0);
+        assert!(state.interval() >= 1);
+    }
+
+    #[test]
+    fn lower_retrievability_weighs_more() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 11).unwrap();
+        let reviewed = |days| ReviewInfo {
+            last_reviewed: today.checked_sub_days(chrono::Days::new(days)),
+            fsrs: Some(FsrsState::new(Grade::Good)),
+            ..Default::default()
+        };
+
+        let (fresh, stale) = (
+            breakdown(Some(&reviewed(0)), today),
+            breakdown(Some(&reviewed(30)), today),
+        );
+        assert_eq!(fresh.retention, Some(1.0));
+        assert_eq!(fresh.priority, 0.0);
+        assert!(stale.retention.unwrap() < DESIRED_RETENTION);
+        assert_eq!(stale.priority, 100.0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Sync of the revs dir through a git remote, driving the `git` command.
+//!
+//! The revs dir is a repository of its own. History files are merged as
+//! unions of their lines. Rotated backups, the undo journal, the lock file
+//! and the index of the vault stay local.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+const GITIGNORE: &str =
+    "*.tmp\n*.json.[0-9]*\n*.msgpack.[0-9]*\nundo.json\nmemo.lock\nindex.json\n";
+const GITATTRIBUTES: &str = "*.jsonl merge=union\n";
+
+/// What a sync did.
+#[derive(Debug, Default)]
+pub struct Synced {
+    /// Files committed before merging.
+    pub committed: usize,
+    /// Whether there was anything on the remote to merge.
+    pub merged: bool,
+}
+
+/// Commits the changes of the repository `dir`, creating it for `remote`
+/// first if needed, merges the branch of the remote into it and pushes the
+/// result back. A conflicting merge is aborted, leaving `dir` as it was after
+/// the commit.
+pub fn sync(dir: &Path, remote: &str, message: &str) -> io::Result<Synced> {
+    if !dir.join(".git").exists() {
+        git(dir, &["init", "-q"])?;
+    }
+    for (name, contents) in [(".gitignore", GITIGNORE), (".gitattributes", GITATTRIBUTES)] {
+        if !dir.join(name).exists() {
+            fs::write(dir.join(name), contents)?;
+        }
+    }
+    match git(dir, &["remote", "get-url", "origin"]) {
+        Ok(url) if url.trim() == remote => {}
+        Ok(_) => drop(git(dir, &["remote", "set-url", "origin", remote])?),
+        Err(_) => drop(git(dir, &["remote", "add", "origin", remote])?),
+    }
+
+    let mut synced = Synced {
+        committed: changes(dir)?.len(),
+        ..Synced::default()
+    };
+    if synced.committed > 0 {
+        git(dir, &["add", "-A"])?;
+        commit(dir, &["commit", "-q", "-m", message])?;
+    }
+
+    let branch = git(dir, &["symbolic-ref", "--short", "HEAD"])?;
+    let branch = branch.trim();
+    if git(dir, &["fetch", "-q", "origin", branch]).is_ok() {
+        if let Err(e) = commit(
+            dir,
+            &[
+                "merge",
+                "-q",
+                "--no-edit",
+                "--allow-unrelated-histories",
+                "FETCH_HEAD",
+            ],
+        ) {
+            let conflicts =
+                git(dir, &["diff", "--name-only", "--diff-filter=U"]).unwrap_or_default();
+            let _ = git(dir, &["merge", "--abort"]);
+            let conflicts: Vec<&str> = conflicts.lines().collect();
+            if conflicts.is_empty() {
+                return Err(e);
+            }
+            return Err(io::Error::other(format!(
+                "conflicting changes to {} in {}, merge them by hand",
+                conflicts.join(", "),
+                dir.display()
+            )));
+        }
+        synced.merged = true;
+    }
+    git(dir, &["push", "-q", "-u", "origin", branch])?;
+
+    Ok(synced)
+}
+
+/// Files of the repository `dir` with uncommitted changes, all of them if it
+/// is no repository yet.
+pub fn changes(dir: &Path) -> io::Result<Vec<String>> {
+    if !dir.join(".git").exists() {
+        let mut files: Vec<String> = fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        return Ok(files);
+    }
+
+    Ok(
+        git(dir, &["status", "--porcelain", "--untracked-files=all"])?
+            .lines()
+            .filter_map(|l| l.get(3..))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Runs a command creating commits, with an identity of its own where git
+/// has none configured.
+fn commit(dir: &Path, args: &[&str]) -> io::Result<String> {
+    if git(dir, &["config", "user.email"]).is_ok() {
+        return git(dir, args);
+    }
+
+    let mut with_identity = vec!["-c", "user.name=memo", "-c", "user.email=memo@localhost"];
+    with_identity.extend_from_slice(args);
+    git(dir, &with_identity)
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {}: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,124 @@
+//! Append-only log of reviews, one JSON record per line, kept next to the
+//! revs file of each deck.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::config::DeckConfig;
+use crate::review::{Grade, ReviewData, ReviewInfo};
+use crate::{deck, store};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub date: NaiveDate,
+    pub deck: String,
+    pub file_name: String,
+    pub grade: Grade,
+
+    /// Seconds spent on the review, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+
+    /// Random ID telling apart reviews alike otherwise, like two of a file
+    /// on the same day on two machines. Missing in older logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// A new ID for an [`Event`].
+pub fn new_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+/// `history-<deck>.jsonl` in the revs dir.
+pub fn path(revs_dir: &Path, deck: &str) -> PathBuf {
+    revs_dir.join(format!("history-{}.jsonl", deck::slug(deck)))
+}
+
+/// Every event of the log, skipping lines that don't parse.
+pub fn load(path: &Path) -> Vec<Event> {
+    fs::read_to_string(path)
+        .map(|data| {
+            data.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn append(path: &Path, events: &[Event]) -> io::Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    // One write for all lines, a torn one loses at most the last line,
+    // which `load` skips.
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event).map_err(io::Error::other)?);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(lines.as_bytes())?;
+    file.sync_data()
+}
+
+/// Number of lines of the log that don't parse.
+pub fn bad_lines(path: &Path) -> usize {
+    fs::read_to_string(path).map_or(0, |data| {
+        data.lines()
+            .filter(|line| serde_json::from_str::<Event>(line).is_err())
+            .count()
+    })
+}
+
+/// Rewrites the log without the lines that don't parse.
+pub fn compact(path: &Path) -> io::Result<()> {
+    write(path, &load(path))
+}
+
+/// Writes `events` as the whole log.
+pub fn write(path: &Path, events: &[Event]) -> io::Result<()> {
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event).map_err(io::Error::other)?);
+        lines.push('\n');
+    }
+
+    store::write_atomic(path, lines.as_bytes(), 0)
+}
+
+/// Drops the last `count` lines of the log.
+pub fn truncate(path: &Path, count: usize) -> io::Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+    let data = fs::read_to_string(path)?;
+    let lines: Vec<&str> = data.lines().collect();
+    let mut kept = lines[..lines.len().saturating_sub(count)].join("\n");
+    if !kept.is_empty() {
+        kept.push('\n');
+    }
+
+    store::write_atomic(path, kept.as_bytes(), 0)
+}
+
+/// Rebuilds review data by feeding every event, in order, to the scheduler
+/// of `settings`.
+pub fn replay(events: &[Event], settings: &DeckConfig) -> ReviewData {
+    let mut review_data = ReviewData::new();
+
+    for event in events {
+        let info = review_data
+            .entry(event.file_name.clone())
+            .or_insert_with(|| ReviewInfo::new(event.file_name.clone()));
+        deck::record(info, event.grade, event.date, settings);
+    }
+
+    review_data
+}
@@ -0,0 +1,80 @@
+//! User commands run at points of memo's work, handed what happened as JSON
+//! on stdin: `pre_draw` before drawing, which fails the draw by failing,
+//! and `post_review` and `post_save` after saving.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use serde_json::Value;
+
+/// Runs `command` through the shell with `payload` on stdin, failing if it
+/// does, with what it wrote to stderr.
+pub fn run(command: &str, payload: &Value) -> io::Result<()> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.args(["/C", command]);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.args(["-c", command]);
+        c
+    };
+    feed(&mut shell, payload, false).map(|_| ())
+}
+
+/// Runs `command` with `payload` on stdin, returning its stdout if `capture`
+/// and failing if it does with what it wrote to stderr, as hooks and
+/// plugins are run.
+pub(crate) fn feed(command: &mut Command, payload: &Value, capture: bool) -> io::Result<String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(if capture {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Written while its output is read, or a command answering before
+    // reading it all would fill the pipes both ways and wait on memo forever.
+    let writer = child.stdin.take().map(|mut stdin| {
+        let input = format!("{}\n", payload);
+        // Commands that don't read their input close the pipe early.
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        })
+    });
+
+    let output = child.wait_with_output()?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(if stderr.is_empty() {
+            format!("exited with {}", output.status)
+        } else {
+            stderr
+        }));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn feed_answers_before_reading_large_payloads() {
+        let payload = Value::String("x".repeat(1 << 20));
+        let mut command = Command::new("sh");
+        command.args(["-c", "head -c 200000 /dev/zero; wc -c"]);
+
+        let stdout = feed(&mut command, &payload, true).unwrap();
+        assert_eq!(stdout.len(), 200000 + "1048579\n".len());
+        assert!(stdout.ends_with("1048579\n"));
+    }
+}
@@ -0,0 +1,69 @@
+//! iCalendar files of forecast reviews, one all-day event a day.
+
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate};
+
+/// A calendar of the reviews of `deck` due each day, `due` giving the names
+/// of the files due, stamped with `today`. Events are named by deck and day,
+/// so that importing again replaces those imported before.
+pub fn calendar(deck: &str, due: &BTreeMap<NaiveDate, Vec<String>>, today: NaiveDate) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//memo//review forecast//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(&format!("memo {}", deck))),
+    ];
+    for (day, files) in due.iter().filter(|(_, f)| !f.is_empty()) {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!(
+                "UID:{}-{}@memo",
+                day.format("%Y%m%d"),
+                deck.replace(|c: char| !c.is_alphanumeric(), "-")
+            ),
+            format!("DTSTAMP:{}T000000Z", today.format("%Y%m%d")),
+            format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (*day + Days::new(1)).format("%Y%m%d")
+            ),
+            format!(
+                "SUMMARY:{}",
+                escape(&format!("{}: {} file(s) due", deck, files.len()))
+            ),
+            format!("DESCRIPTION:{}", escape(&files.join("\n"))),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| fold(l)).collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// `line` ended by CRLF and folded to lines of at most 75 bytes, as the
+/// format asks.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
@@ -0,0 +1,77 @@
+//! Paths kept out of decks, by the `exclude` globs of the config and the
+//! `.memoignore` file at the vault root.
+//!
+//! A pattern with a `/` is matched against the path relative to the root,
+//! one without against every component of it. `*` and `?` don't cross a
+//! `/`, `**` does, and a trailing `/**` also matches the dir itself.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct Ignore {
+    root: PathBuf,
+    patterns: Vec<String>,
+}
+
+impl Ignore {
+    /// `patterns` together with the lines of `<root>/.memoignore`, skipping
+    /// blank ones and `#` comments.
+    pub fn load(root: &Path, patterns: &[String]) -> Self {
+        let file = fs::read_to_string(root.join(".memoignore")).unwrap_or_default();
+        let patterns = patterns
+            .iter()
+            .map(String::as_str)
+            .chain(file.lines())
+            .map(|p| p.trim().trim_end_matches('/'))
+            .filter(|p| !p.is_empty() && !p.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+
+        Self {
+            root: root.to_path_buf(),
+            patterns,
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if relative.is_empty() {
+            return false;
+        }
+
+        self.patterns.iter().any(|p| match p.strip_prefix('/') {
+            Some(p) => matches(p, &relative),
+            None if p.contains('/') => matches(p, &relative),
+            None => relative.split('/').any(|c| matches(p, c)),
+        })
+    }
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = path.chars().collect();
+
+    glob(&p, &t)
+        || pattern
+            .strip_suffix("/**")
+            .is_some_and(|dir| matches(dir, path))
+}
+
+fn glob(p: &[char], t: &[char]) -> bool {
+    match p {
+        [] => t.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob(rest, t) || (0..t.len()).any(|i| t[i] == '/' && glob(rest, &t[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=t.len()).any(|i| glob(rest, &t[i..])),
+        ['*', rest @ ..] => (0..=t.len())
+            .take_while(|&i| i == 0 || t[i - 1] != '/')
+            .any(|i| glob(rest, &t[i..])),
+        ['?', rest @ ..] => t.first().is_some_and(|&c| c != '/') && glob(rest, &t[1..]),
+        [c, rest @ ..] => t.first() == Some(c) && glob(rest, &t[1..]),
+    }
+}
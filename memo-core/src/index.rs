@@ -0,0 +1,382 @@
+//! Cache of the listings of vault dirs and of what decks need of the notes
+//! in them, `index.json` in the cache dir.
+//!
+//! A dir's mtime changes whenever an entry is added to, removed from or
+//! renamed in it, so a dir with the mtime of its cached listing is not read
+//! again, nor a note with the mtime of its cached [`Item`] or [`Note`].
+//! Those changed within the last seconds are not cached, their mtime may not
+//! show a change made right after.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::frontmatter::{self, Overrides};
+use crate::ignore::Ignore;
+use crate::{hash, scan, sections, store};
+
+/// Of the index's layout, an index of another one is read anew.
+const VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Index {
+    #[serde(default)]
+    version: u32,
+    dirs: BTreeMap<PathBuf, Listing>,
+    #[serde(default)]
+    items: BTreeMap<PathBuf, Item>,
+    #[serde(default)]
+    notes: BTreeMap<PathBuf, Note>,
+    #[serde(skip)]
+    changed: bool,
+}
+
+/// What a deck needs of a review item, read from its note.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    /// Nanoseconds since the epoch of the note's mtime.
+    mtime: u128,
+    /// Day the note was last modified.
+    pub modified: NaiveDate,
+    pub words: usize,
+    /// Of the text of the item, see [`hash::fnv1a`].
+    pub hash: String,
+    /// Of the note's frontmatter.
+    pub overrides: Overrides,
+    /// Of the note, see [`frontmatter::note_id`].
+    pub id: Option<String>,
+    /// Of the note's frontmatter.
+    pub tags: Vec<String>,
+}
+
+/// What splitting a note into review items needs of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    mtime: u128,
+    /// Of the whole note, see [`scan::words`].
+    pub words: usize,
+    /// Titles of its headings of levels 1 and 2, see [`sections::titles`].
+    pub headings: [Vec<String>; 2],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Listing {
+    /// Nanoseconds since the epoch.
+    mtime: u128,
+    files: Vec<String>,
+    dirs: Vec<String>,
+}
+
+impl Index {
+    pub fn path(cache_dir: &Path) -> PathBuf {
+        cache_dir.join("index.json")
+    }
+
+    /// The index at `path`, empty if there is none, it doesn't parse or is
+    /// of another version.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<Self>(&data).ok())
+            .filter(|index| index.version == VERSION)
+            .unwrap_or(Self {
+                version: VERSION,
+                ..Self::default()
+            })
+    }
+
+    /// Saves the index if any listing changed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if !self.changed || !path.parent().is_some_and(Path::is_dir) {
+            return Ok(());
+        }
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+
+        store::write_atomic(path, json.as_bytes(), 0)
+    }
+
+    /// Every file under `dir` that isn't ignored, recursively and sorted
+    /// within each dir. The subdirs of `dir` are walked in parallel, by a
+    /// worker per core at most.
+    pub fn files(&mut self, dir: &Path, ignore: &Ignore) -> Vec<PathBuf> {
+        if ignore.is_ignored(dir) {
+            return Vec::new();
+        }
+        let Some(root) = self.list(dir) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<PathBuf> = root.1.files.iter().map(|f| dir.join(f)).collect();
+        let mut listings = vec![(dir.to_path_buf(), root.1.clone(), root.0)];
+        let subdirs: Vec<PathBuf> = root
+            .1
+            .dirs
+            .iter()
+            .map(|d| dir.join(d))
+            .filter(|d| !ignore.is_ignored(d))
+            .collect();
+        let workers = thread::available_parallelism()
+            .map_or(4, |n| n.get())
+            .min(subdirs.len());
+        let next = AtomicUsize::new(0);
+        let mut walks: Vec<_> = thread::scope(|scope| {
+            let (index, subdirs, next) = (&*self, &subdirs, &next);
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut walks = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(d) = subdirs.get(i) else {
+                                return walks;
+                            };
+                            walks.push((i, index.walk(d, ignore)));
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .filter_map(|h| h.join().ok())
+                .flatten()
+                .collect()
+        });
+        walks.sort_by_key(|(i, _)| *i);
+        for (_, (walked, read)) in walks {
+            files.extend(walked);
+            listings.extend(read);
+        }
+        files.retain(|f| !ignore.is_ignored(f));
+
+        // Listings of dirs under `dir` not seen now are of removed ones.
+        let seen: HashSet<&PathBuf> = listings.iter().map(|(d, _, _)| d).collect();
+        let before = self.dirs.len();
+        self.dirs
+            .retain(|d, _| !d.starts_with(dir) || seen.contains(d));
+        self.changed |= self.dirs.len() != before;
+        for (path, listing, fresh) in listings {
+            if fresh {
+                self.changed = true;
+                self.dirs.insert(path, listing);
+            }
+        }
+
+        files
+    }
+
+    /// What a deck needs of the review item at `path`, cached if the mtime
+    /// of its note says it is unchanged. `None` if the note can't be read.
+    pub fn item(&mut self, path: &Path) -> Option<Item> {
+        let note = sections::split(path).0;
+        let modified = fs::metadata(&note).and_then(|m| m.modified()).ok()?;
+        let mtime = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        if let Some(item) = self.items.get(path)
+            && item.mtime == mtime
+        {
+            return Some(item.clone());
+        }
+
+        let text = fs::read_to_string(&note).ok()?;
+        let body = sections::read(path);
+        let frontmatter = frontmatter::parse(&text).0;
+        let item = Item {
+            mtime: if is_recent(modified) { 0 } else { mtime },
+            modified: DateTime::<Local>::from(modified).date_naive(),
+            words: scan::words(&body),
+            hash: format!("{:016x}", hash::fnv1a(body.as_bytes())),
+            overrides: frontmatter.overrides(),
+            id: frontmatter::note_id(&text),
+            tags: frontmatter.tags(),
+        };
+        self.items.insert(path.to_path_buf(), item.clone());
+        self.changed = true;
+
+        Some(item)
+    }
+
+    /// What splitting the note at `path` needs of it, cached if its mtime
+    /// says it is unchanged. `None` if it can't be read.
+    pub fn note(&mut self, path: &Path) -> Option<Note> {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        let mtime = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        if let Some(note) = self.notes.get(path)
+            && note.mtime == mtime
+        {
+            return Some(note.clone());
+        }
+
+        let text = fs::read_to_string(path).ok()?;
+        let body = frontmatter::parse(&text).1;
+        let note = Note {
+            mtime: if is_recent(modified) { 0 } else { mtime },
+            words: scan::words(body),
+            headings: [1, 2].map(|level| sections::titles(path, body, level)),
+        };
+        self.notes.insert(path.to_path_buf(), note.clone());
+        self.changed = true;
+
+        Some(note)
+    }
+
+    /// Drops the items under `dir` other than `items`, and the notes other
+    /// than theirs, those of removed notes.
+    pub fn retain_items(&mut self, dir: &Path, items: &[PathBuf]) {
+        let kept: HashSet<&PathBuf> = items.iter().collect();
+        let notes: HashSet<PathBuf> = items.iter().map(|p| sections::split(p).0).collect();
+        let before = self.items.len() + self.notes.len();
+        self.items
+            .retain(|p, _| !p.starts_with(dir) || kept.contains(p));
+        self.notes
+            .retain(|p, _| !p.starts_with(dir) || notes.contains(p));
+        self.changed |= self.items.len() + self.notes.len() != before;
+    }
+
+    /// The files under `dir`, with the listings of the dirs on the way and
+    /// whether they were read anew.
+    fn walk(&self, dir: &Path, ignore: &Ignore) -> (Vec<PathBuf>, Vec<(PathBuf, Listing, bool)>) {
+        let mut files = Vec::new();
+        let mut listings = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let Some((fresh, listing)) = self.list(&dir) else {
+                continue;
+            };
+            files.extend(listing.files.iter().map(|f| dir.join(f)));
+            pending.extend(
+                listing
+                    .dirs
+                    .iter()
+                    .rev()
+                    .map(|d| dir.join(d))
+                    .filter(|d| !ignore.is_ignored(d)),
+            );
+            listings.push((dir, listing.clone(), fresh));
+        }
+
+        (files, listings)
+    }
+
+    /// The listing of `dir`, cached if its mtime says it is unchanged, and
+    /// whether it was read anew.
+    fn list(&self, dir: &Path) -> Option<(bool, Listing)> {
+        let modified = fs::metadata(dir).and_then(|m| m.modified()).ok()?;
+        let mtime = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        if let Some(listing) = self.dirs.get(dir)
+            && listing.mtime == mtime
+        {
+            return Some((false, listing.clone()));
+        }
+
+        let mut listing = Listing {
+            mtime,
+            files: Vec::new(),
+            dirs: Vec::new(),
+        };
+        for entry in fs::read_dir(dir).ok()?.filter_map(Result::ok) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Ok(kind) = entry.file_type() else {
+                continue;
+            };
+            if kind.is_dir() {
+                listing.dirs.push(name);
+            } else if kind.is_file() || entry.path().is_file() {
+                // Links to files count as files, links to dirs aren't followed.
+                listing.files.push(name);
+            }
+        }
+        listing.files.sort();
+        listing.dirs.sort();
+
+        if is_recent(modified) {
+            listing.mtime = 0;
+        }
+
+        Some((true, listing))
+    }
+}
+
+/// Whether `modified` is too recent for its mtime to show a change made
+/// right after.
+fn is_recent(modified: SystemTime) -> bool {
+    SystemTime::now()
+        .duration_since(modified)
+        .is_ok_and(|age| age < Duration::from_secs(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeckConfig;
+
+    #[test]
+    fn walks_every_subdir_in_order_and_caches_items() {
+        let dir = std::env::temp_dir().join(format!("memo-index-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut expected = Vec::new();
+        for d in 0..20 {
+            let sub = dir.join(format!("d{:02}", d)).join("inner");
+            fs::create_dir_all(&sub).unwrap();
+            let file = sub.join("note.md");
+            fs::write(&file, "---\nmemo-priority: 2\n---\none two three\n").unwrap();
+            expected.push(file);
+        }
+        let ignore = Ignore::load(&dir, &[]);
+
+        let mut index = Index::default();
+        assert_eq!(index.files(&dir, &ignore), expected);
+
+        let item = index.item(&expected[0]).unwrap();
+        assert_eq!(item.words, 3);
+        assert_eq!(item.overrides.priority, 2.0);
+        assert_eq!(index.item(&expected[0]), Some(item));
+        assert_eq!(index.item(&dir.join("missing.md")), None);
+
+        index.retain_items(&dir, &expected[1..]);
+        assert!(index.items.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn caches_what_splitting_and_relinking_need() {
+        let dir = std::env::temp_dir().join(format!("memo-index-notes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let note = dir.join("net.md");
+        fs::write(
+            &note,
+            "---\nid: abc\ntags: [net]\n---\n# TCP\none two\n## SYN\nthree\n# UDP\nfour\n",
+        )
+        .unwrap();
+        let settings = DeckConfig {
+            split_headings: true,
+            ..DeckConfig::default()
+        };
+
+        let mut index = Index::default();
+        let items = scan::items(&dir, &Ignore::load(&dir, &[]), &settings, &mut index);
+        assert_eq!(
+            items,
+            [sections::path(&note, "TCP"), sections::path(&note, "UDP")]
+        );
+        let cached = index.note(&note).unwrap();
+        assert_eq!(cached.words, 10);
+        assert_eq!(cached.headings, [vec!["TCP", "UDP"], vec!["SYN"]]);
+
+        let item = index.item(&items[0]).unwrap();
+        assert_eq!(item.id.as_deref(), Some("abc"));
+        assert_eq!(item.tags, ["net"]);
+
+        index.retain_items(&dir, &items[1..]);
+        assert_eq!(index.notes.len(), 1);
+        index.retain_items(&dir, &[]);
+        assert!(index.notes.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+//! Leitner boxes: passing a review moves a file up a box, failing sends it
+//! back to the first one, each box having its own interval.
+
+use chrono::NaiveDate;
+
+use crate::review::{Grade, ReviewInfo};
+use crate::weight::Breakdown;
+
+/// Days between reviews of each box, the first box being box 1.
+pub fn default_intervals() -> Vec<u32> {
+    vec![1, 2, 4, 8, 16, 32]
+}
+
+/// Moves the file to its next box, boxes past the last interval share it.
+pub fn review(info: &mut ReviewInfo, grade: Grade) {
+    let current = info.leitner_box.unwrap_or(0);
+    info.leitner_box = Some(match grade {
+        Grade::Again => 1,
+        _ => current + 1,
+    });
+}
+
+/// Days between reviews of `leitner_box`, by the default intervals if
+/// `intervals` is empty.
+pub fn interval(leitner_box: u32, intervals: &[u32]) -> u32 {
+    let defaults;
+    let intervals = if intervals.is_empty() {
+        defaults = default_intervals();
+        &defaults
+    } else {
+        intervals
+    };
+
+    intervals[(leitner_box.max(1) as usize - 1).min(intervals.len() - 1)]
+}
+
+/// Weight of a file, growing with how far it is into (and past) its box's interval.
+pub fn breakdown(info: Option<&ReviewInfo>, today: NaiveDate, intervals: &[u32]) -> Breakdown {
+    let Some(d) = info.and_then(|i| i.last_reviewed) else {
+        return Breakdown::new_file();
+    };
+
+    let leitner_box = info.and_then(|i| i.leitner_box).unwrap_or(1);
+    let days_since_last = (today - d).num_days().max(0);
+    let overdue = days_since_last as f64 / interval(leitner_box, intervals).max(1) as f64;
+
+    Breakdown {
+        days_since: Some(days_since_last),
+        overdue: Some(overdue),
+        priority: overdue.min(2.0) * 50.0,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boxes_move_up_and_back() {
+        let mut info = ReviewInfo::default();
+        review(&mut info, Grade::Hard);
+        review(&mut info, Grade::Good);
+        review(&mut info, Grade::Easy);
+        assert_eq!(info.leitner_box, Some(3));
+        review(&mut info, Grade::Again);
+        assert_eq!(info.leitner_box, Some(1));
+    }
+
+    #[test]
+    fn intervals_of_boxes() {
+        let intervals = [1, 3, 7];
+        assert_eq!(interval(0, &intervals), 1);
+        assert_eq!(interval(1, &intervals), 1);
+        assert_eq!(interval(3, &intervals), 7);
+        assert_eq!(interval(9, &intervals), 7);
+        assert_eq!(interval(4, &[]), default_intervals()[3]);
+        assert_eq!(interval(99, &[]), 32);
+    }
+
+    #[test]
+    fn weight_grows_into_the_interval() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 11).unwrap();
+        let info = ReviewInfo {
+            last_reviewed: NaiveDate::from_ymd_opt(2025, 7, 9),
+            leitner_box: Some(3),
+            ..Default::default()
+        };
+
+        let b = breakdown(Some(&info), today, &[1, 2, 4]);
+        assert_eq!(b.overdue, Some(0.5));
+        assert_eq!(b.priority, 25.0);
+        assert_eq!(breakdown(Some(&info), today, &[0]).overdue, Some(2.0));
+    }
+}
@@ -0,0 +1,56 @@
+//! Scheduling core of memo: review data, weights, persistence and selection.
+
+pub mod anki;
+pub mod backtest;
+pub mod balance;
+pub mod cards;
+pub mod chart;
+pub mod config;
+pub mod daily;
+pub mod deck;
+pub mod doctor;
+pub mod email;
+pub mod error;
+pub mod expr;
+pub mod focus;
+pub mod forecast;
+pub mod frontmatter;
+pub mod fsrs;
+pub mod git;
+pub mod hash;
+pub mod history;
+pub mod hooks;
+pub mod ics;
+pub mod ignore;
+pub mod index;
+pub mod leitner;
+pub mod lock;
+pub mod merge;
+pub mod msgpack;
+pub mod optimize;
+pub mod pause;
+pub mod plugin;
+pub mod png;
+pub mod remote;
+pub mod review;
+pub mod scan;
+pub mod scheduler;
+pub mod schema;
+pub mod sections;
+pub mod select;
+pub mod sm2;
+pub mod sqlite;
+pub mod sr;
+pub mod stats;
+pub mod store;
+pub mod streak;
+pub mod toml;
+pub mod undo;
+pub mod webhook;
+pub mod weight;
+pub mod zip;
+
+pub use config::Config;
+pub use deck::Deck;
+pub use error::Error;
+pub use review::{Grade, ReviewInfo};
@@ -0,0 +1,205 @@
+//! Lock file keeping two memo processes from saving over each other.
+//!
+//! `memo.lock` in the revs dir holds the PID of its owner and when it was
+//! taken. It is stale, and taken over, once that process is gone or the
+//! lock is older than a day, or if it doesn't say who owns it and hasn't
+//! changed for a few seconds, its owner killed before writing it.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeDelta};
+
+use crate::config::Config;
+use crate::error::Error;
+
+/// How long a lock file that doesn't parse is taken to be still being
+/// written by its owner.
+const UNREADABLE_STALE: Duration = Duration::from_secs(5);
+
+/// A lock held until dropped.
+#[derive(Debug)]
+pub struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    pub fn path(revs_dir: &Path) -> PathBuf {
+        revs_dir.join("memo.lock")
+    }
+
+    /// Takes the lock of the revs dir of `config` to save there, making the
+    /// dirs saved in first, see [`Config::create_dirs`].
+    pub fn for_saving(config: &Config, wait: bool) -> Result<Self, Error> {
+        config.create_dirs().map_err(Error::Storage)?;
+
+        Self::acquire(&config.revs_dir(), wait)
+    }
+
+    /// Takes the lock of `revs_dir`, waiting for its owner to let go if
+    /// `wait` and failing with [`Error::Locked`] otherwise.
+    pub fn acquire(revs_dir: &Path, wait: bool) -> Result<Self, Error> {
+        let path = Self::path(revs_dir);
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    writeln!(
+                        file,
+                        "{}\n{}",
+                        std::process::id(),
+                        Local::now().to_rfc3339()
+                    )
+                    .map_err(Error::Storage)?;
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                // No revs dir yet, nothing to guard.
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self { path }),
+                Err(e) => return Err(Error::Storage(e)),
+            }
+
+            let Some(owner) = Owner::read(&path) else {
+                // Being written by its owner, removed in the meantime, or
+                // left unwritten by an owner killed right after creating it.
+                match age(&path) {
+                    Some(elapsed) if elapsed > UNREADABLE_STALE => take_over(&path, |moved| {
+                        Owner::read(moved).is_none()
+                            && age(moved).is_some_and(|a| a > UNREADABLE_STALE)
+                    }),
+                    Some(_) if !wait => return Err(Error::Locked(path, None)),
+                    _ => thread::sleep(Duration::from_millis(50)),
+                }
+                continue;
+            };
+            if owner.is_stale() {
+                take_over(&path, |moved| {
+                    Owner::read(moved).is_some_and(|o| o == owner)
+                });
+                continue;
+            }
+            if !wait {
+                return Err(Error::Locked(path, Some(owner.pid)));
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        if Owner::read(&self.path).is_some_and(|o| o.pid == std::process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Moves the stale lock file `path` out of the way for the caller to take
+/// the lock, if it still is the one judged stale by `stale`.
+///
+/// Removing it instead could remove the lock of another memo that took it
+/// over since it was judged; a rename moves the file atomically, so only
+/// the one file judged is moved, and only by one of the memos taking it
+/// over at once.
+fn take_over(path: &Path, stale: impl Fn(&Path) -> bool) {
+    let moved = path.with_extension(format!("lock.stale-{}", std::process::id()));
+    // Taken over by another memo first.
+    if fs::rename(path, &moved).is_err() {
+        return;
+    }
+    if !stale(&moved) {
+        // Of a memo that took it over in the meantime, so put it back,
+        // unless yet another took its place.
+        let _ = fs::hard_link(&moved, path);
+    }
+    let _ = fs::remove_file(&moved);
+}
+
+/// How long ago `path` was last written.
+fn age(path: &Path) -> Option<Duration> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+}
+
+#[derive(PartialEq)]
+struct Owner {
+    pid: u32,
+    since: DateTime<Local>,
+}
+
+impl Owner {
+    fn read(path: &Path) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        let mut lines = text.lines();
+
+        Some(Self {
+            pid: lines.next()?.trim().parse().ok()?,
+            since: DateTime::parse_from_rfc3339(lines.next()?.trim())
+                .ok()?
+                .with_timezone(&Local),
+        })
+    }
+
+    fn is_stale(&self) -> bool {
+        Local::now() - self.since > TimeDelta::days(1) || alive(self.pid) == Some(false)
+    }
+}
+
+/// Whether the process `pid` runs, if that can be told.
+fn alive(pid: u32) -> Option<bool> {
+    if cfg!(target_os = "linux") {
+        return Some(Path::new("/proc").join(pid.to_string()).exists());
+    }
+    if cfg!(unix) {
+        return std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .ok()
+            .map(|s| s.success());
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_locks_are_taken_over_once() {
+        let dir = std::env::temp_dir().join(format!("memo-lock-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = Lock::path(&dir);
+        // Of a process long gone.
+        let stale = format!("{}\n{}\n", u32::MAX, Local::now().to_rfc3339());
+        fs::write(&path, &stale).unwrap();
+
+        // Judged stale before another memo took the lock: left as is.
+        let owner = Owner::read(&path).unwrap();
+        fs::write(&path, "1\n2025-07-01T00:00:00+00:00\n").unwrap();
+        take_over(&path, |moved| {
+            Owner::read(moved).is_some_and(|o| o == owner)
+        });
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "1\n2025-07-01T00:00:00+00:00\n"
+        );
+
+        fs::write(&path, &stale).unwrap();
+        let lock = Lock::acquire(&dir, false).unwrap();
+        let owner = Owner::read(&path).unwrap();
+        assert_eq!(owner.pid, std::process::id());
+        assert!(matches!(Lock::acquire(&dir, false), Err(Error::Locked(..))));
+        drop(lock);
+        assert!(!path.exists());
+
+        let left: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        assert!(left.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
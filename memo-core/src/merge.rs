@@ -0,0 +1,174 @@
+//! Merging of review data edited apart, e.g. on two machines.
+//!
+//! Of an entry on both sides the one reviewed last wins, its review count
+//! that of either side plus the reviews only the other side's log has.
+
+use crate::history::Event;
+use crate::review::{ReviewData, ReviewInfo};
+
+/// How many entries a merge changed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Merged {
+    /// Entries of theirs only.
+    pub added: usize,
+    /// Entries reviewed later on their side.
+    pub taken: usize,
+    /// Entries of ours given their reviews, counts included.
+    pub counted: usize,
+}
+
+/// The events of both logs, in order of date. Events appearing on both
+/// sides, as those from before the two went apart do, are kept once.
+pub fn events(ours: &[Event], theirs: &[Event]) -> Vec<Event> {
+    let mut merged = ours.to_vec();
+    merged.extend(only(theirs, ours).into_iter().cloned());
+    merged.sort_by_key(|e| e.date);
+
+    merged
+}
+
+/// The events of `log` that `other` doesn't have, each event of `other`
+/// matching one of `log` at most.
+pub fn only<'a>(log: &'a [Event], other: &[Event]) -> Vec<&'a Event> {
+    let mut unmatched: Vec<&Event> = other.iter().collect();
+    log.iter()
+        .filter(|event| match unmatched.iter().position(|e| e == event) {
+            Some(i) => {
+                unmatched.swap_remove(i);
+                false
+            }
+            None => true,
+        })
+        .collect()
+}
+
+/// Merges `theirs` into `ours`, adding to the review count of either side
+/// the reviews of a file only the log of the other has, `our_events` and
+/// `their_events`. Counts not logged, like those of reviews made before
+/// logging or imported, are kept so.
+pub fn merge(
+    ours: &mut ReviewData,
+    theirs: Vec<ReviewInfo>,
+    our_events: &[Event],
+    their_events: &[Event],
+) -> Merged {
+    let our_only = only(our_events, their_events);
+    let their_only = only(their_events, our_events);
+    let logged = |events: &[&Event], file_name: &str| {
+        events.iter().filter(|e| e.file_name == file_name).count() as u32
+    };
+
+    let mut merged = Merged::default();
+    for info in theirs {
+        let Some(mine) = ours.get_mut(&info.file_name) else {
+            merged.added += 1;
+            ours.insert(info.file_name.clone(), info);
+            continue;
+        };
+
+        let count = (mine.review_count + logged(&their_only, &info.file_name))
+            .max(info.review_count + logged(&our_only, &info.file_name));
+        if info.last_reviewed > mine.last_reviewed {
+            *mine = info;
+            merged.taken += 1;
+        } else if count != mine.review_count {
+            merged.counted += 1;
+        }
+        mine.review_count = count;
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::review::Grade;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 7, d).unwrap()
+    }
+
+    fn event(file_name: &str, d: u32, id: &str) -> Event {
+        Event {
+            date: day(d),
+            deck: "408".to_string(),
+            file_name: file_name.to_string(),
+            grade: Grade::Good,
+            duration: None,
+            id: Some(id.to_string()),
+        }
+    }
+
+    fn info(file_name: &str, d: u32, review_count: u32) -> ReviewInfo {
+        ReviewInfo {
+            file_name: file_name.to_string(),
+            last_reviewed: Some(day(d)),
+            review_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn events_are_kept_once() {
+        let shared = event("a.md", 1, "1");
+        let ours = [shared.clone(), event("a.md", 3, "2")];
+        let theirs = [event("b.md", 2, "3"), shared.clone()];
+
+        let merged = events(&ours, &theirs);
+        let ids: Vec<_> = merged.iter().map(|e| e.id.as_deref().unwrap()).collect();
+        assert_eq!(ids, ["1", "3", "2"]);
+        assert_eq!(only(&theirs, &ours), [&theirs[0]]);
+    }
+
+    #[test]
+    fn alike_events_without_ids_are_matched_one_each() {
+        let mut plain = event("a.md", 1, "");
+        plain.id = None;
+        let ours = [plain.clone()];
+        let theirs = [plain.clone(), plain.clone()];
+
+        assert_eq!(only(&theirs, &ours).len(), 1);
+        assert_eq!(events(&ours, &theirs).len(), 2);
+    }
+
+    #[test]
+    fn merge_counts_the_reviews_of_both_sides() {
+        // Both had a.md reviewed twice, then one more review each apart.
+        let base = [event("a.md", 1, "1"), event("a.md", 2, "2")];
+        let our_events = [base.as_slice(), &[event("a.md", 3, "3")]].concat();
+        let their_events = [base.as_slice(), &[event("a.md", 4, "4")]].concat();
+
+        let mut ours: ReviewData = [("a.md".to_string(), info("a.md", 3, 3))].into();
+        let theirs = vec![info("a.md", 4, 3), info("b.md", 4, 1)];
+
+        let merged = merge(&mut ours, theirs, &our_events, &their_events);
+        assert_eq!(
+            merged,
+            Merged {
+                added: 1,
+                taken: 1,
+                counted: 0,
+            }
+        );
+        assert_eq!(ours["a.md"].review_count, 4);
+        assert_eq!(ours["a.md"].last_reviewed, Some(day(4)));
+        assert_eq!(ours["b.md"].review_count, 1);
+    }
+
+    #[test]
+    fn merge_keeps_counts_not_logged() {
+        let mut ours: ReviewData = [("a.md".to_string(), info("a.md", 5, 10))].into();
+        let merged = merge(&mut ours, vec![info("a.md", 2, 4)], &[], &[]);
+
+        assert_eq!(merged, Merged::default());
+        assert_eq!(ours["a.md"].review_count, 10);
+
+        let their_events = [event("a.md", 6, "1")];
+        let merged = merge(&mut ours, vec![info("a.md", 5, 10)], &[], &their_events);
+        assert_eq!(merged.counted, 1);
+        assert_eq!(ours["a.md"].review_count, 11);
+    }
+}
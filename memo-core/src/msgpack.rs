@@ -0,0 +1,380 @@
+//! MessagePack, a binary JSON: smaller and faster to read than the text of
+//! a large deck's revs file. Only what JSON values need is written, and read.
+
+use serde_json::{Map, Number, Value};
+
+/// Nesting deeper than this is refused rather than overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write(&mut out, value);
+
+    out
+}
+
+fn write(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                write_uint(out, u);
+            } else if let Some(i) = n.as_i64() {
+                write_int(out, i);
+            } else {
+                out.push(0xcb);
+                out.extend_from_slice(&n.as_f64().unwrap_or_default().to_be_bytes());
+            }
+        }
+        Value::String(s) => {
+            let len = s.len();
+            match len {
+                0..32 => out.push(0xa0 | len as u8),
+                32..0x100 => out.extend_from_slice(&[0xd9, len as u8]),
+                0x100..0x10000 => {
+                    out.push(0xda);
+                    out.extend_from_slice(&(len as u16).to_be_bytes());
+                }
+                _ => {
+                    out.push(0xdb);
+                    out.extend_from_slice(&(len as u32).to_be_bytes());
+                }
+            }
+            out.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(items) => {
+            write_len(out, items.len(), 0x90, 0xdc);
+            for item in items {
+                write(out, item);
+            }
+        }
+        Value::Object(map) => {
+            write_len(out, map.len(), 0x80, 0xde);
+            for (key, value) in map {
+                write(out, &Value::String(key.clone()));
+                write(out, value);
+            }
+        }
+    }
+}
+
+fn write_uint(out: &mut Vec<u8>, u: u64) {
+    match u {
+        0..0x80 => out.push(u as u8),
+        0x80..0x100 => out.extend_from_slice(&[0xcc, u as u8]),
+        0x100..0x10000 => {
+            out.push(0xcd);
+            out.extend_from_slice(&(u as u16).to_be_bytes());
+        }
+        0x10000..0x1_0000_0000 => {
+            out.push(0xce);
+            out.extend_from_slice(&(u as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(0xcf);
+            out.extend_from_slice(&u.to_be_bytes());
+        }
+    }
+}
+
+/// A negative `i`, the rest are written by [`write_uint`].
+fn write_int(out: &mut Vec<u8>, i: i64) {
+    if i >= -32 {
+        out.push(i as i8 as u8);
+    } else if i >= i8::MIN as i64 {
+        out.extend_from_slice(&[0xd0, i as i8 as u8]);
+    } else if i >= i16::MIN as i64 {
+        out.push(0xd1);
+        out.extend_from_slice(&(i as i16).to_be_bytes());
+    } else if i >= i32::MIN as i64 {
+        out.push(0xd2);
+        out.extend_from_slice(&(i as i32).to_be_bytes());
+    } else {
+        out.push(0xd3);
+        out.extend_from_slice(&i.to_be_bytes());
+    }
+}
+
+/// The length of an array or map, `fix` the marker of short ones and `wide`
+/// that of 16-bit lengths, the 32-bit one following it.
+fn write_len(out: &mut Vec<u8>, len: usize, fix: u8, wide: u8) {
+    if len < 16 {
+        out.push(fix | len as u8);
+    } else if len < 0x10000 {
+        out.push(wide);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(wide + 1);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+/// The value `data` holds, which must be all of it.
+pub fn decode(data: &[u8]) -> Result<Value, String> {
+    let mut reader = Reader { data, at: 0 };
+    let value = reader.value(0)?;
+    if reader.at != data.len() {
+        return Err(format!("trailing bytes at offset {}", reader.at));
+    }
+
+    Ok(value)
+}
+
+/// The items of the array under `key` of the map `data`, or of `data` if an
+/// array itself, up to the first that doesn't decode, as of data cut short.
+pub fn whole_items(data: &[u8], key: &str) -> Vec<Value> {
+    let mut reader = Reader { data, at: 0 };
+    let mut items = Vec::new();
+    let _ = reader.items_of(key, &mut items);
+
+    items
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.at..self.at.saturating_add(n))
+            .ok_or_else(|| format!("unexpected end of data at offset {}", self.at))?;
+        self.at += n;
+
+        Ok(bytes)
+    }
+
+    fn uint(&mut self, n: usize) -> Result<u64, String> {
+        Ok(self
+            .take(n)?
+            .iter()
+            .fold(0, |acc, b| acc << 8 | u64::from(*b)))
+    }
+
+    /// A signed integer of `n` bytes.
+    fn int(&mut self, n: usize) -> Result<i64, String> {
+        let shift = 64 - 8 * n as u32;
+        Ok(((self.uint(n)? << shift) as i64) >> shift)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("nested too deep".to_string());
+        }
+        let marker = self.take(1)?[0];
+        let value = match marker {
+            0x00..=0x7f => Value::from(marker),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize, depth)?,
+            0x90..=0x9f => self.array((marker & 0x0f) as usize, depth)?,
+            0xa0..=0xbf => self.string((marker & 0x1f) as usize)?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xca => {
+                let bits = self.uint(4)? as u32;
+                number(f32::from_bits(bits) as f64)
+            }
+            0xcb => number(f64::from_bits(self.uint(8)?)),
+            0xcc => Value::from(self.uint(1)?),
+            0xcd => Value::from(self.uint(2)?),
+            0xce => Value::from(self.uint(4)?),
+            0xcf => Value::from(self.uint(8)?),
+            0xd0 => Value::from(self.int(1)?),
+            0xd1 => Value::from(self.int(2)?),
+            0xd2 => Value::from(self.int(4)?),
+            0xd3 => Value::from(self.int(8)?),
+            0xd9 => {
+                let len = self.uint(1)? as usize;
+                self.string(len)?
+            }
+            0xda => {
+                let len = self.uint(2)? as usize;
+                self.string(len)?
+            }
+            0xdb => {
+                let len = self.uint(4)? as usize;
+                self.string(len)?
+            }
+            0xdc => {
+                let len = self.uint(2)? as usize;
+                self.array(len, depth)?
+            }
+            0xdd => {
+                let len = self.uint(4)? as usize;
+                self.array(len, depth)?
+            }
+            0xde => {
+                let len = self.uint(2)? as usize;
+                self.map(len, depth)?
+            }
+            0xdf => {
+                let len = self.uint(4)? as usize;
+                self.map(len, depth)?
+            }
+            0xe0..=0xff => Value::from(marker as i8),
+            _ => {
+                return Err(format!(
+                    "unsupported type 0x{:02x} at offset {}",
+                    marker,
+                    self.at - 1
+                ));
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// The length of the array, or map, whose marker is next, `None` with
+    /// nothing read if something else is.
+    fn header(&mut self, array: bool) -> Result<Option<usize>, String> {
+        let (fix, wide) = if array { (0x90, 0xdc) } else { (0x80, 0xde) };
+        let len = match self.data.get(self.at) {
+            Some(m) if m & 0xf0 == fix => {
+                self.at += 1;
+                (m & 0x0f) as usize
+            }
+            Some(m) if *m == wide => {
+                self.at += 1;
+                self.uint(2)? as usize
+            }
+            Some(m) if *m == wide + 1 => {
+                self.at += 1;
+                self.uint(4)? as usize
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(len))
+    }
+
+    /// See [`whole_items`].
+    fn items_of(&mut self, key: &str, items: &mut Vec<Value>) -> Result<(), String> {
+        if let Some(len) = self.header(false)? {
+            let mut found = false;
+            for _ in 0..len {
+                if self.value(1)? == key {
+                    found = true;
+                    break;
+                }
+                self.value(1)?;
+            }
+            if !found {
+                return Ok(());
+            }
+        }
+        let Some(len) = self.header(true)? else {
+            return Ok(());
+        };
+        for _ in 0..len {
+            items.push(self.value(1)?);
+        }
+
+        Ok(())
+    }
+
+    fn string(&mut self, len: usize) -> Result<Value, String> {
+        let at = self.at;
+        let bytes = self.take(len)?;
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(Value::String(s.to_string())),
+            Err(_) => Err(format!("invalid UTF-8 at offset {}", at)),
+        }
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<Value, String> {
+        // Every item is a byte at least, which bounds what is reserved.
+        let mut items = Vec::with_capacity(len.min(self.data.len() - self.at));
+        for _ in 0..len {
+            items.push(self.value(depth + 1)?);
+        }
+
+        Ok(Value::Array(items))
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<Value, String> {
+        let mut map = Map::new();
+        for _ in 0..len {
+            let at = self.at;
+            let Value::String(key) = self.value(depth + 1)? else {
+                return Err(format!("map key not a string at offset {}", at));
+            };
+            map.insert(key, self.value(depth + 1)?);
+        }
+
+        Ok(Value::Object(map))
+    }
+}
+
+/// `f` as a JSON number, `null` if it has none like NaN.
+fn number(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let long = "x".repeat(300);
+        let many: Vec<Value> = (0..20).map(Value::from).collect();
+        let value = json!({
+            "null": null,
+            "bools": [true, false],
+            "ints": [0, 127, 128, 255, 256, 65535, 65536, u32::MAX, u64::MAX],
+            "negative": [-1, -32, -33, -128, -129, -32768, -32769, i32::MIN, i64::MIN],
+            "floats": [0.5, -2.25, 1e300],
+            "strings": ["", "short", "x".repeat(40), long, "ünïcødé"],
+            "many": many,
+            "nested": { "a": { "b": [[], {}] } },
+        });
+
+        assert_eq!(decode(&encode(&value)).unwrap(), value);
+    }
+
+    #[test]
+    fn compact_markers() {
+        assert_eq!(encode(&json!(5)), [0x05]);
+        assert_eq!(encode(&json!(-1)), [0xff]);
+        assert_eq!(encode(&json!("ab")), [0xa2, b'a', b'b']);
+        assert_eq!(encode(&json!([null, true])), [0x92, 0xc0, 0xc3]);
+        assert_eq!(encode(&json!({ "a": 1 })), [0x81, 0xa1, b'a', 0x01]);
+    }
+
+    #[test]
+    fn decodes_wider_markers_than_written() {
+        assert_eq!(decode(&[0xd0, 0x05]).unwrap(), json!(5));
+        assert_eq!(decode(&[0xca, 0x3f, 0xc0, 0x00, 0x00]).unwrap(), json!(1.5));
+        assert_eq!(decode(&[0xdd, 0, 0, 0, 1, 0x01]).unwrap(), json!([1]));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(decode(&[]).is_err());
+        assert!(decode(&[0x92, 0x01]).is_err());
+        assert!(decode(&[0x01, 0x02]).is_err());
+        assert!(decode(&[0xc1]).is_err());
+        assert!(decode(&[0xa1, 0xff]).is_err());
+        assert!(decode(&[0x81, 0x01, 0x01]).is_err());
+        assert!(decode(&[0xdd, 0xff, 0xff, 0xff, 0xff]).is_err());
+
+        let deep = [vec![0x91; MAX_DEPTH + 2], vec![0xc0]].concat();
+        assert_eq!(decode(&deep).unwrap_err(), "nested too deep");
+    }
+
+    #[test]
+    fn whole_items_of_data_cut_short() {
+        let data = encode(&json!({ "a": 0, "reviews": [1, "two", [3]] }));
+        let items = whole_items(&data[..data.len() - 1], "reviews");
+        assert_eq!(items, [json!(1), json!("two")]);
+
+        let data = encode(&json!([1, 2, 3]));
+        assert_eq!(whole_items(&data[..3], "reviews"), [json!(1), json!(2)]);
+        assert!(whole_items(&encode(&json!({ "other": [1] })), "reviews").is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+//! Fitting the decay rate of a deck to how well its files were recalled.
+
+use std::collections::HashMap;
+
+use crate::history::Event;
+use crate::review::Grade;
+
+/// Fewest reviews a fit is made from.
+pub const MIN_SAMPLES: usize = 10;
+
+/// A review following an earlier one of the same file: the days between
+/// them and whether the file was recalled, i.e. not graded `again`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub elapsed: i64,
+    pub recalled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub decay_rate: f64,
+    pub samples: usize,
+    /// Share of the samples recalled.
+    pub recall: f64,
+    /// Mean log-likelihood of the samples under `decay_rate`, higher is better.
+    pub log_likelihood: f64,
+}
+
+/// Every review of `events` that follows an earlier one of the same file.
+pub fn samples(events: &[Event]) -> Vec<Sample> {
+    let mut events: Vec<&Event> = events.iter().collect();
+    events.sort_by_key(|e| e.date);
+
+    let mut last = HashMap::new();
+    let mut samples = Vec::new();
+    for event in events {
+        if let Some(previous) = last.insert(&event.file_name, event.date) {
+            samples.push(Sample {
+                elapsed: (event.date - previous).num_days().max(0),
+                recalled: event.grade != Grade::Again,
+            });
+        }
+    }
+
+    samples
+}
+
+/// Mean log-likelihood of `samples` if retention after `t` days is `rate^t`.
+pub fn log_likelihood(samples: &[Sample], rate: f64) -> f64 {
+    let total: f64 = samples
+        .iter()
+        .map(|s| {
+            let p = rate.powi(s.elapsed as i32).clamp(1e-6, 1.0 - 1e-6);
+            if s.recalled { p.ln() } else { (1.0 - p).ln() }
+        })
+        .sum();
+
+    total / samples.len().max(1) as f64
+}
+
+/// The decay rate, in steps of 0.001 between 0.5 and 0.999, that best
+/// explains `samples`. `None` if there are fewer than [`MIN_SAMPLES`].
+pub fn fit_decay(samples: &[Sample]) -> Option<Fit> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    let (decay_rate, log_likelihood) = (500..1000)
+        .map(|r| {
+            let rate = r as f64 / 1000.0;
+            (rate, log_likelihood(samples, rate))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    Some(Fit {
+        decay_rate,
+        samples: samples.len(),
+        recall: samples.iter().filter(|s| s.recalled).count() as f64 / samples.len() as f64,
+        log_likelihood,
+    })
+}
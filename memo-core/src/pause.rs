@@ -0,0 +1,70 @@
+//! Breaks from reviewing, whose length is added to the schedules of every
+//! deck once they are over, kept in `pauses.json` in the revs dir.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::review::ReviewData;
+use crate::store;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Pause {
+    pub from: NaiveDate,
+    /// Last day of the pause.
+    pub to: NaiveDate,
+
+    /// Decks whose schedules were moved already.
+    #[serde(default)]
+    pub applied: Vec<String>,
+}
+
+impl Pause {
+    pub fn days(&self) -> u64 {
+        (self.to - self.from).num_days().max(0) as u64 + 1
+    }
+
+    /// Moves the schedule of every file last reviewed before the pause
+    /// later by its length, returning how many were moved.
+    pub fn shift(&self, review_data: &mut ReviewData) -> usize {
+        let days = Days::new(self.days());
+        let mut moved = 0;
+        for info in review_data.values_mut() {
+            let Some(last) = info.last_reviewed.filter(|d| *d < self.from) else {
+                continue;
+            };
+            info.last_reviewed = Some(last + days);
+            info.due = info.due.map(|d| d + days);
+            moved += 1;
+        }
+
+        moved
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Pauses {
+    pub pauses: Vec<Pause>,
+}
+
+impl Pauses {
+    /// `pauses.json` in the revs dir.
+    pub fn path(revs_dir: &Path) -> PathBuf {
+        revs_dir.join("pauses.json")
+    }
+
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        store::write_atomic(path, json.as_bytes(), 0)
+    }
+}
@@ -0,0 +1,131 @@
+//! Plugins: executables in the `plugins` dir next to the config file, in a
+//! subdir by kind, run in name order and handed the deck as JSON on stdin.
+//!
+//! - `filter/`: print the names of the files to draw from, one a line.
+//! - `weight/`: print `<factor> <name>` lines, scaling the weight of the
+//!   files named.
+//! - `sink/`: handed the files drawn, free to do with them as they like.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::hooks;
+
+/// What a plugin does, by the subdir it is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Filter,
+    Weight,
+    Sink,
+}
+
+impl Kind {
+    pub const ALL: [Self; 3] = [Self::Filter, Self::Weight, Self::Sink];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Filter => "filter",
+            Self::Weight => "weight",
+            Self::Sink => "sink",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugin {
+    pub name: String,
+    pub kind: Kind,
+    pub path: PathBuf,
+}
+
+/// `plugins` in the dir of the config file.
+pub fn dir() -> Option<PathBuf> {
+    Some(Config::path()?.parent()?.join("plugins"))
+}
+
+/// Every plugin in `dir`, by kind and then name. Hidden files are left out,
+/// and on Unix those not executable.
+pub fn load(dir: &Path) -> Vec<Plugin> {
+    let mut plugins = Vec::new();
+    for kind in Kind::ALL {
+        let Ok(entries) = fs::read_dir(dir.join(kind.name())) else {
+            continue;
+        };
+        let mut found: Vec<Plugin> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| is_executable(p))
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().to_string();
+                (!name.starts_with('.')).then_some(Plugin { name, kind, path })
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        plugins.extend(found);
+    }
+
+    plugins
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+impl Plugin {
+    /// Names of the files the filter keeps of those in `payload`.
+    pub fn filter(&self, payload: &Value) -> io::Result<Vec<String>> {
+        let output = self.call(payload, true)?;
+
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Factors the weight plugin scales files by, keyed by file name.
+    pub fn weigh(&self, payload: &Value) -> io::Result<HashMap<String, f64>> {
+        let output = self.call(payload, true)?;
+        let mut factors = HashMap::new();
+        for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let parsed = line.split_once(char::is_whitespace).and_then(|(f, name)| {
+                let factor: f64 = f.parse().ok()?;
+                (factor.is_finite() && factor >= 0.0).then(|| (name.trim().to_string(), factor))
+            });
+            let Some((name, factor)) = parsed else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("expected `<factor> <name>`, got `{}`", line),
+                ));
+            };
+            factors.insert(name, factor);
+        }
+
+        Ok(factors)
+    }
+
+    /// Hands the files drawn to the sink, its output going to memo's.
+    pub fn sink(&self, payload: &Value) -> io::Result<()> {
+        self.call(payload, false).map(|_| ())
+    }
+
+    /// Runs the plugin with `payload` on stdin, returning its stdout if
+    /// `capture`, failing if it does with what it wrote to stderr.
+    fn call(&self, payload: &Value, capture: bool) -> io::Result<String> {
+        hooks::feed(&mut Command::new(&self.path), payload, capture)
+    }
+}
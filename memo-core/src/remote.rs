@@ -0,0 +1,193 @@
+//! Remote backend keeping each revs file on a WebDAV server or in an
+//! S3-compatible bucket, driving the `curl` command.
+//!
+//! The JSON file in the revs dir is a cache of the remote one. It is read
+//! when the remote can't be reached, and a save that can't be uploaded
+//! leaves a `<key>.json.unsent` marker so that the upload is retried, before
+//! anything is downloaded over it, by the next run. Both are warned of on
+//! stderr.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::{Backend, Config};
+use crate::review::{ReviewData, ReviewInfo};
+use crate::schema;
+use crate::store::{self, Json, Storage};
+
+/// Where curl writes the responses to uploads, which are of no use.
+const NULL_DEVICE: &str = if cfg!(windows) { "NUL" } else { "/dev/null" };
+
+pub struct Remote {
+    /// URL of the file, `<url>/<key>.json`.
+    pub url: String,
+    pub backend: Backend,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// Region signed for by the S3 backend.
+    pub region: String,
+    pub cache: Json,
+}
+
+impl Remote {
+    pub fn new(config: &Config, key: &str) -> Self {
+        let storage = &config.storage;
+        let base = storage
+            .url
+            .as_deref()
+            .unwrap_or_default()
+            .trim_end_matches('/');
+
+        Self {
+            url: format!("{}/{}.json", base, key),
+            backend: storage.backend,
+            user: storage.user.clone(),
+            password: storage.password.clone(),
+            region: storage
+                .region
+                .clone()
+                .unwrap_or_else(|| "us-east-1".to_string()),
+            cache: Json::new(config, key),
+        }
+    }
+
+    fn unsent(&self) -> PathBuf {
+        store::with_suffix(&self.cache.path, "unsent")
+    }
+
+    /// Downloads the file to `to`, returning `false` if there is none. Nothing
+    /// is left at `to` but a downloaded file.
+    fn download(&self, to: &Path) -> io::Result<bool> {
+        let downloaded = match self.curl(&["-o".as_ref(), to.as_os_str()]) {
+            Ok(200..=299) => return Ok(true),
+            Ok(404) => Ok(false),
+            Ok(status) => Err(io::Error::other(format!("{}: HTTP {}", self.url, status))),
+            Err(e) => Err(e),
+        };
+        // The error page of the server, or what came before curl failed.
+        let _ = fs::remove_file(to);
+
+        downloaded
+    }
+
+    fn upload(&self, from: &Path) -> io::Result<()> {
+        let status = self.curl(&[
+            "-o".as_ref(),
+            NULL_DEVICE.as_ref(),
+            "-T".as_ref(),
+            from.as_os_str(),
+        ])?;
+        if !(200..=299).contains(&status) {
+            return Err(io::Error::other(format!("{}: HTTP {}", self.url, status)));
+        }
+
+        Ok(())
+    }
+
+    /// Runs curl on the file with `args`, returning the HTTP status.
+    /// Credentials go through stdin, out of sight of other users.
+    fn curl(&self, args: &[&std::ffi::OsStr]) -> io::Result<u16> {
+        let mut command = Command::new("curl");
+        command
+            .args(["-sS", "-K", "-", "-w", "%{http_code}"])
+            .args(args)
+            .arg(&self.url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if self.backend == Backend::S3 {
+            command
+                .arg("--aws-sigv4")
+                .arg(format!("aws:amz:{}:s3", self.region));
+        } else {
+            command.arg("--anyauth");
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("cannot run curl: {}", e)))?;
+
+        if let Some(mut stdin) = child.stdin.take()
+            && let Some(user) = &self.user
+        {
+            let credentials = format!("{}:{}", user, self.password.as_deref().unwrap_or_default());
+            writeln!(
+                stdin,
+                "user = \"{}\"",
+                credentials.replace('\\', "\\\\").replace('"', "\\\"")
+            )?;
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::other(format!(
+                "cannot reach {}: {}",
+                self.url,
+                stderr.trim().trim_start_matches("curl: ")
+            )));
+        }
+
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .map_err(|_| io::Error::other(format!("{}: no HTTP status", self.url)))
+    }
+}
+
+impl Storage for Remote {
+    /// The remote file, the cache if it can't be reached or holds changes
+    /// not uploaded yet.
+    fn load(&self) -> io::Result<Vec<ReviewInfo>> {
+        if self.unsent().exists() {
+            match self.upload(&self.cache.path) {
+                Ok(()) => fs::remove_file(self.unsent())?,
+                Err(e) => eprintln!(
+                    "memo: {}; using {}, whose changes are not uploaded yet",
+                    e,
+                    self.cache.location()
+                ),
+            }
+            return self.cache.load();
+        }
+
+        let download = store::with_suffix(&self.cache.path, "download");
+        match self.download(&download) {
+            Ok(true) => {
+                let data = fs::read(&download)?;
+                fs::remove_file(&download)?;
+                schema::parse(&data, &self.url)?;
+                store::write_atomic(&self.cache.path, &data, self.cache.backups)?;
+                self.cache.load()
+            }
+            // Nothing there yet, the cache is uploaded by the next save.
+            Ok(false) => self.cache.load(),
+            Err(e) if self.cache.path.exists() => {
+                eprintln!("memo: {}; using the copy at {}", e, self.cache.location());
+                self.cache.load()
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, data: &ReviewData) -> io::Result<()> {
+        self.cache.save(data)?;
+        if let Err(e) = self.upload(&self.cache.path) {
+            eprintln!(
+                "memo: {}; saved to {}, uploaded by the next run",
+                e,
+                self.cache.location()
+            );
+            fs::write(self.unsent(), "")?;
+        } else if self.unsent().exists() {
+            fs::remove_file(self.unsent())?;
+        }
+
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        self.url.clone()
+    }
+}
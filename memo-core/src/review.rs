@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::fsrs::FsrsState;
+use crate::sm2::Sm2State;
+
+/// Review data of a deck keyed by file stem.
+pub type ReviewData = HashMap<String, ReviewInfo>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ReviewInfo {
+    pub file_name: String,
+    pub last_reviewed: Option<NaiveDate>,
+    pub review_count: u32,
+
+    /// Stable ID of the note, recorded when tracking by ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sm2: Option<Sm2State>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fsrs: Option<FsrsState>,
+
+    /// Leitner box, starting from 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leitner_box: Option<u32>,
+
+    /// Day the file is due, if moved off the one of its interval to spread
+    /// the load. Cleared by the next review.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+
+    /// Not drawn before this day, set by hand. Cleared by the next review.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub postponed: Option<NaiveDate>,
+
+    /// Date the file was drawn on, until it is rated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<NaiveDate>,
+
+    /// Drawn next regardless of weight, until reviewed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+
+    /// On hold, never drawn until unsuspended.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspended: bool,
+
+    /// Marked hard by `memo hard`, the highest difficulty.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hard: bool,
+
+    /// Free-text notes on the file, shown whenever it is drawn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Annotation>,
+
+    /// Difficulty derived from the grades, see [`ReviewInfo::difficulty`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graded_difficulty: Option<f64>,
+
+    /// Factor the grades given under the decay scheduler slow the file's
+    /// decay by, shorter intervals below 1 and longer ones above.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stretch: Option<f64>,
+
+    /// Hash of the content when memo last saw it, tracked by `on_change`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+
+    /// Content changed since the last review, boosted by `change_boost`
+    /// until the next one.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub changed: bool,
+
+    /// Fields unknown to this version, kept so that saving doesn't drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ReviewInfo {
+    /// From 0 for easy files to 1 for hard ones: 1 if marked hard, else a
+    /// moving average of the grades, `again` counting 1 and `easy` 0, and 0
+    /// for files never graded.
+    pub fn difficulty(&self) -> f64 {
+        if self.hard {
+            return 1.0;
+        }
+
+        self.graded_difficulty.unwrap_or(0.0)
+    }
+
+    /// Takes `grade` into the moving average of the grades.
+    pub fn grade_difficulty(&mut self, grade: Grade) {
+        let score = match grade {
+            Grade::Again => 1.0,
+            Grade::Hard => 2.0 / 3.0,
+            Grade::Good => 1.0 / 3.0,
+            Grade::Easy => 0.0,
+        };
+        self.graded_difficulty = Some(match self.graded_difficulty {
+            Some(average) => average + (score - average) * 0.3,
+            None => score,
+        });
+    }
+
+    pub fn new(file_name: String) -> Self {
+        Self {
+            file_name,
+            last_reviewed: None,
+            review_count: 0,
+            id: None,
+            sm2: None,
+            fsrs: None,
+            leitner_box: None,
+            due: None,
+            postponed: None,
+            pending: None,
+            pinned: false,
+            suspended: false,
+            hard: false,
+            notes: Vec::new(),
+            graded_difficulty: None,
+            stretch: None,
+            hash: None,
+            changed: false,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// A note on a file, left by `memo note`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub date: NaiveDate,
+    pub text: String,
+}
+
+/// Outcome of a review, as given to `memo rate`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl FromStr for Grade {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "again" => Ok(Self::Again),
+            "hard" => Ok(Self::Hard),
+            "good" => Ok(Self::Good),
+            "easy" => Ok(Self::Easy),
+            _ => Err(format!("expected again, hard, good or easy, got `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Again => "again",
+            Self::Hard => "hard",
+            Self::Good => "good",
+            Self::Easy => "easy",
+        })
+    }
+}
+
+pub fn mark_pending(review_data: &mut ReviewData, file_name: String, today: NaiveDate) {
+    review_data
+        .entry(file_name.clone())
+        .or_insert_with(|| ReviewInfo::new(file_name))
+        .pending = Some(today);
+}
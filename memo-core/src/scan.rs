@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::DeckConfig;
+use crate::ignore::Ignore;
+use crate::index::Index;
+use crate::{frontmatter, sections};
+
+/// Every file under `dir` with one of `extensions` that isn't ignored,
+/// recursively. Extensions are matched ignoring case and a leading dot.
+/// Listings of dirs are cached in `index`.
+pub fn note_files(
+    dir: &Path,
+    ignore: &Ignore,
+    extensions: &[String],
+    index: &mut Index,
+) -> Vec<PathBuf> {
+    index
+        .files(dir, ignore)
+        .into_iter()
+        .filter(|f| has_extension(f, extensions))
+        .collect()
+}
+
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    path.extension().is_some_and(|ext| {
+        let ext = ext.to_string_lossy();
+        extensions
+            .iter()
+            .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+    })
+}
+
+/// The file name without its extension, followed by `#` and the heading
+/// for an item of a split note, see [`sections`].
+pub fn file_stem(path: &Path) -> String {
+    let (file, heading) = sections::split(path);
+    let stem = file
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+
+    match heading {
+        Some(heading) => format!("{}#{}", stem, heading),
+        None => stem,
+    }
+}
+
+/// The review items under `dir`: its note files, or their sections for
+/// decks that split notes by heading, long ones by `split_above`. What that
+/// needs of the notes is cached in `index`.
+pub fn items(
+    dir: &Path,
+    ignore: &Ignore,
+    settings: &DeckConfig,
+    index: &mut Index,
+) -> Vec<PathBuf> {
+    let files = note_files(dir, ignore, &settings.extensions, index);
+    if !settings.split_headings && settings.split_above.is_none() {
+        return files;
+    }
+
+    files
+        .iter()
+        .flat_map(|f| {
+            let Some(note) = index.note(f) else {
+                return vec![f.clone()];
+            };
+            let items = if settings.split_headings {
+                sections::items(f, &note.headings[0])
+            } else {
+                vec![f.clone()]
+            };
+            let long = settings.split_above.is_some_and(|n| note.words > n);
+            if items.len() == 1 && long {
+                sections::items(f, &note.headings[1])
+            } else {
+                items
+            }
+        })
+        .collect()
+}
+
+/// Stems shared by several of `files`, which would share review data, with
+/// those files.
+pub fn collisions(files: &[PathBuf]) -> Vec<(String, Vec<PathBuf>)> {
+    let mut by_stem: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for file in files {
+        by_stem
+            .entry(file_stem(file))
+            .or_default()
+            .push(file.clone());
+    }
+
+    by_stem.into_iter().filter(|(_, f)| f.len() > 1).collect()
+}
+
+/// Words of the text, not counting its frontmatter.
+pub fn words(text: &str) -> usize {
+    frontmatter::parse(text).1.split_whitespace().count()
+}
+
+/// The `summary` field of the item's note, or else the first `lines`
+/// non-blank lines of the item.
+pub fn preview(path: &Path, lines: usize) -> Vec<String> {
+    let note = sections::split(path).0;
+    let text = fs::read_to_string(note).unwrap_or_default();
+    if let Some(summary) = frontmatter::parse(&text).0.get("summary") {
+        return vec![summary.to_string()];
+    }
+
+    sections::read(path)
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty())
+        .take(lines)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Estimated minutes to read the item at `words_per_minute`, at least one
+/// for opening it and looking it over.
+pub fn reading_minutes(path: &Path, words_per_minute: u32) -> f64 {
+    let words = words(&sections::read(path));
+
+    (words as f64 / words_per_minute.max(1) as f64).max(1.0)
+}
@@ -0,0 +1,296 @@
+//! The schedulers a deck can use, selected by `scheduler` in its config.
+
+use chrono::NaiveDate;
+
+use crate::config::{DeckConfig, SchedulerKind};
+use crate::expr::{Formula, Vars};
+use crate::fsrs::{self, FsrsState};
+use crate::review::{Grade, ReviewInfo};
+use crate::weight::{self, Breakdown};
+use crate::{leitner, sm2};
+
+pub trait Scheduler {
+    /// The terms of the weight of a file of `words` words, `max_out` being
+    /// the days assumed since the last review of a reviewed file without a date.
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        words: usize,
+        today: NaiveDate,
+        max_out: i64,
+    ) -> Breakdown;
+
+    /// Updates the scheduler's own state of a file for a review on `today`,
+    /// before its date and count are.
+    fn review(&self, _info: &mut ReviewInfo, _grade: Grade, _today: NaiveDate) {}
+
+    /// Days between the last review of a reviewed file and its next one,
+    /// `None` if the scheduler has no notion of a file being due.
+    fn interval(&self, info: &ReviewInfo) -> Option<u32>;
+}
+
+/// The scheduler of `settings`.
+pub fn of(settings: &DeckConfig) -> Box<dyn Scheduler> {
+    match settings.scheduler {
+        SchedulerKind::Decay => Box::new(Decay {
+            rate: settings.decay_rate,
+        }),
+        SchedulerKind::Sm2 => Box::new(Sm2),
+        SchedulerKind::Fsrs => Box::new(Fsrs),
+        SchedulerKind::Leitner => Box::new(Leitner {
+            intervals: settings.leitner_intervals.clone(),
+        }),
+        SchedulerKind::Flat => Box::new(Flat),
+        SchedulerKind::Oldest => Box::new(Oldest),
+        SchedulerKind::Formula => match &settings.formula {
+            Some(formula) => Box::new(Custom {
+                formula: formula.clone(),
+                decay_rate: settings.decay_rate,
+            }),
+            // Rejected when loading the config.
+            None => Box::new(Flat),
+        },
+    }
+}
+
+pub struct Decay {
+    pub rate: f64,
+}
+
+impl Scheduler for Decay {
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        _words: usize,
+        today: NaiveDate,
+        max_out: i64,
+    ) -> Breakdown {
+        weight::breakdown(info, today, max_out, self.rate)
+    }
+
+    fn review(&self, info: &mut ReviewInfo, grade: Grade, _today: NaiveDate) {
+        info.stretch = Some(weight::stretch(info.stretch, grade));
+    }
+
+    fn interval(&self, info: &ReviewInfo) -> Option<u32> {
+        Some(weight::stretched_interval(
+            self.rate,
+            info.stretch.unwrap_or(1.0),
+        ))
+    }
+}
+
+pub struct Sm2;
+
+impl Scheduler for Sm2 {
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        _words: usize,
+        today: NaiveDate,
+        _max_out: i64,
+    ) -> Breakdown {
+        sm2::breakdown(info, today)
+    }
+
+    fn review(&self, info: &mut ReviewInfo, grade: Grade, _today: NaiveDate) {
+        info.sm2.get_or_insert_with(Default::default).review(grade);
+    }
+
+    fn interval(&self, info: &ReviewInfo) -> Option<u32> {
+        Some(info.sm2.as_ref().map_or(1, |s| s.interval.max(1)))
+    }
+}
+
+pub struct Fsrs;
+
+impl Scheduler for Fsrs {
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        _words: usize,
+        today: NaiveDate,
+        _max_out: i64,
+    ) -> Breakdown {
+        fsrs::breakdown(info, today)
+    }
+
+    fn review(&self, info: &mut ReviewInfo, grade: Grade, today: NaiveDate) {
+        let elapsed = info
+            .last_reviewed
+            .map_or(0, |d| (today - d).num_days().max(0));
+        match &mut info.fsrs {
+            Some(fsrs) => fsrs.review(grade, elapsed),
+            None => info.fsrs = Some(FsrsState::new(grade)),
+        }
+    }
+
+    fn interval(&self, info: &ReviewInfo) -> Option<u32> {
+        Some(info.fsrs.as_ref().map_or(1, |s| s.interval()))
+    }
+}
+
+pub struct Leitner {
+    pub intervals: Vec<u32>,
+}
+
+impl Scheduler for Leitner {
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        _words: usize,
+        today: NaiveDate,
+        _max_out: i64,
+    ) -> Breakdown {
+        leitner::breakdown(info, today, &self.intervals)
+    }
+
+    fn review(&self, info: &mut ReviewInfo, grade: Grade, _today: NaiveDate) {
+        leitner::review(info, grade);
+    }
+
+    fn interval(&self, info: &ReviewInfo) -> Option<u32> {
+        Some(leitner::interval(
+            info.leitner_box.unwrap_or(1),
+            &self.intervals,
+        ))
+    }
+}
+
+/// Every file weighs the same, so draws are uniformly random.
+pub struct Flat;
+
+impl Scheduler for Flat {
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        _words: usize,
+        today: NaiveDate,
+        _max_out: i64,
+    ) -> Breakdown {
+        Breakdown {
+            days_since: days_since(info, today),
+            ..Default::default()
+        }
+    }
+
+    fn interval(&self, _info: &ReviewInfo) -> Option<u32> {
+        None
+    }
+}
+
+/// Files weigh a point per day since their last review, new files as much
+/// as under the other schedulers, so `--top` draws the least recent ones.
+pub struct Oldest;
+
+impl Scheduler for Oldest {
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        _words: usize,
+        today: NaiveDate,
+        max_out: i64,
+    ) -> Breakdown {
+        if info.is_none_or(|i| i.review_count == 0) {
+            return Breakdown::new_file();
+        }
+
+        let days = days_since(info, today).unwrap_or(max_out);
+        Breakdown {
+            days_since: Some(days),
+            priority: days as f64,
+            ..Default::default()
+        }
+    }
+
+    fn interval(&self, _info: &ReviewInfo) -> Option<u32> {
+        None
+    }
+}
+
+/// Weighs files by the `formula` of the deck.
+pub struct Custom {
+    pub formula: Formula,
+    pub decay_rate: f64,
+}
+
+impl Scheduler for Custom {
+    fn breakdown(
+        &self,
+        info: Option<&ReviewInfo>,
+        words: usize,
+        today: NaiveDate,
+        max_out: i64,
+    ) -> Breakdown {
+        let review_count = info.map_or(0, |i| i.review_count);
+        let days = days_since(info, today).unwrap_or(max_out);
+        let retention = self.decay_rate.powi(days as i32);
+        let vars = Vars {
+            days_since: days as f64,
+            review_count: review_count as f64,
+            word_count: words as f64,
+            retention,
+            decay_rate: self.decay_rate,
+            is_new: if review_count == 0 { 1.0 } else { 0.0 },
+            max_out: max_out as f64,
+            difficulty: info.map_or(0.0, ReviewInfo::difficulty),
+        };
+
+        Breakdown {
+            days_since: (review_count > 0).then_some(days),
+            retention: (review_count > 0).then_some(retention),
+            value: Some(self.formula.eval(&vars)),
+            ..Default::default()
+        }
+    }
+
+    fn interval(&self, _info: &ReviewInfo) -> Option<u32> {
+        None
+    }
+}
+
+fn days_since(info: Option<&ReviewInfo>, today: NaiveDate) -> Option<i64> {
+    info?.last_reviewed.map(|d| (today - d).num_days().max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decay_intervals_follow_the_grades() {
+        let decay = Decay { rate: 0.96 };
+        let today = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        let after = |grades: &[Grade]| {
+            let mut info = ReviewInfo::default();
+            for grade in grades {
+                decay.review(&mut info, *grade, today);
+            }
+            decay.interval(&info).unwrap()
+        };
+
+        let good = after(&[Grade::Good]);
+        assert_eq!(good, weight::interval(0.96));
+        assert!(after(&[Grade::Hard]) < good);
+        assert!(after(&[Grade::Again]) < after(&[Grade::Hard]));
+        assert!(after(&[Grade::Easy]) > good);
+        assert!(after(&[Grade::Easy, Grade::Easy]) > after(&[Grade::Easy]));
+        assert_eq!(after(&[Grade::Again; 9]), after(&[Grade::Again; 10]));
+    }
+
+    #[test]
+    fn stretched_files_decay_slower() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 11).unwrap();
+        let info = |stretch| ReviewInfo {
+            last_reviewed: NaiveDate::from_ymd_opt(2025, 7, 1),
+            review_count: 1,
+            stretch,
+            ..Default::default()
+        };
+        let retention = |i: &ReviewInfo| weight::breakdown(Some(i), today, 30, 0.9).retention;
+
+        assert_eq!(retention(&info(None)), retention(&info(Some(1.0))));
+        assert!(retention(&info(Some(2.0))) > retention(&info(None)));
+        assert!(retention(&info(Some(0.5))) < retention(&info(None)));
+    }
+}
@@ -0,0 +1,165 @@
+//! Versions of the format of the review data, and the migrations between
+//! them.
+//!
+//! A revs file is `{"version": <n>, "reviews": [<entry>, ...]}`. Data of an
+//! older version is upgraded step by step when loaded, and saved at the
+//! current one; data of a newer version is refused rather than saved without
+//! what this version doesn't know of.
+//!
+//! 1. A bare array of entries.
+//! 2. The array in an object with the version.
+
+use std::io;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::review::{ReviewData, ReviewInfo};
+use crate::store;
+
+/// Version of the data this version of memo writes.
+pub const VERSION: u64 = 2;
+
+/// The steps upgrading the data a version, the first from version 1.
+const MIGRATIONS: [fn(Value) -> Value; (VERSION - 1) as usize] = [wrap];
+
+fn wrap(entries: Value) -> Value {
+    json!({ "version": 2, "reviews": entries })
+}
+
+/// Version of `data`, 1 for a bare array.
+pub fn version(data: &Value) -> Option<u64> {
+    match data {
+        Value::Array(_) => Some(1),
+        Value::Object(map) => map.get("version")?.as_u64(),
+        _ => None,
+    }
+}
+
+/// Whether the JSON `text` is data of a version newer than this one, which
+/// is refused rather than unreadable.
+pub fn is_newer(text: &[u8]) -> bool {
+    serde_json::from_slice(text)
+        .ok()
+        .and_then(|data| version(&data))
+        .is_some_and(|v| v > VERSION)
+}
+
+/// `data` of any version upgraded to the current one.
+pub fn migrate(mut data: Value) -> Result<Value, String> {
+    let from = match version(&data) {
+        Some(v) if v >= 1 => v,
+        _ => return Err("expected an array or an object with a `version`".to_string()),
+    };
+    if from > VERSION {
+        return Err(format!(
+            "version {} of the data is newer than this memo's, {}; upgrade memo",
+            from, VERSION
+        ));
+    }
+    for step in &MIGRATIONS[from as usize - 1..] {
+        data = step(data);
+    }
+
+    Ok(data)
+}
+
+/// The entries of `data` of any version.
+pub fn decode(data: Value) -> Result<Vec<ReviewInfo>, String> {
+    let mut data = migrate(data)?;
+    let reviews = data.get_mut("reviews").map(Value::take).unwrap_or_default();
+
+    serde_json::from_value(reviews).map_err(|e| e.to_string())
+}
+
+/// [`decode`] of the JSON `text`, errors naming `location`.
+pub fn parse(text: &[u8], location: &str) -> io::Result<Vec<ReviewInfo>> {
+    serde_json::from_slice(text)
+        .map_err(|e| e.to_string())
+        .and_then(decode)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", location, e)))
+}
+
+#[derive(Serialize)]
+struct Current<'a> {
+    version: u64,
+    reviews: Vec<&'a ReviewInfo>,
+}
+
+impl<'a> Current<'a> {
+    fn of(data: &'a ReviewData) -> Self {
+        Self {
+            version: VERSION,
+            reviews: store::sorted(data),
+        }
+    }
+}
+
+/// `data` as pretty JSON at the current version, entries in file name order.
+pub fn encode(data: &ReviewData) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Current::of(data))
+}
+
+/// `data` as a value at the current version, for other encodings.
+pub fn to_value(data: &ReviewData) -> serde_json::Result<Value> {
+    serde_json::to_value(Current::of(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(file_name: &str) -> Value {
+        json!({ "file_name": file_name, "last_reviewed": "2025-07-01", "review_count": 3 })
+    }
+
+    #[test]
+    fn migrates_a_bare_array() {
+        let v1 = json!([entry("a.md"), entry("b.md")]);
+        assert_eq!(version(&v1), Some(1));
+
+        let v2 = migrate(v1.clone()).unwrap();
+        assert_eq!(v2, json!({ "version": 2, "reviews": v1 }));
+        assert_eq!(migrate(v2.clone()).unwrap(), v2);
+
+        let entries = decode(json!([entry("a.md")])).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].file_name, "a.md");
+        assert_eq!(entries[0].review_count, 3);
+    }
+
+    #[test]
+    fn refuses_newer_and_unknown_data() {
+        let v3 = json!({ "version": VERSION + 1, "reviews": [] });
+        assert!(migrate(v3.clone()).unwrap_err().contains("upgrade memo"));
+        assert!(is_newer(v3.to_string().as_bytes()));
+        assert!(!is_newer(b"[]"));
+        assert!(!is_newer(b"not json"));
+
+        assert!(migrate(json!({ "reviews": [] })).is_err());
+        assert!(migrate(json!({ "version": 0 })).is_err());
+        assert!(migrate(json!("text")).is_err());
+    }
+
+    #[test]
+    fn encodes_at_the_current_version_in_order() {
+        let data: ReviewData = decode(json!([entry("b.md"), entry("a.md")]))
+            .unwrap()
+            .into_iter()
+            .map(|info| (info.file_name.clone(), info))
+            .collect();
+        let text = encode(&data).unwrap();
+        let value: Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(version(&value), Some(VERSION));
+        let names: Vec<_> = value["reviews"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["file_name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["a.md", "b.md"]);
+        assert_eq!(parse(text.as_bytes(), "x").unwrap().len(), 2);
+        assert!(parse(b"{", "x").unwrap_err().to_string().starts_with("x: "));
+    }
+}
@@ -0,0 +1,140 @@
+//! Notes split by heading into review items of their own.
+//!
+//! An item is addressed by the path of its note with `#` and the heading
+//! appended to the file name, e.g. `Networking.md#TCP`, which
+//! [`scan::file_stem`](crate::scan::file_stem) turns into the key of its
+//! review data, `Networking#TCP`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter;
+
+/// The note and heading of an item, no heading for a whole note.
+pub fn split(path: &Path) -> (PathBuf, Option<String>) {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match file_name.split_once('#') {
+        Some((name, heading)) if Path::new(name).extension().is_some() => {
+            (path.with_file_name(name), Some(heading.to_string()))
+        }
+        _ => (path.to_path_buf(), None),
+    }
+}
+
+/// The item of `file` under `heading`.
+pub fn path(file: &Path, heading: &str) -> PathBuf {
+    let mut file_name = file.file_name().unwrap_or_default().to_os_string();
+    file_name.push("#");
+    file_name.push(heading);
+
+    file.with_file_name(file_name)
+}
+
+/// The items of the note `file` with the heading `titles`, see [`titles`],
+/// or the note itself if it has fewer than two.
+pub fn items(file: &Path, titles: &[String]) -> Vec<PathBuf> {
+    if titles.len() < 2 {
+        return vec![file.to_path_buf()];
+    }
+
+    titles.iter().map(|h| path(file, h)).collect()
+}
+
+/// Titles of the headings of `level` in `body`, the note `file` without its
+/// frontmatter.
+pub fn titles(file: &Path, body: &str, level: usize) -> Vec<String> {
+    headings(body, is_org(file), level)
+        .into_iter()
+        .filter_map(|(_, h)| h)
+        .collect()
+}
+
+/// Text of an item, the note without its frontmatter if it is a whole one.
+/// A section runs from its heading up to the next of the same level or
+/// above, the first one taking along the text before it.
+pub fn read(path: &Path) -> String {
+    let (file, heading) = split(path);
+    let text = fs::read_to_string(&file).unwrap_or_default();
+    let body = frontmatter::parse(&text).1;
+    let Some(heading) = heading else {
+        return body.to_string();
+    };
+
+    let org = is_org(&file);
+    let lines: Vec<&str> = body.lines().collect();
+    for level in 1..=6 {
+        let headings = headings(body, org, level);
+        let Some(i) = headings
+            .iter()
+            .position(|(_, h)| h.as_deref() == Some(heading.as_str()))
+        else {
+            continue;
+        };
+        let first = headings.iter().position(|(_, h)| h.is_some()) == Some(i);
+        let start = if first { 0 } else { headings[i].0 };
+        let end = headings.get(i + 1).map_or(lines.len(), |(line, _)| *line);
+        return lines[start..end].join("\n");
+    }
+
+    String::new()
+}
+
+/// Line numbers of the headings of `level` and above, with the titles of
+/// those of `level`. Titles are made unique by appending ` (2)`, ` (3)` and
+/// so on, and have `/`, `\` and `#` replaced by `-` to fit in a path.
+fn headings(text: &str, org: bool, level: usize) -> Vec<(usize, Option<String>)> {
+    let mark = if org { '*' } else { '#' };
+    let mut headings: Vec<(usize, Option<String>)> = Vec::new();
+    let mut in_code = false;
+
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim().to_ascii_lowercase();
+        if org && trimmed.starts_with("#+end_") {
+            in_code = false;
+            continue;
+        }
+        if (org && trimmed.starts_with("#+begin_")) || (!org && trimmed.starts_with("```")) {
+            in_code = org || !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        let depth = line.chars().take_while(|c| *c == mark).count();
+        let Some(title) = line[depth..].strip_prefix(' ') else {
+            continue;
+        };
+        if depth == 0 || depth > level {
+            continue;
+        }
+        if depth < level {
+            headings.push((i, None));
+            continue;
+        }
+
+        let title = if org {
+            title.trim()
+        } else {
+            title.trim().trim_end_matches('#').trim()
+        };
+        let title = title.replace(['/', '\\', '#'], "-");
+        let mut unique = title.clone();
+        let mut n = 1;
+        while headings.iter().any(|(_, h)| h.as_ref() == Some(&unique)) {
+            n += 1;
+            unique = format!("{} ({})", title, n);
+        }
+        headings.push((i, Some(unique)));
+    }
+
+    headings
+}
+
+fn is_org(file: &Path) -> bool {
+    file.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("org"))
+}
@@ -0,0 +1,78 @@
+use rand::Rng;
+
+/// The `count` items with the highest weights, or all of them if fewer.
+pub fn top<T: Clone>(weights: &[(T, usize)], count: usize) -> Vec<T> {
+    let mut weights = weights.to_vec();
+    weights.sort_unstable_by_key(|w| w.1);
+
+    (0..count)
+        .map_while(|_| weights.pop().map(|w| w.0))
+        .collect()
+}
+
+/// `count` distinct items drawn at random, each with a chance proportional to its weight.
+///
+/// Sampled without replacement by the keys of Efraimidis and Spirakis: every
+/// item gets `ln(u) / weight` for a uniform `u`, the highest keys win. That
+/// draws the same as picking one item at a time by weight among those left,
+/// in time and memory linear in the number of items rather than the weights.
+pub fn random<T: Clone, R: Rng + ?Sized>(
+    weights: &[(T, usize)],
+    count: usize,
+    rng: &mut R,
+) -> Vec<T> {
+    let mut keyed: Vec<(f64, &T)> = weights
+        .iter()
+        .filter(|(_, w)| *w > 0)
+        .map(|(item, w)| {
+            let u: f64 = 1.0 - rng.random::<f64>();
+            (u.ln() / *w as f64, item)
+        })
+        .collect();
+    keyed.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+
+    keyed
+        .into_iter()
+        .take(count)
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    #[test]
+    fn top_takes_the_heaviest() {
+        let weights = [("a", 3), ("b", 9), ("c", 1), ("d", 5)];
+        assert_eq!(top(&weights, 2), ["b", "d"]);
+        assert_eq!(top(&weights, 9), ["b", "d", "a", "c"]);
+        assert!(top(&weights, 0).is_empty());
+    }
+
+    #[test]
+    fn random_draws_distinct_items_by_weight() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let weights = [("a", 1), ("b", 1000), ("c", 0), ("d", 1)];
+
+        let mut heavy = 0;
+        for _ in 0..200 {
+            let mut drawn = random(&weights, 2, &mut rng);
+            if drawn.first() == Some(&"b") {
+                heavy += 1;
+            }
+            assert!(!drawn.contains(&"c"));
+            drawn.sort();
+            drawn.dedup();
+            assert_eq!(drawn.len(), 2);
+        }
+        assert!(heavy > 190, "{}", heavy);
+
+        let mut all = random(&weights, 9, &mut rng);
+        all.sort();
+        assert_eq!(all, ["a", "b", "d"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! The SM-2 algorithm of SuperMemo, driven by the grades given to `memo rate`.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::review::{Grade, ReviewInfo};
+use crate::weight::Breakdown;
+
+const INITIAL_EASE: f64 = 2.5;
+const MINIMUM_EASE: f64 = 1.3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Sm2State {
+    pub ease: f64,
+    /// Days until the next review is due.
+    pub interval: u32,
+    /// Successful reviews in a row.
+    pub repetitions: u32,
+}
+
+impl Default for Sm2State {
+    fn default() -> Self {
+        Self {
+            ease: INITIAL_EASE,
+            interval: 0,
+            repetitions: 0,
+        }
+    }
+}
+
+impl Sm2State {
+    pub fn review(&mut self, grade: Grade) {
+        let quality = quality(grade);
+
+        if quality >= 3.0 {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f64 * self.ease).round() as u32,
+            };
+            self.repetitions += 1;
+        } else {
+            self.interval = 1;
+            self.repetitions = 0;
+        }
+
+        let miss = 5.0 - quality;
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MINIMUM_EASE);
+    }
+}
+
+/// The 0-5 response quality of SM-2.
+fn quality(grade: Grade) -> f64 {
+    match grade {
+        Grade::Again => 1.0,
+        Grade::Hard => 3.0,
+        Grade::Good => 4.0,
+        Grade::Easy => 5.0,
+    }
+}
+
+/// Weight of a file, growing with how far it is into (and past) its interval.
+pub fn breakdown(info: Option<&ReviewInfo>, today: NaiveDate) -> Breakdown {
+    let Some(d) = info.and_then(|i| i.last_reviewed) else {
+        return Breakdown::new_file();
+    };
+
+    let interval = info
+        .and_then(|i| i.sm2.as_ref())
+        .map_or(1, |s| s.interval.max(1));
+    let days_since_last = (today - d).num_days().max(0);
+    let overdue = days_since_last as f64 / interval as f64;
+
+    Breakdown {
+        days_since: Some(days_since_last),
+        overdue: Some(overdue),
+        priority: overdue.min(2.0) * 50.0,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_grow_by_ease() {
+        let mut state = Sm2State::default();
+        let intervals: Vec<u32> = (0..4)
+            .map(|_| {
+                state.review(Grade::Good);
+                state.interval
+            })
+            .collect();
+        assert_eq!(intervals, [1, 6, 15, 38]);
+        assert_eq!(state.ease, INITIAL_EASE);
+        assert_eq!(state.repetitions, 4);
+    }
+
+    #[test]
+    fn grades_change_the_ease() {
+        let mut state = Sm2State::default();
+        state.review(Grade::Easy);
+        assert!((state.ease - 2.6).abs() < 1e-9);
+        state.review(Grade::Hard);
+        assert!((state.ease - 2.46).abs() < 1e-9);
+        assert_eq!(state.interval, 6);
+
+        state.review(Grade::Again);
+        assert_eq!((state.interval, state.repetitions), (1, 0));
+        assert!((state.ease - 1.92).abs() < 1e-9);
+        for _ in 0..5 {
+            state.review(Grade::Again);
+        }
+        assert_eq!(state.ease, MINIMUM_EASE);
+    }
+
+    #[test]
+    fn overdue_files_weigh_more() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 11).unwrap();
+        let info = ReviewInfo {
+            last_reviewed: NaiveDate::from_ymd_opt(2025, 7, 1),
+            sm2: Some(Sm2State {
+                interval: 5,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let b = breakdown(Some(&info), today);
+        assert_eq!(b.days_since, Some(10));
+        assert_eq!(b.overdue, Some(2.0));
+        assert_eq!(b.priority, 100.0);
+        assert_eq!(
+            breakdown(None, today).priority,
+            Breakdown::new_file().priority
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! SQLite backend, driving the `sqlite3` command line shell.
+//!
+//! Every entry is a row of a single `reviews` table, keyed by the name of
+//! the revs file it would otherwise live in and its file name. The entry
+//! itself is stored as JSON so that fields added later need no new columns,
+//! and the version of its format is kept by key in a `versions` table.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde_json::{Value, json};
+
+use crate::review::{ReviewData, ReviewInfo};
+use crate::schema;
+use crate::store::{self, Storage};
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS reviews (
+    key TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    last_reviewed TEXT,
+    review_count INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (key, file_name)
+);
+CREATE TABLE IF NOT EXISTS versions (
+    key TEXT PRIMARY KEY,
+    version INTEGER NOT NULL
+);";
+
+pub struct Sqlite {
+    pub db: PathBuf,
+    pub key: String,
+}
+
+impl Storage for Sqlite {
+    fn load(&self) -> io::Result<Vec<ReviewInfo>> {
+        // Rows saved before versions were recorded are of version 2.
+        let out = run(
+            &self.db,
+            &format!(
+                "SELECT COALESCE((SELECT version FROM versions WHERE key = {0}), 2);
+SELECT data FROM reviews WHERE key = {0} ORDER BY file_name;",
+                quote(&self.key)
+            ),
+        )?;
+
+        let mut lines = out.lines().filter(|l| !l.is_empty());
+        let version: u64 = lines.next().and_then(|v| v.parse().ok()).unwrap_or(2);
+        let reviews = lines
+            .map(|l| serde_json::from_str(l).map_err(io::Error::other))
+            .collect::<io::Result<Vec<Value>>>()?;
+
+        schema::decode(json!({ "version": version, "reviews": reviews })).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", self.location(), e),
+            )
+        })
+    }
+
+    fn save(&self, data: &ReviewData) -> io::Result<()> {
+        let mut sql = format!(
+            "BEGIN;\nDELETE FROM reviews WHERE key = {0};\nINSERT OR REPLACE INTO versions VALUES ({0}, {1});\n",
+            quote(&self.key),
+            schema::VERSION
+        );
+        for info in store::sorted(data) {
+            let json = serde_json::to_string(info).map_err(io::Error::other)?;
+            sql.push_str(&format!(
+                "INSERT INTO reviews VALUES ({}, {}, {}, {}, {});\n",
+                quote(&self.key),
+                quote(&info.file_name),
+                info.last_reviewed
+                    .map_or_else(|| "NULL".to_string(), |d| quote(&d.to_string())),
+                info.review_count,
+                quote(&json),
+            ));
+        }
+        sql.push_str("COMMIT;\n");
+
+        run(&self.db, &sql).map(|_| ())
+    }
+
+    fn location(&self) -> String {
+        format!("{} ({})", self.db.display(), self.key)
+    }
+}
+
+/// Every key with rows in the database.
+pub fn keys(db: &Path) -> io::Result<Vec<String>> {
+    let out = run(db, "SELECT DISTINCT key FROM reviews ORDER BY key;")?;
+
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// Runs `sql` after making sure the schema exists, returning what it printed.
+fn run(db: &Path, sql: &str) -> io::Result<String> {
+    exec(db, &format!("{}\n{}", SCHEMA, sql))
+}
+
+/// Runs `sql` on any database, returning what it printed.
+pub fn exec(db: &Path, sql: &str) -> io::Result<String> {
+    let mut child = Command::new("sqlite3")
+        .arg("-batch")
+        .arg("-bail")
+        .arg(db)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run sqlite3: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(sql.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "sqlite3: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `s` as an SQL string literal.
+pub fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
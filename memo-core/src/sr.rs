@@ -0,0 +1,179 @@
+//! Interop with the Spaced Repetition plugin for Obsidian, which keeps the
+//! schedule of a note in its frontmatter as `sr-due`, `sr-interval` and
+//! `sr-ease`, and that of a card in an `<!--SR:!<due>,<interval>,<ease>-->`
+//! comment after it, one `!` entry per cloze deletion.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{Days, NaiveDate};
+
+use crate::cards::{self, Card};
+use crate::config::SchedulerKind;
+use crate::deck::{self, Deck};
+use crate::frontmatter::{self, Frontmatter};
+use crate::review::ReviewInfo;
+use crate::sm2::Sm2State;
+use crate::{scan, sections, store};
+
+/// A schedule as the plugin keeps it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    pub due: NaiveDate,
+    pub interval: u32,
+    /// Ease in percent, 250 for 2.5.
+    pub ease: u32,
+}
+
+impl Schedule {
+    /// The schedule of a note, from its frontmatter.
+    pub fn of_note(frontmatter: &Frontmatter) -> Option<Self> {
+        Some(Self {
+            due: frontmatter.get("sr-due")?.parse().ok()?,
+            interval: frontmatter.get("sr-interval")?.parse().ok()?,
+            ease: frontmatter.get("sr-ease")?.parse().ok()?,
+        })
+    }
+
+    /// The schedule of a card, the first of its entries.
+    pub fn of_card(card: &Card) -> Option<Self> {
+        let entry = card.sr.as_deref()?.split('!').find(|e| !e.is_empty())?;
+        let mut parts = entry.split(',').map(str::trim);
+
+        Some(Self {
+            due: parts.next()?.parse().ok()?,
+            interval: parts.next()?.parse().ok()?,
+            ease: parts.next()?.parse().ok()?,
+        })
+    }
+
+    /// Where `info` stands, if it was reviewed.
+    pub fn of_info(
+        info: &ReviewInfo,
+        due: Option<NaiveDate>,
+        interval: Option<u32>,
+    ) -> Option<Self> {
+        let last = info.last_reviewed?;
+        let interval = interval.unwrap_or(1);
+
+        Some(Self {
+            due: due.unwrap_or(last + Days::new(interval as u64)),
+            interval,
+            ease: info
+                .sm2
+                .as_ref()
+                .map_or(250, |s| (s.ease * 100.0).round() as u32),
+        })
+    }
+
+    /// Takes the schedule over into `info` unless it was reviewed since.
+    /// Returns whether that changed anything.
+    pub fn apply(&self, info: &mut ReviewInfo, scheduler: SchedulerKind) -> bool {
+        let last = self.due - Days::new(self.interval as u64);
+        if info.last_reviewed >= Some(last) {
+            return false;
+        }
+
+        info.last_reviewed = Some(last);
+        info.review_count = info.review_count.max(1);
+        info.due = Some(self.due);
+        if scheduler == SchedulerKind::Sm2 {
+            let repetitions = info.sm2.as_ref().map_or(1, |s| s.repetitions.max(1));
+            info.sm2 = Some(Sm2State {
+                ease: self.ease as f64 / 100.0,
+                interval: self.interval,
+                repetitions,
+            });
+        }
+
+        true
+    }
+
+    fn entry(&self) -> String {
+        format!("!{},{},{}", self.due, self.interval, self.ease)
+    }
+}
+
+/// Writes the schedules of the files of `deck` named in `file_names` to
+/// their frontmatter. Items of split notes are left out, the plugin knows of
+/// whole notes only.
+pub fn write_notes(deck: &Deck, file_names: &[String]) -> io::Result<()> {
+    for file in &deck.files {
+        let file_name = scan::file_stem(file);
+        if !file_names.contains(&file_name) || sections::split(file).1.is_some() {
+            continue;
+        }
+        let Some(info) = deck.info(&file_name) else {
+            continue;
+        };
+        let settings = deck.settings_for(&file_name);
+        let Some(schedule) =
+            Schedule::of_info(info, deck.due(&file_name), deck::interval(info, &settings))
+        else {
+            continue;
+        };
+
+        let text = fs::read_to_string(file)?;
+        let updated = frontmatter::set(
+            &text,
+            &[
+                ("sr-due", schedule.due.to_string()),
+                ("sr-interval", schedule.interval.to_string()),
+                ("sr-ease", schedule.ease.to_string()),
+            ],
+        );
+        if updated != text {
+            store::write_atomic(file, updated.as_bytes(), 0)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the `schedules` of cards of the note `file`, by card ID, to their
+/// comments.
+pub fn write_cards(file: &Path, schedules: &[(String, Schedule)]) -> io::Result<()> {
+    let text = fs::read_to_string(file)?;
+    let file_name = scan::file_stem(file);
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+
+    // From the bottom up, so that inserted lines don't move the cards above.
+    let mut cards = cards::parse(&file_name, &text);
+    cards.sort_by_key(|c| std::cmp::Reverse(c.line));
+    for card in cards {
+        let Some((_, schedule)) = schedules.iter().find(|(id, _)| *id == card.id) else {
+            continue;
+        };
+        let entries = card
+            .cloze
+            .as_deref()
+            .map_or(1, |c| c.matches("{{c").count().max(1));
+        let comment = format!("<!--SR:{}-->", schedule.entry().repeat(entries));
+
+        if card.multiline {
+            let next = card.line + 1;
+            match lines.get(next) {
+                Some(l) if l.trim_start().starts_with("<!--SR:") => lines[next] = comment,
+                _ => lines.insert(next, comment),
+            }
+        } else {
+            let line = &lines[card.line];
+            let kept = match line.rfind("<!--SR:") {
+                Some(start) => line[..start].trim_end(),
+                None => line.trim_end(),
+            };
+            lines[card.line] = format!("{} {}", kept, comment);
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    if text.ends_with('\n') {
+        updated.push('\n');
+    }
+    if updated != text {
+        store::write_atomic(file, updated.as_bytes(), 0)?;
+    }
+
+    Ok(())
+}
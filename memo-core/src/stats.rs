@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, NaiveDate};
+use serde::Serialize;
+
+use crate::backtest::{self, Prediction};
+use crate::deck::{self, Deck};
+use crate::history::{self, Event};
+use crate::review::Grade;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DeckStats {
+    pub deck: String,
+    pub files: usize,
+    /// Files never reviewed.
+    pub new: usize,
+    pub reviewed_today: usize,
+    /// Reviews since Monday.
+    pub reviewed_this_week: usize,
+    /// Mean interval in days over reviewed files.
+    pub average_interval: Option<f64>,
+    /// Mean seconds spent on a review, over those timed.
+    pub average_duration: Option<f64>,
+    pub overdue: usize,
+    pub grades: GradeCounts,
+    /// Recall of repeated reviews by days since the review before, against
+    /// the deck's scheduler.
+    pub retention: Vec<RetentionBucket>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct GradeCounts {
+    pub again: usize,
+    pub hard: usize,
+    pub good: usize,
+    pub easy: usize,
+}
+
+impl DeckStats {
+    pub fn compute(deck: &Deck, today: NaiveDate) -> Self {
+        let events = history::load(&deck.history);
+        let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+
+        let mut grades = GradeCounts::default();
+        for event in &events {
+            match event.grade {
+                Grade::Again => grades.again += 1,
+                Grade::Hard => grades.hard += 1,
+                Grade::Good => grades.good += 1,
+                Grade::Easy => grades.easy += 1,
+            }
+        }
+
+        let intervals: Vec<u32> = deck
+            .review_data
+            .values()
+            .filter_map(|i| deck::interval(i, &deck.settings_for(&i.file_name)))
+            .collect();
+        let average_interval = (!intervals.is_empty())
+            .then(|| intervals.iter().map(|&i| i as f64).sum::<f64>() / intervals.len() as f64);
+
+        let durations: Vec<u64> = events.iter().filter_map(|e| e.duration).collect();
+        let average_duration = (!durations.is_empty())
+            .then(|| durations.iter().sum::<u64>() as f64 / durations.len() as f64);
+
+        let predictions = backtest::replay(&events, &deck.settings).predictions;
+
+        Self {
+            deck: deck.name.clone(),
+            files: deck.review_data.len(),
+            new: deck
+                .review_data
+                .values()
+                .filter(|i| i.last_reviewed.is_none())
+                .count(),
+            reviewed_today: events.iter().filter(|e| e.date == today).count(),
+            reviewed_this_week: events
+                .iter()
+                .filter(|e| e.date >= monday && e.date <= today)
+                .count(),
+            average_interval,
+            average_duration,
+            overdue: deck
+                .review_data
+                .keys()
+                .filter(|f| deck.due(f).is_some_and(|d| d < today))
+                .count(),
+            grades,
+            retention: RetentionBucket::split(&predictions),
+        }
+    }
+}
+
+/// Starts of the spans of days since the review before that recall is
+/// told apart by, the last one open.
+pub const BUCKETS: [i64; 8] = [0, 1, 2, 4, 8, 15, 31, 91];
+
+/// Fewest reviews in a bucket for its recall to tell against the model.
+pub const MIN_BUCKET_REVIEWS: usize = 5;
+
+/// Gap between recall and predicted retention past which the model is
+/// miscalibrated.
+pub const MISCALIBRATION: f64 = 0.1;
+
+/// Repeated reviews some days after the review before, with how many were
+/// recalled and how many the scheduler expected.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RetentionBucket {
+    pub from: i64,
+    /// Last day of the span, `None` for the open one.
+    pub to: Option<i64>,
+    pub reviews: usize,
+    /// Share recalled, graded anything but `again`.
+    pub recall: f64,
+    /// Mean retention the scheduler predicted, if it predicts any.
+    pub predicted: Option<f64>,
+}
+
+impl RetentionBucket {
+    /// `predictions` by [`BUCKETS`], empty ones left out.
+    pub fn split(predictions: &[Prediction]) -> Vec<Self> {
+        BUCKETS
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &from)| {
+                let to = BUCKETS.get(i + 1).map(|next| next - 1);
+                let inside: Vec<&Prediction> = predictions
+                    .iter()
+                    .filter(|p| p.elapsed >= from && to.is_none_or(|t| p.elapsed <= t))
+                    .collect();
+                if inside.is_empty() {
+                    return None;
+                }
+                let predicted: Vec<f64> = inside.iter().filter_map(|p| p.predicted).collect();
+
+                Some(Self {
+                    from,
+                    to,
+                    reviews: inside.len(),
+                    recall: inside.iter().filter(|p| p.recalled).count() as f64
+                        / inside.len() as f64,
+                    predicted: (!predicted.is_empty())
+                        .then(|| predicted.iter().sum::<f64>() / predicted.len() as f64),
+                })
+            })
+            .collect()
+    }
+
+    /// Recall minus predicted retention when they are [`MISCALIBRATION`]
+    /// apart over enough reviews: below zero if the model is too hopeful.
+    pub fn miscalibration(&self) -> Option<f64> {
+        let gap = self.recall - self.predicted?;
+        (self.reviews >= MIN_BUCKET_REVIEWS && gap.abs() > MISCALIBRATION).then_some(gap)
+    }
+
+    /// The span as `2-3`, `1` or `91+`.
+    pub fn label(&self) -> String {
+        match self.to {
+            Some(to) if to == self.from => self.from.to_string(),
+            Some(to) => format!("{}-{}", self.from, to),
+            None => format!("{}+", self.from),
+        }
+    }
+}
+
+/// Number of reviews per day over the given events.
+pub fn daily_counts<'a>(events: impl IntoIterator<Item = &'a Event>) -> BTreeMap<NaiveDate, usize> {
+    let mut counts = BTreeMap::new();
+    for event in events {
+        *counts.entry(event.date).or_insert(0) += 1;
+    }
+
+    counts
+}
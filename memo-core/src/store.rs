@@ -0,0 +1,365 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::{Backend, Config};
+use crate::remote::Remote;
+use crate::review::{ReviewData, ReviewInfo};
+use crate::sqlite::Sqlite;
+use crate::{msgpack, schema};
+
+/// Where review data is persisted, one instance per revs file (or the
+/// equivalent of one in other backends).
+pub trait Storage {
+    fn load(&self) -> io::Result<Vec<ReviewInfo>>;
+
+    fn save(&self, data: &ReviewData) -> io::Result<()>;
+
+    /// Where the data lives, for messages.
+    fn location(&self) -> String;
+}
+
+/// The file in the revs dir of the backends keeping one per deck, which
+/// `memo recover` and `memo doctor` mend.
+pub trait RevsFile: Storage {
+    fn path(&self) -> &Path;
+
+    /// Number of previous versions kept as `<file>.1` (newest) to
+    /// `<file>.<n>`.
+    fn backups(&self) -> usize;
+
+    /// The entries of `data`, the contents of such a file, upgraded to the
+    /// current version.
+    fn parse(&self, data: &[u8]) -> Result<Vec<ReviewInfo>, String>;
+
+    /// Whether `data` is of a version newer than this one, which is refused
+    /// rather than unreadable.
+    fn is_newer(&self, data: &[u8]) -> bool;
+
+    /// The entries still whole in `data` that doesn't parse as a whole, the
+    /// first of those of the same file name winning.
+    fn salvage(&self, data: &[u8]) -> ReviewData;
+
+    fn encode(&self, data: &ReviewData) -> io::Result<Vec<u8>>;
+}
+
+/// The revs file of the deck data named `key` if the configured backend
+/// keeps one.
+pub fn revs_file(config: &Config, key: &str) -> Option<Box<dyn RevsFile>> {
+    match config.storage.backend {
+        Backend::Json => Some(Box::new(Json::new(config, key))),
+        Backend::Msgpack => Some(Box::new(Msgpack::new(config, key))),
+        _ => None,
+    }
+}
+
+/// [`Storage::load`] of a revs file: a missing file is empty, one that
+/// doesn't parse an error.
+fn load_file(file: &dyn RevsFile) -> io::Result<Vec<ReviewInfo>> {
+    match fs::read(file.path()) {
+        Ok(data) => file.parse(&data).map_err(|e| {
+            let hint = if file.is_newer(&data) {
+                ""
+            } else {
+                "; `memo recover` salvages what it can"
+            };
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}{}", file.location(), e, hint),
+            )
+        }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// [`Storage::save`] of a revs file, refusing to overwrite one that doesn't
+/// load, left to `memo recover`.
+fn save_file(file: &dyn RevsFile, data: &ReviewData) -> io::Result<()> {
+    if let Ok(old) = fs::read(file.path())
+        && let Err(e) = file.parse(&old)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}; not overwritten", file.location(), e),
+        ));
+    }
+
+    write_atomic(file.path(), &file.encode(data)?, file.backups())
+}
+
+/// A `<key>.json` file in the revs dir.
+pub struct Json {
+    pub path: PathBuf,
+
+    /// Previous versions kept as `<key>.json.1` (newest) to `<key>.json.<n>`.
+    pub backups: usize,
+}
+
+impl Json {
+    pub fn new(config: &Config, key: &str) -> Self {
+        Self {
+            path: config.revs_dir().join(format!("{}.json", key)),
+            backups: config.storage.backups,
+        }
+    }
+}
+
+impl Storage for Json {
+    /// Older versions of the format are upgraded.
+    fn load(&self) -> io::Result<Vec<ReviewInfo>> {
+        load_file(self)
+    }
+
+    fn save(&self, data: &ReviewData) -> io::Result<()> {
+        save_file(self, data)
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+impl RevsFile for Json {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn backups(&self) -> usize {
+        self.backups
+    }
+
+    fn parse(&self, data: &[u8]) -> Result<Vec<ReviewInfo>, String> {
+        serde_json::from_slice(data)
+            .map_err(|e| e.to_string())
+            .and_then(schema::decode)
+    }
+
+    fn is_newer(&self, data: &[u8]) -> bool {
+        schema::is_newer(data)
+    }
+
+    /// Tries an entry at every `{`.
+    fn salvage(&self, data: &[u8]) -> ReviewData {
+        let mut salvaged = ReviewData::new();
+        let mut at = 0;
+        while let Some(i) = data[at..].iter().position(|b| *b == b'{') {
+            let start = at + i;
+            let mut entries = serde_json::Deserializer::from_slice(&data[start..]).into_iter();
+            match entries.next() {
+                Some(Ok(info)) => {
+                    let info: ReviewInfo = info;
+                    salvaged.entry(info.file_name.clone()).or_insert(info);
+                    at = start + entries.byte_offset();
+                }
+                _ => at = start + 1,
+            }
+        }
+
+        salvaged
+    }
+
+    fn encode(&self, data: &ReviewData) -> io::Result<Vec<u8>> {
+        Ok(schema::encode(data).map_err(io::Error::other)?.into_bytes())
+    }
+}
+
+/// A `<key>.msgpack` file in the revs dir, the data of a [`Json`] file in
+/// MessagePack.
+pub struct Msgpack {
+    pub path: PathBuf,
+
+    /// Previous versions kept as `<key>.msgpack.1` (newest) to
+    /// `<key>.msgpack.<n>`.
+    pub backups: usize,
+}
+
+impl Msgpack {
+    pub fn new(config: &Config, key: &str) -> Self {
+        Self {
+            path: config.revs_dir().join(format!("{}.msgpack", key)),
+            backups: config.storage.backups,
+        }
+    }
+}
+
+impl Storage for Msgpack {
+    /// Like [`Json::load`].
+    fn load(&self) -> io::Result<Vec<ReviewInfo>> {
+        load_file(self)
+    }
+
+    fn save(&self, data: &ReviewData) -> io::Result<()> {
+        save_file(self, data)
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+impl RevsFile for Msgpack {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn backups(&self) -> usize {
+        self.backups
+    }
+
+    fn parse(&self, data: &[u8]) -> Result<Vec<ReviewInfo>, String> {
+        msgpack::decode(data).and_then(schema::decode)
+    }
+
+    fn is_newer(&self, data: &[u8]) -> bool {
+        msgpack::decode(data)
+            .ok()
+            .and_then(|data| schema::version(&data))
+            .is_some_and(|v| v > schema::VERSION)
+    }
+
+    /// The entries before the first that doesn't decode.
+    fn salvage(&self, data: &[u8]) -> ReviewData {
+        let mut salvaged = ReviewData::new();
+        for entry in msgpack::whole_items(data, "reviews") {
+            if let Ok(info) = serde_json::from_value::<ReviewInfo>(entry) {
+                salvaged.entry(info.file_name.clone()).or_insert(info);
+            }
+        }
+
+        salvaged
+    }
+
+    fn encode(&self, data: &ReviewData) -> io::Result<Vec<u8>> {
+        let value = schema::to_value(data).map_err(io::Error::other)?;
+        Ok(msgpack::encode(&value))
+    }
+}
+
+/// The configured backend for the data named `key`, e.g. `revs-408`.
+pub fn open(config: &Config, key: &str) -> Box<dyn Storage> {
+    open_backend(config, config.storage.backend, key)
+}
+
+/// `backend` for the data named `key`, whichever is configured.
+pub fn open_backend(config: &Config, backend: Backend, key: &str) -> Box<dyn Storage> {
+    match backend {
+        Backend::Json => Box::new(Json::new(config, key)),
+        Backend::Msgpack => Box::new(Msgpack::new(config, key)),
+        Backend::Sqlite => Box::new(Sqlite {
+            db: config.sqlite_path(),
+            key: key.to_string(),
+        }),
+        Backend::Webdav | Backend::S3 => Box::new(Remote::new(config, key)),
+    }
+}
+
+/// Replaces a revs file that doesn't parse by its newest backup that does,
+/// returning the number of that backup. Without one the file is moved aside
+/// by [`quarantine`], returning `None`.
+pub fn restore(file: &dyn RevsFile) -> io::Result<Option<usize>> {
+    if let Some((n, _)) = newest_backup(file) {
+        fs::copy(with_suffix(file.path(), &n.to_string()), file.path())?;
+        return Ok(Some(n));
+    }
+    quarantine(file.path())?;
+
+    Ok(None)
+}
+
+/// The number and entries of the newest backup of `file` that parses.
+pub fn newest_backup(file: &dyn RevsFile) -> Option<(usize, Vec<ReviewInfo>)> {
+    (1..=file.backups()).find_map(|n| {
+        let data = fs::read(with_suffix(file.path(), &n.to_string())).ok()?;
+        Some((n, file.parse(&data).ok()?))
+    })
+}
+
+/// Moves the file at `path` aside to `<file>.corrupt`, or `<file>.corrupt.<n>`
+/// if taken, returning where to.
+pub fn quarantine(path: &Path) -> io::Result<PathBuf> {
+    set_aside(path, "corrupt")
+}
+
+/// Moves the file at `path` aside to `<file>.<suffix>`, or
+/// `<file>.<suffix>.<n>` if taken, returning where to.
+pub fn set_aside(path: &Path, suffix: &str) -> io::Result<PathBuf> {
+    let mut target = with_suffix(path, suffix);
+    let mut n = 1;
+    while target.exists() {
+        n += 1;
+        target = with_suffix(path, &format!("{}.{}", suffix, n));
+    }
+    fs::rename(path, &target)?;
+
+    Ok(target)
+}
+
+/// Entries sorted by file name, the order they are saved in.
+pub fn sorted(data: &ReviewData) -> Vec<&ReviewInfo> {
+    let mut data: Vec<_> = data.values().collect();
+    data.sort_by_key(|d| &d.file_name);
+
+    data
+}
+
+/// Replaces `path` with `contents` by writing a temp file next to it and
+/// renaming it over, so that a crash leaves either the old or the new file.
+/// The old file is rotated into the `backups` numbered copies first. A file
+/// holding `contents` already is left as is, its backups too.
+pub fn write_atomic(path: &Path, contents: &[u8], backups: usize) -> io::Result<()> {
+    if fs::read(path).is_ok_and(|old| old == contents) {
+        return Ok(());
+    }
+    let attempt = || -> io::Result<()> {
+        let tmp = with_suffix(path, "tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        if backups > 0 && path.exists() {
+            for n in (1..backups).rev() {
+                let from = with_suffix(path, &n.to_string());
+                if from.exists() {
+                    fs::rename(&from, with_suffix(path, &(n + 1).to_string()))?;
+                }
+            }
+            fs::copy(path, with_suffix(path, "1"))?;
+        }
+
+        fs::rename(&tmp, path)
+    };
+
+    attempt().map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+/// `path` with `.<suffix>` appended to its file name.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_atomic_rotates_only_changed_contents() {
+        let dir = std::env::temp_dir().join(format!("memo-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("revs-x.json");
+        let read = |suffix: &str| fs::read_to_string(with_suffix(&path, suffix)).ok();
+
+        for contents in ["a", "b", "b", "c", "c"] {
+            write_atomic(&path, contents.as_bytes(), 2).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "c");
+        assert_eq!(read("1").as_deref(), Some("b"));
+        assert_eq!(read("2").as_deref(), Some("a"));
+        assert_eq!(read("3"), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+//! Consecutive days with at least one review, across every deck.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::store;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Streak {
+    pub current: u32,
+    pub longest: u32,
+    pub last_day: Option<NaiveDate>,
+}
+
+impl Streak {
+    /// `streak.json` in the revs dir.
+    pub fn path(revs_dir: &Path) -> PathBuf {
+        revs_dir.join("streak.json")
+    }
+
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        store::write_atomic(path, json.as_bytes(), 0)
+    }
+
+    /// Counts `day` as a day with reviews, days before the last one are ignored.
+    pub fn record(&mut self, day: NaiveDate) {
+        match self.last_day {
+            Some(last) if day <= last => return,
+            Some(last) if last + Days::new(1) == day => self.current += 1,
+            _ => self.current = 1,
+        }
+        self.last_day = Some(day);
+        self.longest = self.longest.max(self.current);
+    }
+
+    /// The current streak as of `today`, which is still alive if the last
+    /// review was yesterday.
+    pub fn current(&self, today: NaiveDate) -> u32 {
+        match self.last_day {
+            Some(last) if last + Days::new(1) >= today => self.current,
+            _ => 0,
+        }
+    }
+}
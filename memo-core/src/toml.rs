@@ -0,0 +1,655 @@
+//! A small TOML reader covering the subset used by memo's config file.
+//!
+//! Documents are parsed into a `serde_json` map so they can be deserialized
+//! with the same derives as everything else. Dates and times are kept as
+//! plain strings.
+
+use std::fmt;
+
+use serde_json::{Map, Number, Value};
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub fn parse(input: &str) -> Result<Map<String, Value>, ParseError> {
+    Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        line: 1,
+    }
+    .document()
+}
+
+/// Sets `key` of the table at `table` to `value` in the document `input`,
+/// keeping everything else (comments included) as is. A missing table is
+/// appended at the end.
+pub fn set(input: &str, table: &[&str], key: &str, value: &Value) -> Result<String, ParseError> {
+    parse(input)?;
+
+    let lines: Vec<&str> = input.lines().collect();
+    let mut current: Vec<String> = Vec::new();
+    let mut found_table = table.is_empty();
+    // Line after the last entry of the table, where a new key goes.
+    let mut insert_at = if table.is_empty() { Some(0) } else { None };
+    let entry = format!("{} = {}", format_key(key), format_value(value));
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if line.starts_with('[') {
+            current = header(line).unwrap_or_default();
+            if current == table {
+                found_table = true;
+                insert_at = Some(i + 1);
+            }
+        } else if !line.is_empty() && !line.starts_with('#') && current == table {
+            let end = i + continuation(&lines[i..]);
+            if line_key(line).is_some_and(|k| k == [key]) {
+                let mut out: Vec<String> = lines[..i].iter().map(|l| l.to_string()).collect();
+                out.push(entry);
+                out.extend(lines[end + 1..].iter().map(|l| l.to_string()));
+                return Ok(out.join("\n") + "\n");
+            }
+            i = end;
+            insert_at = Some(i + 1);
+        }
+        i += 1;
+    }
+
+    let mut out: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    match insert_at.filter(|_| found_table) {
+        Some(at) => out.insert(at, entry),
+        None => {
+            if out.last().is_some_and(|l| !l.trim().is_empty()) {
+                out.push(String::new());
+            }
+            let path: Vec<String> = table.iter().map(|s| format_key(s)).collect();
+            out.push(format!("[{}]", path.join(".")));
+            out.push(entry);
+        }
+    }
+
+    Ok(out.join("\n") + "\n")
+}
+
+/// A value as written in a document.
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => quote(s),
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(format_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Object(map) => format!(
+            "{{ {} }}",
+            map.iter()
+                .map(|(k, v)| format!("{} = {}", format_key(k), format_value(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Null => "\"\"".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn format_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if bare { key.to_string() } else { quote(key) }
+}
+
+/// `s` as a basic string, with the escapes TOML knows of.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
+
+/// The path of a `[table]` header line.
+fn header(line: &str) -> Option<Vec<String>> {
+    let inner = line.strip_prefix('[')?.trim_start_matches('[');
+    let mut parser = Parser {
+        chars: inner.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+
+    parser.key().ok()
+}
+
+/// The key of a `key = value` line.
+fn line_key(line: &str) -> Option<Vec<String>> {
+    let mut parser = Parser {
+        chars: line.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let key = parser.key().ok()?;
+
+    parser.eat('=').then_some(key)
+}
+
+/// Number of lines after the first that a value starting on it spans, for
+/// arrays and inline tables broken over several lines.
+fn continuation(lines: &[&str]) -> usize {
+    let mut depth = 0i32;
+    for (n, line) in lines.iter().enumerate() {
+        let mut quote = None;
+        for c in line.chars() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), c) if c == q => quote = None,
+                (None, '#') => break,
+                (None, '[' | '{') => depth += 1,
+                (None, ']' | '}') => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth <= 0 {
+            return n;
+        }
+    }
+
+    lines.len() - 1
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn document(&mut self) -> Result<Map<String, Value>, ParseError> {
+        let mut root = Map::new();
+        let mut current = Vec::new();
+
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => break,
+                Some('[') => {
+                    self.bump();
+                    let array = self.eat('[');
+                    self.skip_ws();
+                    let path = self.key()?;
+                    self.skip_ws();
+                    self.expect(']')?;
+                    if array {
+                        self.expect(']')?;
+                        let (last, parent) = path.split_last().unwrap();
+                        let table = self.table_at(&mut root, parent)?;
+                        let entry = table
+                            .entry(last.clone())
+                            .or_insert_with(|| Value::Array(Vec::new()));
+                        match entry {
+                            Value::Array(items) => items.push(Value::Object(Map::new())),
+                            _ => return Err(self.error(format!("`{last}` is not an array"))),
+                        }
+                    } else {
+                        self.table_at(&mut root, &path)?;
+                    }
+                    current = path;
+                }
+                Some(_) => {
+                    let key = self.key()?;
+                    self.skip_ws();
+                    self.expect('=')?;
+                    self.skip_ws();
+                    let value = self.value()?;
+                    let table = self.table_at(&mut root, &current)?;
+                    self.insert(table, &key, value)?;
+                }
+            }
+            self.end_of_line()?;
+        }
+
+        Ok(root)
+    }
+
+    /// Walks (and creates) the tables along `path`, descending into the last
+    /// element of any array of tables on the way.
+    fn table_at<'a>(
+        &self,
+        mut table: &'a mut Map<String, Value>,
+        path: &[String],
+    ) -> Result<&'a mut Map<String, Value>, ParseError> {
+        for segment in path {
+            let entry = table
+                .entry(segment.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            let entry = match entry {
+                Value::Array(items) => match items.last_mut() {
+                    Some(last) => last,
+                    None => return Err(self.error(format!("`{segment}` is an empty array"))),
+                },
+                other => other,
+            };
+            table = match entry {
+                Value::Object(map) => map,
+                _ => return Err(self.error(format!("`{segment}` is not a table"))),
+            };
+        }
+
+        Ok(table)
+    }
+
+    fn insert(
+        &self,
+        table: &mut Map<String, Value>,
+        key: &[String],
+        value: Value,
+    ) -> Result<(), ParseError> {
+        let (last, parent) = key.split_last().unwrap();
+        let table = self.table_at(table, parent)?;
+        if table.contains_key(last) {
+            return Err(self.error(format!("duplicate key `{last}`")));
+        }
+        table.insert(last.clone(), value);
+
+        Ok(())
+    }
+
+    fn key(&mut self) -> Result<Vec<String>, ParseError> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_ws();
+            let part = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        self.bump();
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected a key".to_string()));
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            parts.push(part);
+            self.skip_ws();
+            if !self.eat('.') {
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) => self.scalar(),
+            None => Err(self.error("expected a value".to_string())),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank();
+            if !self.eat(',') {
+                self.expect(']')?;
+                return Ok(Value::Array(items));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, ParseError> {
+        self.expect('{')?;
+        let mut table = Map::new();
+        loop {
+            self.skip_ws();
+            if self.eat('}') {
+                return Ok(Value::Object(table));
+            }
+            let key = self.key()?;
+            self.skip_ws();
+            self.expect('=')?;
+            self.skip_ws();
+            let value = self.value()?;
+            self.insert(&mut table, &key, value)?;
+            self.skip_ws();
+            if !self.eat(',') {
+                self.expect('}')?;
+                return Ok(Value::Object(table));
+            }
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, ',' | ']' | '}' | '#'))
+        {
+            self.bump();
+        }
+        let token: String = self.chars[start..self.pos].iter().collect();
+
+        match token.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            _ => {}
+        }
+
+        // Local dates such as `2025-07-01` stay strings.
+        let bytes = token.as_bytes();
+        if bytes.len() >= 10 && bytes[4] == b'-' && bytes[7] == b'-' {
+            return Ok(Value::String(token));
+        }
+
+        let digits = token.replace('_', "");
+        if let Ok(i) = digits.parse::<i64>() {
+            return Ok(Value::Number(i.into()));
+        }
+        if let Some(n) = digits.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Ok(Value::Number(n));
+        }
+
+        Err(self.error(format!("invalid value `{token}`")))
+    }
+
+    fn basic_string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            let c = self.string_char()?;
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let c = match self.bump() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some(u @ ('u' | 'U')) => {
+                            let len = if u == 'u' { 4 } else { 8 };
+                            let hex: String = (0..len).filter_map(|_| self.bump()).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error(format!("invalid escape `\\{u}{hex}`")))?
+                        }
+                        _ => return Err(self.error("invalid escape sequence".to_string())),
+                    };
+                    out.push(c);
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, ParseError> {
+        self.expect('\'')?;
+        let mut out = String::new();
+        loop {
+            match self.string_char()? {
+                '\'' => return Ok(out),
+                c => out.push(c),
+            }
+        }
+    }
+
+    /// The next char of a string, an error if the line ends first, before
+    /// the newline is read so that it is reported at the string's line.
+    fn string_char(&mut self) -> Result<char, ParseError> {
+        match self.peek() {
+            Some(c) if c != '\n' => {
+                self.bump();
+                Ok(c)
+            }
+            _ => Err(self.error("unterminated string".to_string())),
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), ParseError> {
+        self.skip_ws();
+        if self.peek() == Some('#') {
+            self.skip_comment();
+        }
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("unexpected `{c}`"))),
+        }
+    }
+
+    /// Skips whitespace, newlines and comments.
+    fn skip_blank(&mut self) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => self.skip_comment(),
+                _ => return,
+            }
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\r')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.bump();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{c}`")))
+        }
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            line: self.line,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parses_the_subset() {
+        let doc = parse(
+            r#"
+# comment
+title = "a \"b\"\tc" # trailing
+path = 'C:\vault'
+count = 1_000
+ratio = 0.5
+on = true
+since = 2025-07-01
+
+[vault]
+root = "~/notes"
+
+[decks."408"]
+intervals = [1, 3,
+  7]
+inline = { a = 1, b.c = "x" }
+
+[[hooks]]
+run = "one"
+
+[[hooks]]
+run = "two"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Value::Object(doc),
+            json!({
+                "title": "a \"b\"\tc",
+                "path": "C:\\vault",
+                "count": 1000,
+                "ratio": 0.5,
+                "on": true,
+                "since": "2025-07-01",
+                "vault": { "root": "~/notes" },
+                "decks": {
+                    "408": {
+                        "intervals": [1, 3, 7],
+                        "inline": { "a": 1, "b": { "c": "x" } },
+                    },
+                },
+                "hooks": [{ "run": "one" }, { "run": "two" }],
+            })
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        for (input, line) in [
+            ("a = 1\na = 2\n", 2),
+            ("a = \"open\n", 1),
+            ("\n\nb = nope\n", 3),
+            ("c = 1 2\n", 1),
+            ("[t]\nd = [1, 2\n", 3),
+        ] {
+            let e = parse(input).unwrap_err();
+            assert_eq!(e.line, line, "{:?}: {}", input, e);
+        }
+    }
+
+    #[test]
+    fn set_replaces_a_key_in_place() {
+        let input =
+            "# memo\n[vault]\nroot = \"a\" # old\nname = \"Memo\"\n\n[decks.x]\nweight = 1\n";
+        let out = set(input, &["vault"], "root", &json!("b")).unwrap();
+        assert_eq!(
+            out,
+            "# memo\n[vault]\nroot = \"b\"\nname = \"Memo\"\n\n[decks.x]\nweight = 1\n"
+        );
+    }
+
+    #[test]
+    fn set_replaces_a_value_over_several_lines() {
+        let input = "[d]\nlist = [\n  1,\n  2,\n]\nafter = true\n";
+        let out = set(input, &["d"], "list", &json!([3])).unwrap();
+        assert_eq!(out, "[d]\nlist = [3]\nafter = true\n");
+    }
+
+    #[test]
+    fn set_adds_keys_and_tables() {
+        let input = "top = 1\n\n[vault]\nroot = \"a\"\n\n[other]\n";
+        let out = set(input, &["vault"], "name", &json!("Memo")).unwrap();
+        assert_eq!(
+            out,
+            "top = 1\n\n[vault]\nroot = \"a\"\nname = \"Memo\"\n\n[other]\n"
+        );
+
+        let out = set(&out, &["decks", "40 8"], "weight", &json!(2)).unwrap();
+        assert!(
+            out.ends_with("[other]\n\n[decks.\"40 8\"]\nweight = 2\n"),
+            "{}",
+            out
+        );
+        assert_eq!(parse(&out).unwrap()["decks"]["40 8"]["weight"], json!(2));
+
+        let out = set("", &[], "top", &json!({ "a": [true] })).unwrap();
+        assert_eq!(out, "top = { a = [true] }\n");
+    }
+
+    #[test]
+    fn strings_are_written_with_toml_escapes() {
+        let tricky = "a \"quote\" \\ tab\tline\ncr\r\u{8}\u{c}\u{1b}[0m\u{7f} ü";
+        assert_eq!(
+            format_value(&json!(tricky)),
+            r#""a \"quote\" \\ tab\tline\ncr\r\b\f\u001B[0m\u007F ü""#
+        );
+
+        let out = set("", &["a b"], "k\u{1b}", &json!([tricky])).unwrap();
+        assert_eq!(parse(&out).unwrap()["a b"]["k\u{1b}"], json!([tricky]));
+    }
+
+    #[test]
+    fn set_refuses_an_invalid_document() {
+        assert!(set("a = \n", &[], "b", &json!(1)).is_err());
+    }
+}
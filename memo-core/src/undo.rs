@@ -0,0 +1,200 @@
+//! What the last command changed, kept in `undo.json` in the revs dir so
+//! that `memo undo` can put it back.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::daily::DailyDraw;
+use crate::review::{ReviewData, ReviewInfo};
+use crate::store;
+
+/// The changes of one deck.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Change {
+    pub deck: String,
+
+    /// Entries as they were before, of the files whose data changed.
+    pub previous: Vec<ReviewInfo>,
+
+    /// Files that had no entry before.
+    #[serde(default)]
+    pub added: Vec<String>,
+
+    /// Lines appended to the history.
+    #[serde(default)]
+    pub events: usize,
+
+    /// The draw before, if it changed.
+    #[serde(default)]
+    pub drawn: Option<DailyDraw>,
+}
+
+impl Change {
+    /// The difference between the review data `before` and `after`.
+    pub fn between(deck: &str, before: &ReviewData, after: &ReviewData) -> Self {
+        let mut previous: Vec<ReviewInfo> = before
+            .iter()
+            .filter(|(name, info)| after.get(*name) != Some(info))
+            .map(|(_, info)| info.clone())
+            .collect();
+        previous.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        let mut added: Vec<String> = after
+            .keys()
+            .filter(|name| !before.contains_key(*name))
+            .cloned()
+            .collect();
+        added.sort();
+
+        Self {
+            deck: deck.to_string(),
+            previous,
+            added,
+            events: 0,
+            drawn: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty()
+            && self.added.is_empty()
+            && self.events == 0
+            && self.drawn.is_none()
+    }
+
+    /// Puts the entries of `review_data` back as they were.
+    pub fn revert(&self, review_data: &mut ReviewData) {
+        for name in &self.added {
+            review_data.remove(name);
+        }
+        for info in &self.previous {
+            review_data.insert(info.file_name.clone(), info.clone());
+        }
+    }
+}
+
+/// Number of the command run by this process, see [`begin`].
+static RUN: AtomicU64 = AtomicU64::new(0);
+
+/// Starts a new command in a process running more than one, like a request
+/// to `memo serve`, so that `memo undo` only puts back what it changed.
+pub fn begin() {
+    RUN.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The changes of the last command that saved any, one per deck, in the
+/// order they were saved.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Journal {
+    /// Process of that command and its run in that process, later saves of
+    /// which add to the journal rather than replace it.
+    pub pid: u32,
+    #[serde(default)]
+    pub run: u64,
+    pub changes: Vec<Change>,
+}
+
+impl Journal {
+    /// `undo.json` in the revs dir.
+    pub fn path(revs_dir: &Path) -> PathBuf {
+        revs_dir.join("undo.json")
+    }
+
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Records `change`, dropping what earlier commands recorded.
+    pub fn record(path: &Path, change: Change) -> io::Result<()> {
+        let mut journal = Self::load(path);
+        let run = RUN.load(Ordering::Relaxed);
+        if journal.pid != process::id() || journal.run != run {
+            journal = Self {
+                pid: process::id(),
+                run,
+                changes: Vec::new(),
+            };
+        }
+        journal.changes.push(change);
+
+        let json = serde_json::to_string_pretty(&journal).map_err(io::Error::other)?;
+        store::write_atomic(path, json.as_bytes(), 0)
+    }
+
+    pub fn clear(path: &Path) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(entries: &[(&str, u32)]) -> ReviewData {
+        entries
+            .iter()
+            .map(|(name, count)| {
+                let info = ReviewInfo {
+                    file_name: name.to_string(),
+                    review_count: *count,
+                    ..Default::default()
+                };
+                (name.to_string(), info)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn revert_puts_back_the_data_before() {
+        let before = data(&[("a.md", 1), ("b.md", 2), ("c.md", 3)]);
+        let mut after = data(&[("a.md", 1), ("b.md", 5), ("d.md", 1)]);
+
+        let change = Change::between("408", &before, &after);
+        let previous: Vec<_> = change.previous.iter().map(|i| &i.file_name).collect();
+        assert_eq!(previous, ["b.md", "c.md"]);
+        assert_eq!(change.added, ["d.md"]);
+        assert!(!change.is_empty());
+
+        change.revert(&mut after);
+        assert_eq!(after, before);
+        assert!(Change::between("408", &before, &before).is_empty());
+    }
+
+    #[test]
+    fn journal_is_replaced_by_a_new_run() {
+        let dir = std::env::temp_dir().join(format!("memo-undo-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = Journal::path(&dir);
+        let change = |deck: &str| Change::between(deck, &ReviewData::new(), &data(&[("a.md", 1)]));
+
+        Journal::record(&path, change("101")).unwrap();
+        Journal::record(&path, change("408")).unwrap();
+        let decks: Vec<_> = Journal::load(&path)
+            .changes
+            .into_iter()
+            .map(|c| c.deck)
+            .collect();
+        assert_eq!(decks, ["101", "408"]);
+
+        begin();
+        Journal::record(&path, change("301")).unwrap();
+        let journal = Journal::load(&path);
+        assert_eq!(journal.changes.len(), 1);
+        assert_eq!(journal.changes[0].deck, "301");
+
+        Journal::clear(&path).unwrap();
+        Journal::clear(&path).unwrap();
+        assert!(Journal::load(&path).changes.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
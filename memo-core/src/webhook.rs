@@ -0,0 +1,122 @@
+//! Digests of sessions posted to webhooks, through `curl`.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use chrono::{Days, NaiveDate};
+use serde_json::{Value, json};
+
+use crate::config::{WebhookConfig, WebhookKind};
+use crate::forecast;
+use crate::{Deck, Grade};
+
+/// What a session of a deck reviewed and what falls due the day after.
+pub struct Digest {
+    pub deck: String,
+    pub date: NaiveDate,
+    pub reviewed: Vec<(String, Grade)>,
+    pub due_tomorrow: Vec<String>,
+}
+
+impl Digest {
+    /// The digest of `reviewed` in `deck` on `today`.
+    pub fn new(deck: &Deck, reviewed: Vec<(String, Grade)>, today: NaiveDate) -> Self {
+        let tomorrow = today + Days::new(1);
+        let due_tomorrow = forecast::due_files(deck, today, 2)
+            .remove(&tomorrow)
+            .unwrap_or_default();
+
+        Self {
+            deck: deck.name.clone(),
+            date: today,
+            reviewed,
+            due_tomorrow,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let reviewed: Vec<String> = self
+            .reviewed
+            .iter()
+            .map(|(file, grade)| format!("{} ({})", file, grade))
+            .collect();
+        let mut text = format!(
+            "Reviewed {} file(s) in {} on {}: {}",
+            self.reviewed.len(),
+            self.deck,
+            self.date,
+            reviewed.join(", ")
+        );
+        match self.due_tomorrow.len() {
+            0 => text.push_str("\nNothing due tomorrow."),
+            n => text.push_str(&format!(
+                "\nDue tomorrow, {} file(s): {}",
+                n,
+                self.due_tomorrow.join(", ")
+            )),
+        }
+
+        text
+    }
+
+    fn payload(&self, kind: WebhookKind) -> Value {
+        match kind {
+            WebhookKind::Discord => json!({ "content": self.text() }),
+            WebhookKind::Slack => json!({ "text": self.text() }),
+            WebhookKind::Generic => json!({
+                "deck": self.deck,
+                "date": self.date,
+                "reviewed": self
+                    .reviewed
+                    .iter()
+                    .map(|(file, grade)| json!({ "file": file, "grade": grade }))
+                    .collect::<Vec<_>>(),
+                "due_tomorrow": self.due_tomorrow,
+                "text": self.text(),
+            }),
+        }
+    }
+}
+
+/// Whether `hook` wants the sessions of `deck`.
+pub fn wants(hook: &WebhookConfig, deck: &str) -> bool {
+    hook.decks.is_empty() || hook.decks.iter().any(|d| d == deck)
+}
+
+/// Posts `digest` to `hook`, failing on statuses other than 2xx.
+pub fn post(hook: &WebhookConfig, digest: &Digest) -> io::Result<()> {
+    let body = digest.payload(hook.kind).to_string();
+    let mut child = Command::new("curl")
+        .args([
+            "-sS",
+            "-f",
+            "-K",
+            "-",
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+        ])
+        .args(["--max-time", "10"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run curl: {}", e)))?;
+    // The URL of a hook is its secret, so it goes on stdin with the body
+    // rather than on the command line, like the credentials of email.rs.
+    if let Some(mut stdin) = child.stdin.take() {
+        let quote = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(stdin, "url = \"{}\"", quote(&hook.url))?;
+        writeln!(stdin, "data-binary = \"{}\"", quote(&body))?;
+    }
+
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
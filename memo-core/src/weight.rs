@@ -0,0 +1,117 @@
+use chrono::NaiveDate;
+
+use serde::Serialize;
+
+use crate::review::{Grade, ReviewInfo};
+
+/// Defaults of the deck settings of the same names.
+pub const FILES_PER_DAY: usize = 3;
+pub const MAX_OUT_FACTOR: f64 = 2.0;
+pub const BASIC_WEIGHT: f64 = 10.0;
+pub const MINIMUM_WEIGHT: f64 = 1.0;
+pub const DECAY_RATE: f64 = 0.96;
+/// Retention under which a file is considered due.
+pub const DUE_RETENTION: f64 = 0.5;
+
+/// Days assumed since the last review when a reviewed file has no date,
+/// `factor` times the days it takes to draw every file once.
+pub fn max_out(file_count: usize, files_per_day: usize, factor: f64) -> i64 {
+    (file_count as f64 * factor / files_per_day.max(1) as f64).ceil() as i64
+}
+
+/// Bounds of [`ReviewInfo::stretch`].
+const STRETCH: (f64, f64) = (0.25, 8.0);
+
+/// Days for retention to decay to [`DUE_RETENTION`] at `decay_rate` a day.
+pub fn interval(decay_rate: f64) -> u32 {
+    stretched_interval(decay_rate, 1.0)
+}
+
+/// [`interval`] of a file whose decay is slowed down by `stretch`.
+pub fn stretched_interval(decay_rate: f64, stretch: f64) -> u32 {
+    (DUE_RETENTION.ln() / decay_rate.ln() * stretch)
+        .ceil()
+        .max(1.0) as u32
+}
+
+/// The [`ReviewInfo::stretch`] of a file after a review graded `grade`:
+/// `again` halves it, `hard` shortens and `easy` lengthens it.
+pub fn stretch(stretch: Option<f64>, grade: Grade) -> f64 {
+    let factor = match grade {
+        Grade::Again => 0.5,
+        Grade::Hard => 0.8,
+        Grade::Good => 1.0,
+        Grade::Easy => 1.5,
+    };
+
+    (stretch.unwrap_or(1.0) * factor).clamp(STRETCH.0, STRETCH.1)
+}
+
+/// The terms a weight is made of, as shown by `memo explain`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Breakdown {
+    /// `None` for files never reviewed.
+    pub days_since: Option<i64>,
+
+    /// Estimated chance of recalling the file, for schedulers that have one.
+    pub retention: Option<f64>,
+
+    /// Days since the last review over the interval, for interval schedulers.
+    pub overdue: Option<f64>,
+
+    pub priority: f64,
+    pub penalty: f64,
+
+    /// Weight given by a formula, in place of the terms above.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+}
+
+impl Breakdown {
+    /// A file never reviewed, which all schedulers rank first.
+    pub fn new_file() -> Self {
+        Self {
+            priority: 100.0,
+            ..Default::default()
+        }
+    }
+
+    /// `basic` plus the priority less the penalty, or the formula's value,
+    /// at least `minimum`.
+    pub fn weight(&self, basic: f64, minimum: f64) -> usize {
+        self.value
+            .unwrap_or(basic + self.priority - self.penalty)
+            .max(minimum)
+            .round() as usize
+    }
+}
+
+pub fn weight(info: Option<&ReviewInfo>, today: NaiveDate, max_out: i64, decay_rate: f64) -> usize {
+    breakdown(info, today, max_out, decay_rate).weight(BASIC_WEIGHT, MINIMUM_WEIGHT)
+}
+
+pub fn breakdown(
+    info: Option<&ReviewInfo>,
+    today: NaiveDate,
+    max_out: i64,
+    decay_rate: f64,
+) -> Breakdown {
+    let review_count = info.map_or(0, |i| i.review_count);
+    if review_count == 0 {
+        return Breakdown::new_file();
+    }
+
+    let last_review = info.and_then(|i| i.last_reviewed);
+    let days_since_last = last_review.map_or(max_out, |d| (today - d).num_days().max(0));
+    let stretch = info.and_then(|i| i.stretch).unwrap_or(1.0);
+    let retention = decay_rate.powf(days_since_last as f64 / stretch);
+
+    Breakdown {
+        days_since: Some(days_since_last),
+        retention: Some(retention),
+        overdue: None,
+        priority: (1.0 - retention) * 100.0,
+        penalty: (review_count as f64).ln() * 5.0,
+        value: None,
+    }
+}
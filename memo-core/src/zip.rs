@@ -0,0 +1,108 @@
+//! Writing of zip archives, their entries stored without compression, and
+//! reading of such archives back.
+
+use std::io;
+
+use crate::hash::crc32;
+
+/// A zip archive of `entries`, pairs of names and contents.
+pub fn archive(entries: &[(&str, &[u8])]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+
+    for (name, data) in entries {
+        let (Ok(size), Ok(offset), Ok(name_len)) = (
+            u32::try_from(data.len()),
+            u32::try_from(out.len()),
+            u16::try_from(name.len()),
+        ) else {
+            return Err(io::Error::other(format!("{} is too large for a zip", name)));
+        };
+        let crc = crc32(data);
+
+        // Local file header: version 2.0, no flags, stored, no date.
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        out.extend_from_slice(&crc.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&name_len.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        directory.extend_from_slice(&crc.to_le_bytes());
+        directory.extend_from_slice(&size.to_le_bytes());
+        directory.extend_from_slice(&size.to_le_bytes());
+        directory.extend_from_slice(&name_len.to_le_bytes());
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let count = u16::try_from(entries.len()).map_err(io::Error::other)?;
+    let (Ok(directory_size), Ok(directory_offset)) =
+        (u32::try_from(directory.len()), u32::try_from(out.len()))
+    else {
+        return Err(io::Error::other("too large for a zip"));
+    };
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&directory_size.to_le_bytes());
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+
+    Ok(out)
+}
+
+/// The names and contents of the entries of the zip archive `data`, which
+/// must be stored without compression as [`archive`] does.
+pub fn entries(data: &[u8]) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, what.to_string());
+    let u16_at = |i: usize| -> io::Result<usize> {
+        data.get(i..i + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .ok_or_else(|| invalid("truncated zip"))
+    };
+    let u32_at = |i: usize| -> io::Result<u32> {
+        data.get(i..i + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| invalid("truncated zip"))
+    };
+
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while u32_at(at).is_ok_and(|s| s == 0x0403_4b50) {
+        let (flags, method) = (u16_at(at + 6)?, u16_at(at + 8)?);
+        if method != 0 || flags & 0x8 != 0 {
+            return Err(invalid("compressed zip entries are not supported"));
+        }
+        let crc = u32_at(at + 14)?;
+        let size = u32_at(at + 18)? as usize;
+        let (name_len, extra_len) = (u16_at(at + 26)?, u16_at(at + 28)?);
+        let name_start = at + 30;
+        let data_start = name_start + name_len + extra_len;
+        let (Some(name), Some(contents)) = (
+            data.get(name_start..name_start + name_len),
+            data.get(data_start..data_start + size),
+        ) else {
+            return Err(invalid("truncated zip"));
+        };
+        if crc32(contents) != crc {
+            return Err(invalid("corrupt zip entry"));
+        }
+
+        entries.push((
+            String::from_utf8_lossy(name).into_owned(),
+            contents.to_vec(),
+        ));
+        at = data_start + size;
+    }
+
+    Ok(entries)
+}